@@ -1,12 +1,25 @@
 use crate::{
+    auth::{
+        api_keys::ApiKeyAuthenticator,
+        jwt::JwtAuthenticator,
+        middleware::Authentication,
+        peer_credentials::{record_peer_credentials, PeerCredentialsAuthenticator},
+        RouteScopes,
+    },
     config::models::ApiBind,
-    handlers,
-    persistency::traits::{PostRepository, TagRepository},
+    handlers::{self, preconditions::ReadCacheControl},
+    models::Scope,
+    persistency::{
+        posts::models::SavePostOptions,
+        traits::{ApiKeyRepository, AuthorRepository, PostRepository, TagRepository},
+    },
+    utils::unix::{bind_unix_socket, SocketPermissions},
 };
 use actix_web::{
     dev::{Service, ServiceRequest},
     web, App, HttpServer,
 };
+use std::path::Path;
 use tracing::info;
 
 fn log_request(req: &ServiceRequest) {
@@ -20,35 +33,143 @@ fn log_request(req: &ServiceRequest) {
     info!("{method} {uri_path} | {peer_addr}");
 }
 
+/// Server-wide settings of the API, from `Config`.
+#[derive(Debug, Clone)]
+pub struct ApiOptions {
+    /// Defaults of the `SavePostOptions` a request does not override.
+    pub save_post_defaults: SavePostOptions,
+    /// `Cache-Control` of post and tag reads.
+    pub read_cache_control: String,
+    /// Reject requests without credentials.
+    pub require_auth: bool,
+    /// Accept bearer tokens besides API keys.
+    pub jwt: Option<JwtAuthenticator>,
+    /// Owner, group and mode of the unix socket.
+    pub socket_permissions: SocketPermissions,
+    /// Let trusted unix socket peers without credentials write, and others read when configured.
+    pub peer_credentials: Option<PeerCredentialsAuthenticator>,
+}
+
+/// Scopes clients need on each resource. The trash holds drafts as well, so reading it needs the
+/// write scope of posts.
+const ROUTE_SCOPES: &[RouteScopes] = &[
+    RouteScopes {
+        prefix: "/api/v1/posts",
+        read: Scope::PostsRead,
+        write: Scope::PostsWrite,
+    },
+    RouteScopes {
+        prefix: "/api/v1/trash",
+        read: Scope::PostsWrite,
+        write: Scope::PostsWrite,
+    },
+    RouteScopes {
+        prefix: "/api/v1/tags",
+        read: Scope::TagsRead,
+        write: Scope::TagsWrite,
+    },
+    RouteScopes {
+        prefix: "/api/v1/authors",
+        read: Scope::AuthorsRead,
+        write: Scope::AuthorsWrite,
+    },
+];
+
 pub async fn initialize_api<
     PR: PostRepository + Clone + Send + 'static,
     TR: TagRepository + Clone + Send + 'static,
+    AR: AuthorRepository + Clone + Send + 'static,
+    KR: ApiKeyRepository + Clone + Send + 'static,
 >(
-    (post_repository, tag_repository): (PR, TR),
+    (post_repository, tag_repository, author_repository, api_key_repository): (PR, TR, AR, KR),
     api_bind: ApiBind,
+    options: ApiOptions,
 ) -> anyhow::Result<()> {
+    let socket_permissions = options.socket_permissions;
     let server = HttpServer::new(move || {
         let post_repository = post_repository.clone();
         let tag_repository = tag_repository.clone();
+        let author_repository = author_repository.clone();
 
         App::new()
+            .wrap(Authentication::new(
+                (
+                    (
+                        ApiKeyAuthenticator::new(api_key_repository.clone()),
+                        options.jwt.clone(),
+                    ),
+                    options.peer_credentials.clone(),
+                ),
+                ROUTE_SCOPES,
+                options.require_auth,
+            ))
             .wrap_fn(|req, srv| {
                 log_request(&req);
                 srv.call(req)
             })
+            .app_data(web::JsonConfig::default().error_handler(handlers::errors::json_error))
+            .app_data(web::QueryConfig::default().error_handler(handlers::errors::query_error))
+            .app_data(web::PathConfig::default().error_handler(handlers::errors::path_error))
             .app_data(web::Data::new(post_repository))
             .app_data(web::Data::new(tag_repository))
+            .app_data(web::Data::new(author_repository))
+            .app_data(web::Data::new(options.save_post_defaults))
+            .app_data(web::Data::new(ReadCacheControl(
+                options.read_cache_control.clone(),
+            )))
             .service(
                 web::resource("/api/v1/posts")
                     .route(web::post().to(handlers::posts::create_post::<PR>))
                     .route(web::get().to(handlers::posts::find_all_posts::<PR>)),
             )
+            .service(
+                web::resource("/api/v1/posts/search")
+                    .route(web::get().to(handlers::posts::search_posts::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/posts/by-slug/{slug}")
+                    .route(web::get().to(handlers::posts::get_post_by_slug::<PR>)),
+            )
             .service(
                 web::resource("/api/v1/posts/{id}")
                     .route(web::get().to(handlers::posts::get_post::<PR>))
                     .route(web::put().to(handlers::posts::update_post::<PR>))
+                    .route(web::patch().to(handlers::posts::patch_post::<PR>))
                     .route(web::delete().to(handlers::posts::delete_post::<PR>)),
             )
+            .service(
+                web::resource("/api/v1/posts/{id}/tags/{name}")
+                    .route(web::put().to(handlers::posts::add_post_tag::<PR>))
+                    .route(web::delete().to(handlers::posts::remove_post_tag::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/posts/{id}/revisions")
+                    .route(web::get().to(handlers::posts::find_post_revisions::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/posts/{id}/revisions/{revision}")
+                    .route(web::get().to(handlers::posts::get_post_revision::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/posts/{id}/revisions/{revision}/diff")
+                    .route(web::get().to(handlers::posts::diff_post_revisions::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/posts/{id}/revisions/{revision}/restore")
+                    .route(web::post().to(handlers::posts::restore_post_revision::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/trash")
+                    .route(web::get().to(handlers::posts::find_deleted_posts::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/trash/{id}")
+                    .route(web::delete().to(handlers::posts::purge_post::<PR>)),
+            )
+            .service(
+                web::resource("/api/v1/trash/{id}/restore")
+                    .route(web::post().to(handlers::posts::restore_deleted_post::<PR>)),
+            )
             .service(
                 web::resource("/api/v1/tags")
                     .route(web::get().to(handlers::tags::find_all_tags::<TR>))
@@ -58,12 +179,28 @@ pub async fn initialize_api<
                 web::resource("/api/v1/tags/{name}")
                     .route(web::get().to(handlers::tags::get_tag::<TR>))
                     .route(web::put().to(handlers::tags::update_tag::<TR>))
+                    .route(web::patch().to(handlers::tags::patch_tag::<TR>))
                     .route(web::delete().to(handlers::tags::delete_tag::<TR>)),
             )
-    });
+            .service(
+                web::resource("/api/v1/authors")
+                    .route(web::get().to(handlers::authors::find_all_authors::<AR>))
+                    .route(web::post().to(handlers::authors::create_author::<AR>)),
+            )
+            .service(
+                web::resource("/api/v1/authors/{name}")
+                    .route(web::get().to(handlers::authors::get_author::<AR>))
+                    .route(web::put().to(handlers::authors::update_author::<AR>))
+                    .route(web::patch().to(handlers::authors::patch_author::<AR>))
+                    .route(web::delete().to(handlers::authors::delete_author::<AR>)),
+            )
+    })
+    .on_connect(record_peer_credentials);
 
     let server = match api_bind {
-        ApiBind::UnixSocket(path) => server.bind_uds(path)?,
+        ApiBind::UnixSocket(path) => {
+            server.listen_uds(bind_unix_socket(Path::new(&path), socket_permissions)?)?
+        }
         ApiBind::Tcp(address) => server.bind(address)?,
     };
 
@@ -71,3 +208,97 @@ pub async fn initialize_api<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::api_keys::{hash_api_key, API_KEY_HEADER},
+        models::PostStatus,
+        persistency::{
+            api_keys::{memory_api_keys_repository::MemoryApiKeysRepository, models::NewApiKey},
+            authors::memory_authors_repository::MemoryAuthorsRepository,
+            memory::MemoryDatabase,
+            models::Precondition,
+            posts::{memory_posts_repository::MemoryPostsRepository, models::NewPost},
+            tags::memory_tags_repository::MemoryTagsRepository,
+        },
+    };
+    use actix_web::{http::StatusCode, test::TestRequest};
+
+    type Posts = MemoryPostsRepository<MemoryTagsRepository, MemoryAuthorsRepository>;
+
+    #[actix_web::test]
+    async fn test_only_writers_read_the_trash() {
+        let db = MemoryDatabase::default();
+        let posts = MemoryPostsRepository::new(
+            db.clone(),
+            MemoryTagsRepository::new(db.clone()),
+            MemoryAuthorsRepository::new(db.clone()),
+        );
+        let api_keys = MemoryApiKeysRepository::new(db);
+        for (name, scope) in [("reader", Scope::PostsRead), ("editor", Scope::PostsWrite)] {
+            api_keys
+                .create(NewApiKey {
+                    name: name.to_string(),
+                    key_hash: hash_api_key(&format!("iem_{name}")),
+                    scopes: vec![scope],
+                    author: None,
+                })
+                .await
+                .unwrap();
+        }
+        let draft = posts
+            .create(
+                NewPost {
+                    slug: None,
+                    title: "Draft".to_string(),
+                    content: "Not yet".to_string(),
+                    tags: Default::default(),
+                    author: None,
+                    status: Some(PostStatus::Draft),
+                    publish_at: None,
+                },
+                SavePostOptions::default(),
+            )
+            .await
+            .unwrap();
+        posts.delete(&draft.id, Precondition::ANY).await.unwrap();
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(Authentication::new(
+                    ApiKeyAuthenticator::new(api_keys),
+                    ROUTE_SCOPES,
+                    false,
+                ))
+                .app_data(web::Data::new(posts))
+                .route(
+                    "/api/v1/trash",
+                    web::get().to(handlers::posts::find_deleted_posts::<Posts>),
+                ),
+        )
+        .await;
+
+        let list = |key: Option<&str>| {
+            let mut req = TestRequest::get().uri("/api/v1/trash");
+            if let Some(key) = key {
+                req = req.insert_header((API_KEY_HEADER, key.to_string()));
+            }
+            actix_web::test::call_service(&app, req.to_request())
+        };
+
+        let anonymous = list(None).await;
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        let body = actix_web::test::read_body(anonymous).await;
+        assert!(!String::from_utf8_lossy(&body).contains("Not yet"));
+        assert_eq!(
+            list(Some("iem_reader")).await.status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let editor = list(Some("iem_editor")).await;
+        assert_eq!(editor.status(), StatusCode::OK);
+        let body = actix_web::test::read_body(editor).await;
+        assert!(String::from_utf8_lossy(&body).contains("Not yet"));
+    }
+}
@@ -0,0 +1,83 @@
+use super::{errors::AuthError, Authenticator, Identity};
+use crate::persistency::traits::ApiKeyRepository;
+use actix_web::dev::ServiceRequest;
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+/// Prefix of every API key, which makes leaked keys easy to recognise.
+pub const API_KEY_PREFIX: &str = "iem_";
+
+/// Header carrying the API key of a request.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// New random API key, e.g. `iem_3f9c…`, carrying 256 bits of entropy.
+pub fn generate_api_key() -> String {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+
+    format!("{API_KEY_PREFIX}{}", hex::encode(secret))
+}
+
+/// Hash stored in place of an API key. Keys are random and long enough that a plain SHA-256
+/// cannot be brute forced, so there is no need for a slow password hash.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Authenticates requests by the API key in their `X-Api-Key` header.
+#[derive(Clone)]
+pub struct ApiKeyAuthenticator<K> {
+    api_keys: K,
+}
+
+impl<K> ApiKeyAuthenticator<K> {
+    pub fn new(api_keys: K) -> Self {
+        Self { api_keys }
+    }
+}
+
+impl<K: ApiKeyRepository> Authenticator for ApiKeyAuthenticator<K> {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError> {
+        let Some(key) = req.headers().get(API_KEY_HEADER) else {
+            return Ok(None);
+        };
+        let key = key.to_str().map_err(|_| AuthError::InvalidApiKey)?;
+
+        let api_key = self
+            .api_keys
+            .find_by_hash(&hash_api_key(key))
+            .await?
+            .ok_or(AuthError::InvalidApiKey)?;
+        debug!("Authenticated with API key {}", api_key.name);
+
+        Ok(Some(Identity {
+            scopes: api_key.scopes,
+            author: api_key.author,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_prefixed_and_unique() {
+        let first = generate_api_key();
+        let second = generate_api_key();
+
+        assert!(first.starts_with(API_KEY_PREFIX));
+        assert_eq!(first.len(), API_KEY_PREFIX.len() + 64);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_hash_is_stable_and_hides_the_key() {
+        let key = generate_api_key();
+
+        assert_eq!(hash_api_key(&key), hash_api_key(&key));
+        assert_ne!(hash_api_key(&key), hash_api_key(&generate_api_key()));
+        assert!(!hash_api_key(&key).contains(&key[API_KEY_PREFIX.len()..]));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{models::Scope, persistency::api_keys::errors::ApiKeyRepositoryError};
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("Authentication is required")]
+    MissingCredentials,
+
+    #[error("Invalid API key")]
+    InvalidApiKey,
+
+    #[error("Invalid bearer token: {0}")]
+    InvalidToken(String),
+
+    #[error("Missing scope: {0}")]
+    InsufficientScope(Scope),
+
+    #[error("Failed to look up API key: {0}")]
+    ApiKeyLookup(#[from] ApiKeyRepositoryError),
+}
+
+#[derive(Error, Debug)]
+pub enum JwtSetupError {
+    #[error("Failed to read JWKS file {0}: {1}")]
+    JwksRead(PathBuf, io::Error),
+
+    #[error("Invalid JWKS file {0}: {1}")]
+    JwksParse(PathBuf, serde_json::Error),
+
+    #[error("JWKS has no key able to verify signatures")]
+    NoJwksKeys,
+}
@@ -0,0 +1,410 @@
+use super::{
+    errors::{AuthError, JwtSetupError},
+    Authenticator, Identity,
+};
+use crate::{
+    config::models::{JwtKeySource, JwtOptions},
+    models::Scope,
+};
+use actix_web::{dev::ServiceRequest, http::header::AUTHORIZATION};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{JwkSet, PublicKeyUse},
+    Algorithm, DecodingKey, Validation,
+};
+use serde_json::{Map, Value};
+use std::{fmt, fs, sync::Arc};
+use tracing::{debug, warn};
+
+/// Algorithms accepted for tokens signed with a shared secret.
+const HMAC_ALGORITHMS: [Algorithm; 3] = [Algorithm::HS256, Algorithm::HS384, Algorithm::HS512];
+
+/// Authenticates requests by the JSON Web Token in their `Authorization: Bearer` header.
+///
+/// Tokens must be signed with the configured secret or a key of the configured JWKS, and must
+/// not be expired. Their scopes and author are read from the configured claims.
+#[derive(Clone)]
+pub struct JwtAuthenticator {
+    keys: Arc<Keys>,
+    options: Arc<JwtOptions>,
+}
+
+enum Keys {
+    Secret(DecodingKey),
+    Jwks(Vec<JwksKey>),
+}
+
+struct JwksKey {
+    id: Option<String>,
+    /// Algorithm pinned by the key, otherwise the one of the token is used.
+    algorithm: Option<Algorithm>,
+    key: DecodingKey,
+}
+
+impl fmt::Debug for JwtAuthenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtAuthenticator")
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JwtAuthenticator {
+    /// Authenticator verifying tokens as `options` say, reading the JWKS file if there is one.
+    pub fn new(options: JwtOptions) -> Result<Self, JwtSetupError> {
+        let keys = match &options.key {
+            JwtKeySource::Secret(secret) => {
+                Keys::Secret(DecodingKey::from_secret(secret.as_bytes()))
+            }
+            JwtKeySource::JwksFile(path) => {
+                let jwks = fs::read_to_string(path)
+                    .map_err(|error| JwtSetupError::JwksRead(path.clone(), error))?;
+                let jwks: JwkSet = serde_json::from_str(&jwks)
+                    .map_err(|error| JwtSetupError::JwksParse(path.clone(), error))?;
+
+                Keys::Jwks(jwks_keys(jwks)?)
+            }
+        };
+
+        Ok(Self {
+            keys: Arc::new(keys),
+            options: Arc::new(options),
+        })
+    }
+
+    /// Key verifying `token` and the validation it must pass.
+    fn key_for(&self, token: &str) -> Result<(&DecodingKey, Validation), AuthError> {
+        let header =
+            decode_header(token).map_err(|error| AuthError::InvalidToken(error.to_string()))?;
+
+        let (key, mut validation) = match self.keys.as_ref() {
+            Keys::Secret(key) => {
+                let mut validation = Validation::new(Algorithm::HS256);
+                validation.algorithms = HMAC_ALGORITHMS.to_vec();
+                (key, validation)
+            }
+            Keys::Jwks(keys) => {
+                let key = match &header.kid {
+                    Some(id) => keys.iter().find(|key| key.id.as_ref() == Some(id)),
+                    None if keys.len() == 1 => keys.first(),
+                    None => None,
+                }
+                .ok_or_else(|| AuthError::InvalidToken("Unknown signing key".to_string()))?;
+                (
+                    &key.key,
+                    Validation::new(key.algorithm.unwrap_or(header.alg)),
+                )
+            }
+        };
+
+        if let Some(issuer) = &self.options.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.options.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        Ok((key, validation))
+    }
+
+    /// Identity carried by the claims of a verified token.
+    fn identity(&self, claims: &Map<String, Value>) -> Result<Identity, AuthError> {
+        let scopes = match claims.get(&self.options.scopes_claim) {
+            Some(Value::String(scopes)) => {
+                scopes.split_whitespace().filter_map(parse_scope).collect()
+            }
+            Some(Value::Array(scopes)) => scopes
+                .iter()
+                .filter_map(Value::as_str)
+                .filter_map(parse_scope)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let author = match &self.options.author_claim {
+            Some(claim) => match claims.get(claim) {
+                Some(Value::String(author)) => Some(author.clone()),
+                _ => {
+                    return Err(AuthError::InvalidToken(format!(
+                        "Missing string claim: {claim}"
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        Ok(Identity { scopes, author })
+    }
+}
+
+impl Authenticator for JwtAuthenticator {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError> {
+        let Some(token) = bearer_token(req) else {
+            return Ok(None);
+        };
+
+        let (key, validation) = self.key_for(token)?;
+        let claims = decode::<Map<String, Value>>(token, key, &validation)
+            .map_err(|error| AuthError::InvalidToken(error.to_string()))?
+            .claims;
+        let identity = self.identity(&claims)?;
+        let subject = claims.get("sub").and_then(Value::as_str);
+        debug!("Authenticated with bearer token of {subject:?}");
+
+        Ok(Some(identity))
+    }
+}
+
+/// Token of an `Authorization: Bearer <token>` header, `None` for other schemes.
+fn bearer_token(req: &ServiceRequest) -> Option<&str> {
+    let header = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = header.split_once(' ')?;
+
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then_some(token.trim())
+}
+
+/// Scope named `scope`, ignoring scopes of other services sharing the token.
+fn parse_scope(scope: &str) -> Option<Scope> {
+    scope.parse().ok()
+}
+
+/// Keys of `jwks` usable to verify signatures.
+fn jwks_keys(jwks: JwkSet) -> Result<Vec<JwksKey>, JwtSetupError> {
+    let keys: Vec<_> = jwks
+        .keys
+        .iter()
+        .filter(|jwk| jwk.common.public_key_use != Some(PublicKeyUse::Encryption))
+        .filter_map(|jwk| {
+            let id = jwk.common.key_id.clone();
+            let algorithm = match jwk.common.key_algorithm {
+                Some(algorithm) => match algorithm.to_string().parse() {
+                    Ok(algorithm) => Some(algorithm),
+                    Err(_) => {
+                        warn!("Ignoring JWKS key {id:?}: {algorithm} does not sign tokens");
+                        return None;
+                    }
+                },
+                None => None,
+            };
+
+            match DecodingKey::from_jwk(jwk) {
+                Ok(key) => Some(JwksKey { id, algorithm, key }),
+                Err(error) => {
+                    warn!("Ignoring JWKS key {id:?}: {error}");
+                    None
+                }
+            }
+        })
+        .collect();
+
+    match keys.is_empty() {
+        true => Err(JwtSetupError::NoJwksKeys),
+        false => Ok(keys),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const SECRET: &str = "correct horse battery staple";
+
+    fn options(key: JwtKeySource) -> JwtOptions {
+        JwtOptions {
+            key,
+            issuer: Some("https://sso.example.com".to_string()),
+            audience: None,
+            scopes_claim: "scope".to_string(),
+            author_claim: Some("preferred_username".to_string()),
+        }
+    }
+
+    fn claims() -> Value {
+        json!({
+            "sub": "1234",
+            "iss": "https://sso.example.com",
+            "aud": "iemanja",
+            "exp": Utc::now().timestamp() + 3600,
+            "scope": "openid posts:read posts:write",
+            "preferred_username": "ana",
+        })
+    }
+
+    fn token(header: Header, claims: &Value, secret: &str) -> String {
+        encode(
+            &header,
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    async fn authenticate(
+        authenticator: &JwtAuthenticator,
+        token: &str,
+    ) -> Result<Option<Identity>, AuthError> {
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, format!("Bearer {token}")))
+            .to_srv_request();
+
+        authenticator.authenticate(&req).await
+    }
+
+    fn secret_authenticator() -> JwtAuthenticator {
+        JwtAuthenticator::new(options(JwtKeySource::Secret(SECRET.to_string()))).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_valid_tokens_map_claims_to_identity() {
+        let authenticator = secret_authenticator();
+        let token = token(Header::default(), &claims(), SECRET);
+
+        assert_eq!(
+            authenticate(&authenticator, &token).await.unwrap(),
+            Some(Identity {
+                scopes: vec![Scope::PostsRead, Scope::PostsWrite],
+                author: Some("ana".to_string()),
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_scopes_claim_may_be_an_array() {
+        let mut claims = claims();
+        claims["roles"] = json!(["tags:read", "billing:admin"]);
+        let authenticator = JwtAuthenticator::new(JwtOptions {
+            scopes_claim: "roles".to_string(),
+            ..options(JwtKeySource::Secret(SECRET.to_string()))
+        })
+        .unwrap();
+        let token = token(Header::default(), &claims, SECRET);
+
+        let identity = authenticate(&authenticator, &token).await.unwrap().unwrap();
+        assert_eq!(identity.scopes, vec![Scope::TagsRead]);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_tokens_are_rejected() {
+        let authenticator = secret_authenticator();
+
+        let mut expired = claims();
+        expired["exp"] = json!(Utc::now().timestamp() - 3600);
+        let mut other_issuer = claims();
+        other_issuer["iss"] = json!("https://evil.example.com");
+        let mut anonymous = claims();
+        anonymous
+            .as_object_mut()
+            .unwrap()
+            .remove("preferred_username");
+
+        for token in [
+            token(Header::default(), &claims(), "wrong secret"),
+            token(Header::default(), &expired, SECRET),
+            token(Header::default(), &other_issuer, SECRET),
+            token(Header::default(), &anonymous, SECRET),
+            "not.a.token".to_string(),
+        ] {
+            assert!(matches!(
+                authenticate(&authenticator, &token).await,
+                Err(AuthError::InvalidToken(_))
+            ));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_audience_is_checked_when_configured() {
+        let token = token(Header::default(), &claims(), SECRET);
+        let authenticator = |audience: &str| {
+            JwtAuthenticator::new(JwtOptions {
+                audience: Some(audience.to_string()),
+                ..options(JwtKeySource::Secret(SECRET.to_string()))
+            })
+            .unwrap()
+        };
+
+        assert!(authenticate(&authenticator("iemanja"), &token)
+            .await
+            .is_ok());
+        assert!(matches!(
+            authenticate(&authenticator("other"), &token).await,
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_requests_without_bearer_token_are_not_identified() {
+        let authenticator = secret_authenticator();
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, "Basic YW5hOnNlY3JldA=="))
+            .to_srv_request();
+
+        assert_eq!(authenticator.authenticate(&req).await.unwrap(), None);
+        assert_eq!(
+            authenticator
+                .authenticate(&TestRequest::default().to_srv_request())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_jwks_keys_are_found_by_key_id() {
+        let jwks = json!({
+            "keys": [
+                { "kty": "oct", "kid": "old", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode("old secret") },
+                { "kty": "oct", "kid": "new", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode(SECRET) },
+            ]
+        });
+        let path = std::env::temp_dir().join(format!("iemanjad-jwks-{}.json", std::process::id()));
+        fs::write(&path, jwks.to_string()).unwrap();
+        let authenticator = JwtAuthenticator::new(options(JwtKeySource::JwksFile(path.clone())));
+        fs::remove_file(&path).unwrap();
+        let authenticator = authenticator.unwrap();
+
+        let signed_with = |kid: Option<&str>| {
+            let header = Header {
+                kid: kid.map(str::to_string),
+                ..Default::default()
+            };
+            token(header, &claims(), SECRET)
+        };
+
+        assert!(authenticate(&authenticator, &signed_with(Some("new")))
+            .await
+            .is_ok());
+        for token in [
+            signed_with(Some("old")),
+            signed_with(Some("gone")),
+            signed_with(None),
+        ] {
+            assert!(matches!(
+                authenticate(&authenticator, &token).await,
+                Err(AuthError::InvalidToken(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unreadable_jwks_files_fail_setup() {
+        let path = PathBuf::from("/nonexistent/iemanjad/jwks.json");
+
+        assert!(matches!(
+            JwtAuthenticator::new(options(JwtKeySource::JwksFile(path))),
+            Err(JwtSetupError::JwksRead(_, _))
+        ));
+        assert!(matches!(
+            jwks_keys(JwkSet { keys: Vec::new() }),
+            Err(JwtSetupError::NoJwksKeys)
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+use super::{errors::AuthError, required_scope, Authenticator, RouteScopes};
+use crate::{
+    handlers::ownership::{AuthenticatedAuthor, GrantedScopes},
+    models::Scope,
+};
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    HttpMessage, ResponseError,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+/// Authenticates requests with `authenticator` and checks that their identity grants the scope
+/// of the route.
+///
+/// Requests without credentials may only read, and not even that when `require_credentials` is
+/// set; anything else fails as missing credentials. Requests get the scopes they are granted as
+/// `GrantedScopes` in their extensions, and authenticated requests acting as an author an
+/// `AuthenticatedAuthor` as well.
+#[derive(Clone)]
+pub struct Authentication<A> {
+    authenticator: A,
+    routes: &'static [RouteScopes],
+    require_credentials: bool,
+}
+
+impl<A> Authentication<A> {
+    pub fn new(
+        authenticator: A,
+        routes: &'static [RouteScopes],
+        require_credentials: bool,
+    ) -> Self {
+        Self {
+            authenticator,
+            routes,
+            require_credentials,
+        }
+    }
+}
+
+impl<S, B, A> Transform<S, ServiceRequest> for Authentication<A>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+    A: Authenticator + Clone + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = AuthenticationMiddleware<S, A>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthenticationMiddleware {
+            service: Rc::new(service),
+            authentication: self.clone(),
+        }))
+    }
+}
+
+pub struct AuthenticationMiddleware<S, A> {
+    service: Rc<S>,
+    authentication: Authentication<A>,
+}
+
+impl<A: Authenticator> Authentication<A> {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<(), AuthError> {
+        let Some(identity) = self.authenticator.authenticate(req).await? else {
+            self.authorize_anonymous(req)?;
+            req.extensions_mut()
+                .insert(GrantedScopes(Scope::READ.to_vec()));
+            return Ok(());
+        };
+
+        if let Some(scope) = required_scope(self.routes, req.method(), req.path()) {
+            if !identity.scopes.contains(&scope) {
+                return Err(AuthError::InsufficientScope(scope));
+            }
+        }
+        if let Some(author) = identity.author {
+            req.extensions_mut().insert(AuthenticatedAuthor(author));
+        }
+        req.extensions_mut().insert(GrantedScopes(identity.scopes));
+
+        Ok(())
+    }
+
+    fn authorize_anonymous(&self, req: &ServiceRequest) -> Result<(), AuthError> {
+        match required_scope(self.routes, req.method(), req.path()) {
+            _ if self.require_credentials => Err(AuthError::MissingCredentials),
+            Some(scope) if !Scope::READ.contains(&scope) => Err(AuthError::MissingCredentials),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S, B, A> Service<ServiceRequest> for AuthenticationMiddleware<S, A>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+    A: Authenticator + Clone + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let authentication = self.authentication.clone();
+
+        Box::pin(async move {
+            match authentication.authenticate(&req).await {
+                Ok(()) => service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body),
+                Err(error) => {
+                    let response = error.error_response();
+                    Ok(req.into_response(response).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::api_keys::{hash_api_key, ApiKeyAuthenticator, API_KEY_HEADER},
+        persistency::{
+            api_keys::{memory_api_keys_repository::MemoryApiKeysRepository, models::NewApiKey},
+            memory::MemoryDatabase,
+            traits::ApiKeyRepository,
+        },
+    };
+    use actix_web::{
+        http::{Method, StatusCode},
+        test, web, App, HttpRequest, HttpResponse,
+    };
+
+    const ROUTES: &[RouteScopes] = &[RouteScopes {
+        prefix: "/posts",
+        read: Scope::PostsRead,
+        write: Scope::PostsWrite,
+    }];
+
+    /// Answers with the author the request is authenticated as.
+    async fn echo_author(req: HttpRequest) -> HttpResponse {
+        let author = req
+            .extensions()
+            .get::<AuthenticatedAuthor>()
+            .map(|AuthenticatedAuthor(author)| author.clone())
+            .unwrap_or_default();
+
+        HttpResponse::Ok().body(author)
+    }
+
+    async fn call(require: bool, method: Method, key: Option<&str>) -> (StatusCode, String) {
+        let api_keys = MemoryApiKeysRepository::new(MemoryDatabase::default());
+        api_keys
+            .create(NewApiKey {
+                name: "reader".to_string(),
+                key_hash: hash_api_key("iem_reader"),
+                scopes: vec![Scope::PostsRead],
+                author: Some("ana".to_string()),
+            })
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(Authentication::new(
+                    ApiKeyAuthenticator::new(api_keys),
+                    ROUTES,
+                    require,
+                ))
+                .route("/posts", web::to(echo_author)),
+        )
+        .await;
+
+        let mut req = test::TestRequest::default().method(method).uri("/posts");
+        if let Some(key) = key {
+            req = req.insert_header((API_KEY_HEADER, key));
+        }
+        let response = test::call_service(&app, req.to_request()).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_requests_without_key_may_only_read_unless_required() {
+        assert_eq!(
+            call(false, Method::GET, None).await,
+            (StatusCode::OK, String::new())
+        );
+
+        let (status, body) = call(true, Method::GET, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains(r#""code":"missing_credentials""#));
+    }
+
+    #[actix_web::test]
+    async fn test_requests_without_key_may_not_write() {
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            let (status, body) = call(false, method, None).await;
+
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(body.contains(r#""code":"missing_credentials""#));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_keys_are_rejected() {
+        let (status, body) = call(false, Method::GET, Some("iem_unknown")).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains(r#""code":"invalid_api_key""#));
+    }
+
+    #[actix_web::test]
+    async fn test_keys_need_the_scope_of_the_route() {
+        assert_eq!(
+            call(true, Method::GET, Some("iem_reader")).await,
+            (StatusCode::OK, "ana".to_string())
+        );
+
+        let (status, body) = call(true, Method::POST, Some("iem_reader")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains(r#""code":"insufficient_scope""#));
+        assert!(body.contains("posts:write"));
+    }
+}
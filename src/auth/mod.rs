@@ -0,0 +1,117 @@
+use crate::models::Scope;
+use actix_web::{dev::ServiceRequest, http::Method};
+use errors::AuthError;
+
+pub mod api_keys;
+pub mod errors;
+pub mod jwt;
+pub mod middleware;
+pub mod peer_credentials;
+
+/// Scopes a client needs on the routes under `prefix`: `read` for `GET` and `HEAD`, `write` for
+/// every other method.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteScopes {
+    pub prefix: &'static str,
+    pub read: Scope,
+    pub write: Scope,
+}
+
+/// What an authenticated client may do, and who it acts as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub scopes: Vec<Scope>,
+    pub author: Option<String>,
+}
+
+/// A way for clients to prove who they are.
+pub trait Authenticator {
+    /// Identity carried by the credentials of `req`, `None` when it has no credentials this
+    /// authenticator understands, or an error when its credentials are invalid.
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError>;
+}
+
+/// An authenticator that may be disabled by configuration.
+impl<A: Authenticator> Authenticator for Option<A> {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError> {
+        match self {
+            Some(authenticator) => authenticator.authenticate(req).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// Two authenticators tried in order: the first one recognising the credentials decides.
+impl<A: Authenticator, B: Authenticator> Authenticator for (A, B) {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError> {
+        match self.0.authenticate(req).await? {
+            Some(identity) => Ok(Some(identity)),
+            None => self.1.authenticate(req).await,
+        }
+    }
+}
+
+/// Scope needed to call `method` on `path`, or `None` when no route in `routes` covers it.
+pub fn required_scope(routes: &[RouteScopes], method: &Method, path: &str) -> Option<Scope> {
+    let route = routes.iter().find(|route| {
+        path.strip_prefix(route.prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })?;
+
+    if method == Method::GET || method == Method::HEAD {
+        Some(route.read)
+    } else {
+        Some(route.write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTES: &[RouteScopes] = &[
+        RouteScopes {
+            prefix: "/api/v1/posts",
+            read: Scope::PostsRead,
+            write: Scope::PostsWrite,
+        },
+        RouteScopes {
+            prefix: "/api/v1/tags",
+            read: Scope::TagsRead,
+            write: Scope::TagsWrite,
+        },
+    ];
+
+    #[test]
+    fn test_reads_and_writes_need_their_own_scope() {
+        assert_eq!(
+            required_scope(ROUTES, &Method::GET, "/api/v1/posts"),
+            Some(Scope::PostsRead)
+        );
+        assert_eq!(
+            required_scope(ROUTES, &Method::HEAD, "/api/v1/tags/rust"),
+            Some(Scope::TagsRead)
+        );
+        assert_eq!(
+            required_scope(ROUTES, &Method::DELETE, "/api/v1/posts/abc/tags/rust"),
+            Some(Scope::PostsWrite)
+        );
+    }
+
+    #[test]
+    fn test_prefixes_match_whole_segments() {
+        assert_eq!(
+            required_scope(ROUTES, &Method::GET, "/api/v1/postscript"),
+            None
+        );
+        assert_eq!(required_scope(ROUTES, &Method::GET, "/health"), None);
+    }
+
+    #[test]
+    fn test_scopes_round_trip_through_their_names() {
+        for scope in Scope::ALL {
+            assert_eq!(scope.to_string().parse::<Scope>(), Ok(scope));
+        }
+        assert!("posts:admin".parse::<Scope>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use super::{errors::AuthError, Authenticator, Identity};
+use crate::models::Scope;
+use actix_web::{
+    dev::{Extensions, ServiceRequest},
+    rt::net::UnixStream,
+};
+use std::{any::Any, collections::HashSet, sync::Arc};
+use tracing::{debug, warn};
+
+/// User and group of the process on the other end of a unix socket connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCredentials {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// Stores the `PeerCredentials` of unix socket connections in their extensions, for
+/// `HttpServer::on_connect`.
+pub fn record_peer_credentials(connection: &dyn Any, extensions: &mut Extensions) {
+    let Some(stream) = connection.downcast_ref::<UnixStream>() else {
+        return;
+    };
+
+    match stream.peer_cred() {
+        Ok(credentials) => {
+            extensions.insert(PeerCredentials {
+                uid: credentials.uid(),
+                gid: credentials.gid(),
+            });
+        }
+        Err(e) => warn!("Failed to read peer credentials: {e}"),
+    }
+}
+
+/// Authenticates requests made through the unix socket by the user and group of their process.
+///
+/// Trusted users and groups get every scope. Any other local process may only read when
+/// `peer_read` is set, and is otherwise left to other authenticators like requests from other
+/// transports, which carry no peer credentials.
+#[derive(Debug, Clone)]
+pub struct PeerCredentialsAuthenticator {
+    trusted_uids: Arc<HashSet<u32>>,
+    trusted_gids: Arc<HashSet<u32>>,
+    peer_read: bool,
+}
+
+impl PeerCredentialsAuthenticator {
+    pub fn new(trusted_uids: HashSet<u32>, trusted_gids: HashSet<u32>, peer_read: bool) -> Self {
+        Self {
+            trusted_uids: Arc::new(trusted_uids),
+            trusted_gids: Arc::new(trusted_gids),
+            peer_read,
+        }
+    }
+
+    fn identity(&self, credentials: &PeerCredentials) -> Option<Identity> {
+        let trusted = self.trusted_uids.contains(&credentials.uid)
+            || self.trusted_gids.contains(&credentials.gid);
+        debug!(
+            "Authenticated as uid {} gid {}, trusted: {trusted}",
+            credentials.uid, credentials.gid
+        );
+
+        let scopes = match (trusted, self.peer_read) {
+            (true, _) => Scope::ALL.to_vec(),
+            (false, true) => Scope::READ.to_vec(),
+            (false, false) => return None,
+        };
+
+        Some(Identity {
+            scopes,
+            author: None,
+        })
+    }
+}
+
+impl Authenticator for PeerCredentialsAuthenticator {
+    async fn authenticate(&self, req: &ServiceRequest) -> Result<Option<Identity>, AuthError> {
+        Ok(req
+            .conn_data::<PeerCredentials>()
+            .and_then(|credentials| self.identity(credentials)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn authenticator(peer_read: bool) -> PeerCredentialsAuthenticator {
+        PeerCredentialsAuthenticator::new(HashSet::from([1000]), HashSet::from([50]), peer_read)
+    }
+
+    const UNTRUSTED: PeerCredentials = PeerCredentials {
+        uid: 1001,
+        gid: 1001,
+    };
+
+    #[test]
+    fn test_trusted_users_and_groups_may_write() {
+        let authenticator = authenticator(false);
+
+        for credentials in [
+            PeerCredentials {
+                uid: 1000,
+                gid: 1000,
+            },
+            PeerCredentials { uid: 1001, gid: 50 },
+        ] {
+            assert_eq!(
+                authenticator.identity(&credentials).unwrap().scopes,
+                Scope::ALL.to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_local_users_are_not_identified() {
+        assert_eq!(authenticator(false).identity(&UNTRUSTED), None);
+    }
+
+    #[test]
+    fn test_other_local_users_may_only_read_with_peer_read() {
+        let identity = authenticator(true).identity(&UNTRUSTED).unwrap();
+
+        assert_eq!(identity.scopes, Scope::READ.to_vec());
+        assert_eq!(identity.author, None);
+    }
+
+    #[actix_web::test]
+    async fn test_connections_without_peer_credentials_are_not_identified() {
+        let req = TestRequest::default().to_srv_request();
+
+        assert_eq!(authenticator(true).authenticate(&req).await.unwrap(), None);
+    }
+
+    #[actix_web::test]
+    async fn test_unix_socket_connections_record_peer_credentials() {
+        let (local, _remote) = UnixStream::pair().unwrap();
+        let mut extensions = Extensions::new();
+
+        record_peer_credentials(&local, &mut extensions);
+
+        let credentials = extensions.get::<PeerCredentials>().unwrap();
+        let expected = local.peer_cred().unwrap();
+        assert_eq!(credentials.uid, expected.uid());
+        assert_eq!(credentials.gid, expected.gid());
+    }
+}
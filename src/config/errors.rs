@@ -4,10 +4,19 @@ use thiserror::Error;
 pub enum ConfigLoadError {
     #[error("Missing property: {0}")]
     MissingProperty(&'static str),
+
+    #[error("Invalid {0}: {1}")]
+    InvalidProperty(&'static str, String),
+
+    #[error("{0} and {1} cannot be used together")]
+    ConflictingProperties(&'static str, &'static str),
 }
 
 #[derive(Debug, Error)]
 pub enum PartialConfigLoadError {
     #[error("Unsupported log level: {0}")]
     UnsupportedLogLevel(String),
+
+    #[error("Invalid value of {0}: {1}")]
+    InvalidValue(&'static str, String),
 }
@@ -1,5 +1,19 @@
 use super::errors::ConfigLoadError;
-use std::net::SocketAddr;
+use crate::models::Scope;
+use actix_web::http::header::HeaderValue;
+use chrono::Duration;
+use clap::Subcommand;
+use std::{fmt, net::SocketAddr, path::PathBuf};
+
+/// `Cache-Control` of cacheable reads unless configured otherwise: caches may store responses but
+/// must revalidate them, which conditional requests make cheap.
+pub const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+
+/// Days a deleted post stays in the trash unless configured otherwise.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// Claim holding the scopes of a bearer token unless configured otherwise, as in OAuth 2.0.
+pub const DEFAULT_JWT_SCOPES_CLAIM: &str = "scope";
 
 #[derive(Debug, Clone)]
 pub enum ApiBind {
@@ -16,11 +30,150 @@ pub enum LogLevel {
     Error,
 }
 
+/// Ownership and permissions of the unix socket, and what local users may do through it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// User owning the socket, by name or id, the daemon's user when `None`.
+    pub owner: Option<String>,
+    /// Group owning the socket, by name or id, the daemon's group when `None`.
+    pub group: Option<String>,
+    /// Permissions of the socket, e.g., `0o660`, as left by the umask when `None`.
+    pub mode: Option<u32>,
+    /// Users whose processes may write through the socket without other credentials.
+    pub trusted_users: Vec<String>,
+    /// Groups whose processes may write through the socket without other credentials.
+    pub trusted_groups: Vec<String>,
+    /// Let processes of any other user read through the socket without other credentials, even
+    /// when credentials are required. Otherwise they are treated as anonymous clients.
+    pub peer_read: bool,
+}
+
+impl SocketOptions {
+    /// Whether peer credentials decide what local processes may do.
+    pub fn authorizes_peers(&self) -> bool {
+        !self.trusted_users.is_empty() || !self.trusted_groups.is_empty() || self.peer_read
+    }
+}
+
+/// Key bearer tokens are verified with.
+#[derive(Clone, PartialEq, Eq)]
+pub enum JwtKeySource {
+    /// Secret shared with the issuer, for HMAC signed tokens.
+    Secret(String),
+    /// JSON Web Key Set of the issuer, read once at startup.
+    JwksFile(PathBuf),
+}
+
+impl fmt::Debug for JwtKeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Secret(_) => f.write_str("Secret(<redacted>)"),
+            Self::JwksFile(path) => f.debug_tuple("JwksFile").field(path).finish(),
+        }
+    }
+}
+
+/// How to verify bearer tokens and map their claims to an identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtOptions {
+    pub key: JwtKeySource,
+    /// Required `iss` claim, any issuer when `None`.
+    pub issuer: Option<String>,
+    /// Required `aud` claim, any audience when `None`.
+    pub audience: Option<String>,
+    /// Claim holding the scopes, either space separated or as an array.
+    pub scopes_claim: String,
+    /// Claim holding the author the token acts as, none when `None`.
+    pub author_claim: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the API server (default)
+    #[default]
+    Serve,
+
+    /// Inspect or change the database schema
+    #[clap(subcommand)]
+    Migrate(MigrateCommand),
+
+    /// Find and remove tag relations left dangling by deleted posts or tags
+    Repair {
+        /// Only list the dangling relations, without removing them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Create, list and revoke API keys
+    #[clap(subcommand)]
+    ApiKey(ApiKeyCommand),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum ApiKeyCommand {
+    /// Create an API key and print it, it cannot be shown again
+    Create {
+        /// Unique name of the key, e.g., "ci"
+        name: String,
+
+        /// Scope granted to the key, e.g., "posts:write", repeated for every scope
+        #[clap(long = "scope", required = true)]
+        scopes: Vec<Scope>,
+
+        /// Author requests made with the key act as, which restricts them to the author's posts
+        #[clap(long)]
+        author: Option<String>,
+    },
+
+    /// List API keys with their scopes
+    List,
+
+    /// Revoke an API key
+    Revoke {
+        /// Name of the key
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum MigrateCommand {
+    /// List every migration and whether it is applied
+    Status,
+
+    /// Apply pending migrations
+    Up {
+        /// Stop after applying this migration instead of applying every pending one
+        #[clap(long)]
+        to: Option<String>,
+    },
+
+    /// Roll back applied migrations
+    Down {
+        /// Roll back every migration applied after this one, instead of only the latest
+        #[clap(long)]
+        to: Option<String>,
+    },
+}
+
 #[derive(Debug)]
 pub struct Config {
     pub log_level: LogLevel,
     pub api_bind: ApiBind,
     pub db_address: String,
+    pub command: Command,
+    /// Create missing tags when saving posts, unless a request says otherwise.
+    pub create_missing_tags: bool,
+    /// `Cache-Control` sent with post and tag reads.
+    pub cache_control: String,
+    /// How long deleted posts stay in the trash before being purged, `None` to keep them until
+    /// purged by hand.
+    pub trash_retention: Option<Duration>,
+    /// Reject reads without credentials too, instead of only writes.
+    pub require_auth: bool,
+    /// Accept bearer tokens, `None` when no key to verify them is configured.
+    pub jwt: Option<JwtOptions>,
+    /// Settings of the unix socket, unused when binding to TCP.
+    pub socket: SocketOptions,
 }
 
 #[derive(Default, Debug)]
@@ -28,6 +181,23 @@ pub struct PartialConfig {
     pub log_level: Option<LogLevel>,
     pub api_bind: Option<ApiBind>,
     pub db_address: Option<String>,
+    pub command: Option<Command>,
+    pub create_missing_tags: Option<bool>,
+    pub cache_control: Option<String>,
+    pub trash_retention_days: Option<u64>,
+    pub require_auth: Option<bool>,
+    pub jwt_secret: Option<String>,
+    pub jwks_file: Option<PathBuf>,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    pub jwt_scopes_claim: Option<String>,
+    pub jwt_author_claim: Option<String>,
+    pub socket_owner: Option<String>,
+    pub socket_group: Option<String>,
+    pub socket_mode: Option<String>,
+    pub socket_trusted_users: Option<Vec<String>>,
+    pub socket_trusted_groups: Option<Vec<String>>,
+    pub socket_peer_read: Option<bool>,
 }
 
 impl TryFrom<PartialConfig> for Config {
@@ -43,11 +213,85 @@ impl TryFrom<PartialConfig> for Config {
         let db_address = partial_config
             .db_address
             .ok_or(ConfigLoadError::MissingProperty("db_address"))?;
+        let command = partial_config.command.unwrap_or_default();
+        let create_missing_tags = partial_config.create_missing_tags.unwrap_or_default();
+        let require_auth = partial_config.require_auth.unwrap_or_default();
+        let cache_control = partial_config
+            .cache_control
+            .unwrap_or_else(|| DEFAULT_CACHE_CONTROL.to_string());
+        if HeaderValue::from_str(&cache_control).is_err() {
+            return Err(ConfigLoadError::InvalidProperty(
+                "cache_control",
+                cache_control,
+            ));
+        }
+
+        let trash_retention_days = partial_config
+            .trash_retention_days
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+        let trash_retention = match trash_retention_days {
+            0 => None,
+            days => Some(
+                i64::try_from(days)
+                    .ok()
+                    .and_then(Duration::try_days)
+                    .ok_or_else(|| {
+                        ConfigLoadError::InvalidProperty("trash_retention_days", days.to_string())
+                    })?,
+            ),
+        };
+
+        let jwt_key = match (partial_config.jwt_secret, partial_config.jwks_file) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigLoadError::ConflictingProperties(
+                    "jwt_secret",
+                    "jwks_file",
+                ))
+            }
+            (Some(secret), None) if secret.is_empty() => {
+                return Err(ConfigLoadError::InvalidProperty("jwt_secret", secret))
+            }
+            (Some(secret), None) => Some(JwtKeySource::Secret(secret)),
+            (None, Some(path)) => Some(JwtKeySource::JwksFile(path)),
+            (None, None) => None,
+        };
+        let jwt = jwt_key.map(|key| JwtOptions {
+            key,
+            issuer: partial_config.jwt_issuer,
+            audience: partial_config.jwt_audience,
+            scopes_claim: partial_config
+                .jwt_scopes_claim
+                .unwrap_or_else(|| DEFAULT_JWT_SCOPES_CLAIM.to_string()),
+            author_claim: partial_config.jwt_author_claim,
+        });
+
+        let socket_mode = partial_config
+            .socket_mode
+            .map(|mode| match u32::from_str_radix(&mode, 8) {
+                Ok(bits) if bits <= 0o777 => Ok(bits),
+                _ => Err(ConfigLoadError::InvalidProperty("socket_mode", mode)),
+            })
+            .transpose()?;
+        let socket = SocketOptions {
+            owner: partial_config.socket_owner,
+            group: partial_config.socket_group,
+            mode: socket_mode,
+            trusted_users: partial_config.socket_trusted_users.unwrap_or_default(),
+            trusted_groups: partial_config.socket_trusted_groups.unwrap_or_default(),
+            peer_read: partial_config.socket_peer_read.unwrap_or_default(),
+        };
 
         Ok(Self {
             log_level,
             api_bind,
             db_address,
+            command,
+            create_missing_tags,
+            cache_control,
+            trash_retention,
+            require_auth,
+            jwt,
+            socket,
         })
     }
 }
@@ -58,6 +302,23 @@ impl PartialConfig {
             log_level: self.log_level.or(other.log_level),
             api_bind: self.api_bind.or(other.api_bind),
             db_address: self.db_address.or(other.db_address),
+            command: self.command.or(other.command),
+            create_missing_tags: self.create_missing_tags.or(other.create_missing_tags),
+            cache_control: self.cache_control.or(other.cache_control),
+            trash_retention_days: self.trash_retention_days.or(other.trash_retention_days),
+            require_auth: self.require_auth.or(other.require_auth),
+            jwt_secret: self.jwt_secret.or(other.jwt_secret),
+            jwks_file: self.jwks_file.or(other.jwks_file),
+            jwt_issuer: self.jwt_issuer.or(other.jwt_issuer),
+            jwt_audience: self.jwt_audience.or(other.jwt_audience),
+            jwt_scopes_claim: self.jwt_scopes_claim.or(other.jwt_scopes_claim),
+            jwt_author_claim: self.jwt_author_claim.or(other.jwt_author_claim),
+            socket_owner: self.socket_owner.or(other.socket_owner),
+            socket_group: self.socket_group.or(other.socket_group),
+            socket_mode: self.socket_mode.or(other.socket_mode),
+            socket_trusted_users: self.socket_trusted_users.or(other.socket_trusted_users),
+            socket_trusted_groups: self.socket_trusted_groups.or(other.socket_trusted_groups),
+            socket_peer_read: self.socket_peer_read.or(other.socket_peer_read),
         }
     }
 }
@@ -76,6 +337,23 @@ mod tests {
             log_level: Some(log_level),
             api_bind: Some(api_bind),
             db_address: Some(db_address),
+            command: None,
+            create_missing_tags: None,
+            cache_control: None,
+            trash_retention_days: None,
+            require_auth: None,
+            jwt_secret: None,
+            jwks_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: None,
+            socket_owner: None,
+            socket_group: None,
+            socket_mode: None,
+            socket_trusted_users: None,
+            socket_trusted_groups: None,
+            socket_peer_read: None,
         };
 
         let config = Config::try_from(partial_config).unwrap();
@@ -84,6 +362,136 @@ mod tests {
             matches!(config.api_bind, ApiBind::Tcp(addr) if addr == "127.0.0.1:8080".parse().unwrap())
         );
         assert_eq!(config.db_address, "foobar");
+        assert_eq!(config.command, Command::Serve);
+        assert!(!config.create_missing_tags);
+        assert!(!config.require_auth);
+        assert_eq!(config.jwt, None);
+        assert_eq!(config.socket, SocketOptions::default());
+        assert!(!config.socket.authorizes_peers());
+        assert_eq!(config.cache_control, DEFAULT_CACHE_CONTROL);
+        assert_eq!(
+            config.trash_retention,
+            Some(Duration::days(DEFAULT_TRASH_RETENTION_DAYS as i64))
+        );
+    }
+
+    #[test]
+    fn test_partial_config_trash_retention() {
+        let partial_config = |days| PartialConfig {
+            log_level: Some(LogLevel::Info),
+            api_bind: Some(ApiBind::Tcp("127.0.0.1:8080".parse().unwrap())),
+            db_address: Some("foobar".to_string()),
+            trash_retention_days: Some(days),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Config::try_from(partial_config(7)).unwrap().trash_retention,
+            Some(Duration::days(7))
+        );
+        assert_eq!(
+            Config::try_from(partial_config(0)).unwrap().trash_retention,
+            None
+        );
+        assert!(matches!(
+            Config::try_from(partial_config(u64::MAX)),
+            Err(ConfigLoadError::InvalidProperty("trash_retention_days", _))
+        ));
+    }
+
+    #[test]
+    fn test_partial_config_invalid_cache_control() {
+        let partial_config = PartialConfig {
+            log_level: Some(LogLevel::Info),
+            api_bind: Some(ApiBind::Tcp("127.0.0.1:8080".parse().unwrap())),
+            db_address: Some("foobar".to_string()),
+            cache_control: Some("max-age=60\n".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Config::try_from(partial_config),
+            Err(ConfigLoadError::InvalidProperty("cache_control", _))
+        ));
+    }
+
+    #[test]
+    fn test_partial_config_jwt() {
+        let partial_config = |jwt_secret: Option<&str>, jwks_file: Option<&str>| PartialConfig {
+            log_level: Some(LogLevel::Info),
+            api_bind: Some(ApiBind::Tcp("127.0.0.1:8080".parse().unwrap())),
+            db_address: Some("foobar".to_string()),
+            jwt_secret: jwt_secret.map(str::to_string),
+            jwks_file: jwks_file.map(PathBuf::from),
+            jwt_audience: Some("iemanja".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Config::try_from(partial_config(Some("secret"), None))
+                .unwrap()
+                .jwt,
+            Some(JwtOptions {
+                key: JwtKeySource::Secret("secret".to_string()),
+                issuer: None,
+                audience: Some("iemanja".to_string()),
+                scopes_claim: DEFAULT_JWT_SCOPES_CLAIM.to_string(),
+                author_claim: None,
+            })
+        );
+        assert_eq!(
+            Config::try_from(partial_config(None, Some("/etc/iemanjad/jwks.json")))
+                .unwrap()
+                .jwt
+                .map(|jwt| jwt.key),
+            Some(JwtKeySource::JwksFile(PathBuf::from(
+                "/etc/iemanjad/jwks.json"
+            )))
+        );
+        assert!(matches!(
+            Config::try_from(partial_config(
+                Some("secret"),
+                Some("/etc/iemanjad/jwks.json")
+            )),
+            Err(ConfigLoadError::ConflictingProperties(
+                "jwt_secret",
+                "jwks_file"
+            ))
+        ));
+        assert!(matches!(
+            Config::try_from(partial_config(Some(""), None)),
+            Err(ConfigLoadError::InvalidProperty("jwt_secret", _))
+        ));
+    }
+
+    #[test]
+    fn test_partial_config_socket_mode() {
+        let partial_config = |mode: &str| PartialConfig {
+            log_level: Some(LogLevel::Info),
+            api_bind: Some(ApiBind::UnixSocket("/tmp/iemanja.sock".to_string())),
+            db_address: Some("foobar".to_string()),
+            socket_mode: Some(mode.to_string()),
+            socket_trusted_groups: Some(vec!["wheel".to_string()]),
+            socket_peer_read: None,
+            ..Default::default()
+        };
+
+        let socket = Config::try_from(partial_config("0660")).unwrap().socket;
+        assert_eq!(socket.mode, Some(0o660));
+        assert!(socket.authorizes_peers());
+        for mode in ["rw-rw----", "1777", "800"] {
+            assert!(matches!(
+                Config::try_from(partial_config(mode)),
+                Err(ConfigLoadError::InvalidProperty("socket_mode", _))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_jwt_secret_is_not_logged() {
+        let key = JwtKeySource::Secret("hunter2".to_string());
+
+        assert!(!format!("{key:?}").contains("hunter2"));
     }
 
     #[test]
@@ -92,6 +500,23 @@ mod tests {
             log_level: None,
             api_bind: Some(ApiBind::Tcp("127.0.0.1:8080".parse().unwrap())),
             db_address: Some("foobar".to_string()),
+            command: None,
+            create_missing_tags: None,
+            cache_control: None,
+            trash_retention_days: None,
+            require_auth: None,
+            jwt_secret: None,
+            jwks_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: None,
+            socket_owner: None,
+            socket_group: None,
+            socket_mode: None,
+            socket_trusted_users: None,
+            socket_trusted_groups: None,
+            socket_peer_read: None,
         };
 
         let result = Config::try_from(partial_config);
@@ -106,6 +531,23 @@ mod tests {
             log_level: Some(LogLevel::Info),
             api_bind: None,
             db_address: Some("foobar".to_string()),
+            command: None,
+            create_missing_tags: None,
+            cache_control: None,
+            trash_retention_days: None,
+            require_auth: None,
+            jwt_secret: None,
+            jwks_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: None,
+            socket_owner: None,
+            socket_group: None,
+            socket_mode: None,
+            socket_trusted_users: None,
+            socket_trusted_groups: None,
+            socket_peer_read: None,
         };
 
         let result = Config::try_from(partial_config);
@@ -120,6 +562,23 @@ mod tests {
             log_level: Some(LogLevel::Info),
             api_bind: Some(ApiBind::Tcp("127.0.0.1:8080".parse().unwrap())),
             db_address: None,
+            command: None,
+            create_missing_tags: None,
+            cache_control: None,
+            trash_retention_days: None,
+            require_auth: None,
+            jwt_secret: None,
+            jwks_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: None,
+            socket_owner: None,
+            socket_group: None,
+            socket_mode: None,
+            socket_trusted_users: None,
+            socket_trusted_groups: None,
+            socket_peer_read: None,
         };
 
         let result = Config::try_from(partial_config);
@@ -138,12 +597,46 @@ mod tests {
             log_level: Some(log_level_1),
             api_bind: Some(api_bind_1),
             db_address: None,
+            command: None,
+            create_missing_tags: None,
+            cache_control: None,
+            trash_retention_days: None,
+            require_auth: None,
+            jwt_secret: None,
+            jwks_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: None,
+            socket_owner: None,
+            socket_group: None,
+            socket_mode: None,
+            socket_trusted_users: None,
+            socket_trusted_groups: None,
+            socket_peer_read: None,
         };
 
         let partial_config_2 = PartialConfig {
             log_level: None,
             api_bind: None,
             db_address: Some(db_address_2.clone()),
+            command: Some(Command::Migrate(MigrateCommand::Status)),
+            create_missing_tags: Some(true),
+            cache_control: Some("public, max-age=60".to_string()),
+            trash_retention_days: Some(7),
+            require_auth: Some(true),
+            jwt_secret: Some("secret".to_string()),
+            jwks_file: None,
+            jwt_issuer: Some("https://sso.example.com".to_string()),
+            jwt_audience: None,
+            jwt_scopes_claim: None,
+            jwt_author_claim: Some("preferred_username".to_string()),
+            socket_owner: None,
+            socket_group: Some("iemanja".to_string()),
+            socket_mode: Some("660".to_string()),
+            socket_trusted_users: Some(vec!["deploy".to_string()]),
+            socket_trusted_groups: None,
+            socket_peer_read: Some(true),
         };
 
         let merged_config = partial_config_1.merge(partial_config_2);
@@ -151,5 +644,32 @@ mod tests {
         assert!(matches!(merged_config.log_level, Some(LogLevel::Info)));
         assert!(matches!(merged_config.api_bind, Some(ApiBind::Tcp(_))));
         assert_eq!(merged_config.db_address, Some(db_address_2));
+        assert_eq!(
+            merged_config.command,
+            Some(Command::Migrate(MigrateCommand::Status))
+        );
+        assert_eq!(merged_config.create_missing_tags, Some(true));
+        assert_eq!(
+            merged_config.cache_control.as_deref(),
+            Some("public, max-age=60")
+        );
+        assert_eq!(merged_config.trash_retention_days, Some(7));
+        assert_eq!(merged_config.require_auth, Some(true));
+        assert_eq!(merged_config.jwt_secret.as_deref(), Some("secret"));
+        assert_eq!(
+            merged_config.jwt_issuer.as_deref(),
+            Some("https://sso.example.com")
+        );
+        assert_eq!(
+            merged_config.jwt_author_claim.as_deref(),
+            Some("preferred_username")
+        );
+        assert_eq!(merged_config.socket_group.as_deref(), Some("iemanja"));
+        assert_eq!(merged_config.socket_mode.as_deref(), Some("660"));
+        assert_eq!(
+            merged_config.socket_trusted_users,
+            Some(vec!["deploy".to_string()])
+        );
+        assert_eq!(merged_config.socket_peer_read, Some(true));
     }
 }
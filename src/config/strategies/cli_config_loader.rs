@@ -1,15 +1,16 @@
 use crate::config::{
     errors::PartialConfigLoadError,
-    models::{ApiBind, LogLevel, PartialConfig},
+    models::{ApiBind, Command, LogLevel, PartialConfig},
     traits::PartialConfigLoader,
 };
 use clap::Parser;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 pub struct CliConfigLoader {
     /// Log level: trace, debug, info, warn, error
-    #[clap(long, default_value = "info")]
+    #[clap(long, global = true, default_value = "info")]
     pub log_level: Option<String>,
 
     /// API binding address, e.g., "127.0.0.1:7029" for TCP or "/tmp/api.sock" for Unix socket
@@ -17,8 +18,79 @@ pub struct CliConfigLoader {
     pub api_bind: Option<ApiBind>,
 
     /// Database address, e.g., "ws://127.0.0.1:8000" for external db or "speedb:///etc/iemanjad/iemanjad.surreal" for local
-    #[clap(long, default_value = "speedb:///etc/iemanjad/iemanjad.surreal")]
+    #[clap(
+        long,
+        global = true,
+        default_value = "speedb:///etc/iemanjad/iemanjad.surreal"
+    )]
     pub db_address: Option<String>,
+
+    /// Create missing tags when saving posts, unless a request sets `create_missing_tags=false`
+    #[clap(long)]
+    pub create_missing_tags: bool,
+
+    /// Cache-Control header of post and tag reads, e.g., "public, max-age=60" [default: no-cache]
+    #[clap(long)]
+    pub cache_control: Option<String>,
+
+    /// Days deleted posts stay in the trash before being purged, 0 to keep them until purged by hand [default: 30]
+    #[clap(long)]
+    pub trash_retention_days: Option<u64>,
+
+    /// Reject reads without an API key or bearer token too, instead of only writes
+    #[clap(long)]
+    pub require_auth: bool,
+
+    /// Secret verifying HMAC signed bearer tokens (HS256, HS384, HS512)
+    #[clap(long)]
+    pub jwt_secret: Option<String>,
+
+    /// JSON Web Key Set file verifying bearer tokens, e.g., "/etc/iemanjad/jwks.json"
+    #[clap(long)]
+    pub jwks_file: Option<PathBuf>,
+
+    /// Issuer bearer tokens must have in their "iss" claim
+    #[clap(long)]
+    pub jwt_issuer: Option<String>,
+
+    /// Audience bearer tokens must have in their "aud" claim
+    #[clap(long)]
+    pub jwt_audience: Option<String>,
+
+    /// Claim of bearer tokens holding their scopes [default: scope]
+    #[clap(long)]
+    pub jwt_scopes_claim: Option<String>,
+
+    /// Claim of bearer tokens holding the author they act as, e.g., "preferred_username"
+    #[clap(long)]
+    pub jwt_author_claim: Option<String>,
+
+    /// User owning the unix socket, by name or id [default: the daemon's user]
+    #[clap(long)]
+    pub socket_owner: Option<String>,
+
+    /// Group owning the unix socket, by name or id [default: the daemon's group]
+    #[clap(long)]
+    pub socket_group: Option<String>,
+
+    /// Octal permissions of the unix socket, e.g., "660" [default: as left by the umask]
+    #[clap(long)]
+    pub socket_mode: Option<String>,
+
+    /// User whose processes may write through the unix socket without credentials, repeated for every user
+    #[clap(long = "socket-trusted-user")]
+    pub socket_trusted_users: Vec<String>,
+
+    /// Group whose processes may write through the unix socket without credentials, repeated for every group
+    #[clap(long = "socket-trusted-group")]
+    pub socket_trusted_groups: Vec<String>,
+
+    /// Let processes of any other user read through the unix socket without credentials, even with --require-auth
+    #[clap(long)]
+    pub socket_peer_read: bool,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
 }
 
 impl PartialConfigLoader for CliConfigLoader {
@@ -31,11 +103,35 @@ impl PartialConfigLoader for CliConfigLoader {
             .and_then(|level| LogLevel::try_from(level).ok());
         let api_bind = config.api_bind;
         let db_address = config.db_address;
+        let command = config.command;
+        let create_missing_tags = config.create_missing_tags.then_some(true);
+        let cache_control = config.cache_control;
+        let trash_retention_days = config.trash_retention_days;
+        let require_auth = config.require_auth.then_some(true);
 
         Ok(PartialConfig {
             log_level,
             api_bind,
             db_address,
+            command,
+            create_missing_tags,
+            cache_control,
+            trash_retention_days,
+            require_auth,
+            jwt_secret: config.jwt_secret,
+            jwks_file: config.jwks_file,
+            jwt_issuer: config.jwt_issuer,
+            jwt_audience: config.jwt_audience,
+            jwt_scopes_claim: config.jwt_scopes_claim,
+            jwt_author_claim: config.jwt_author_claim,
+            socket_owner: config.socket_owner,
+            socket_group: config.socket_group,
+            socket_mode: config.socket_mode,
+            socket_trusted_users: (!config.socket_trusted_users.is_empty())
+                .then_some(config.socket_trusted_users),
+            socket_trusted_groups: (!config.socket_trusted_groups.is_empty())
+                .then_some(config.socket_trusted_groups),
+            socket_peer_read: config.socket_peer_read.then_some(true),
         })
     }
 }
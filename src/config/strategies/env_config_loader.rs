@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf, str::FromStr};
 
 use crate::config::{
     errors::PartialConfigLoadError,
@@ -24,10 +24,105 @@ impl PartialConfigLoader for EnvConfigLoader {
 
         let db_address = env::var("IEMANJA_DATABASE").ok();
 
+        let create_missing_tags = parse_var("IEMANJA_CREATE_MISSING_TAGS")?;
+
+        let cache_control = env::var("IEMANJA_CACHE_CONTROL").ok();
+
+        let trash_retention_days = parse_var("IEMANJA_TRASH_RETENTION_DAYS")?;
+
+        let require_auth = parse_var("IEMANJA_REQUIRE_AUTH")?;
+
+        let jwt_secret = env::var("IEMANJA_JWT_SECRET").ok();
+        let jwks_file = env::var_os("IEMANJA_JWKS_FILE").map(PathBuf::from);
+        let jwt_issuer = env::var("IEMANJA_JWT_ISSUER").ok();
+        let jwt_audience = env::var("IEMANJA_JWT_AUDIENCE").ok();
+        let jwt_scopes_claim = env::var("IEMANJA_JWT_SCOPES_CLAIM").ok();
+        let jwt_author_claim = env::var("IEMANJA_JWT_AUTHOR_CLAIM").ok();
+
+        let socket_owner = env::var("IEMANJA_SOCKET_OWNER").ok();
+        let socket_group = env::var("IEMANJA_SOCKET_GROUP").ok();
+        let socket_mode = env::var("IEMANJA_SOCKET_MODE").ok();
+        let socket_trusted_users = env::var("IEMANJA_SOCKET_TRUSTED_USERS")
+            .ok()
+            .map(|users| split_list(&users));
+        let socket_trusted_groups = env::var("IEMANJA_SOCKET_TRUSTED_GROUPS")
+            .ok()
+            .map(|groups| split_list(&groups));
+        let socket_peer_read = parse_var("IEMANJA_SOCKET_PEER_READ")?;
+
         Ok(PartialConfig {
             log_level,
             api_bind,
             db_address,
+            command: None,
+            create_missing_tags,
+            cache_control,
+            trash_retention_days,
+            require_auth,
+            jwt_secret,
+            jwks_file,
+            jwt_issuer,
+            jwt_audience,
+            jwt_scopes_claim,
+            jwt_author_claim,
+            socket_owner,
+            socket_group,
+            socket_mode,
+            socket_trusted_users,
+            socket_trusted_groups,
+            socket_peer_read,
+        })
+    }
+}
+
+/// Value of the environment variable `name`, which must parse as a `T` when set, e.g. exactly
+/// "true" or "false" for flags.
+fn parse_var<T: FromStr>(name: &'static str) -> Result<Option<T>, PartialConfigLoadError> {
+    parse_value(name, env::var(name).ok())
+}
+
+fn parse_value<T: FromStr>(
+    name: &'static str,
+    value: Option<String>,
+) -> Result<Option<T>, PartialConfigLoadError> {
+    value
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| PartialConfigLoadError::InvalidValue(name, value))
         })
+        .transpose()
+}
+
+/// Items of a comma separated list, e.g., "deploy, backup".
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_must_be_true_or_false() {
+        assert_eq!(
+            parse_value::<bool>("IEMANJA_REQUIRE_AUTH", Some("true".to_string())).unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            parse_value::<bool>("IEMANJA_REQUIRE_AUTH", None).unwrap(),
+            None
+        );
+
+        for value in ["1", "yes", "True", ""] {
+            assert!(matches!(
+                parse_value::<bool>("IEMANJA_REQUIRE_AUTH", Some(value.to_string())),
+                Err(PartialConfigLoadError::InvalidValue("IEMANJA_REQUIRE_AUTH", invalid)) if invalid == value
+            ));
+        }
     }
 }
@@ -0,0 +1,108 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use super::preconditions::{
+    cacheable_response, content_tag, etag, if_match, revision_tag, ReadCacheControl,
+};
+use crate::persistency::{
+    authors::{
+        errors::AuthorRepositoryError,
+        models::{AuthorPatch, NewAuthor},
+    },
+    models::FindAllOptions,
+    traits::AuthorRepository,
+};
+
+pub async fn create_author<T: AuthorRepository>(
+    author_repo: web::Data<T>,
+    author: web::Json<NewAuthor>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    let author = author_repo.create(author.into_inner()).await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header(etag(author.revision))
+        .json(author))
+}
+
+pub async fn find_all_authors<T: AuthorRepository>(
+    req: HttpRequest,
+    author_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
+    query: web::Query<FindAllOptions>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    let authors = author_repo.find_all(query.into_inner()).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        content_tag(&authors),
+        None,
+        &authors,
+    ))
+}
+
+pub async fn get_author<T: AuthorRepository>(
+    req: HttpRequest,
+    author_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
+    name: web::Path<String>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    let author = author_repo.get(name.into_inner().as_str()).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        revision_tag(author.revision),
+        None,
+        &author,
+    ))
+}
+
+pub async fn update_author<T: AuthorRepository>(
+    req: HttpRequest,
+    author_repo: web::Data<T>,
+    name: web::Path<String>,
+    author: web::Json<NewAuthor>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    let author = author_repo
+        .update(
+            name.into_inner().as_str(),
+            author.into_inner(),
+            if_match(&req),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(author.revision))
+        .json(author))
+}
+
+pub async fn patch_author<T: AuthorRepository>(
+    req: HttpRequest,
+    author_repo: web::Data<T>,
+    name: web::Path<String>,
+    patch: web::Json<AuthorPatch>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    let author = author_repo
+        .patch(
+            name.into_inner().as_str(),
+            patch.into_inner(),
+            if_match(&req),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(author.revision))
+        .json(author))
+}
+
+pub async fn delete_author<T: AuthorRepository>(
+    req: HttpRequest,
+    author_repo: web::Data<T>,
+    name: web::Path<String>,
+) -> Result<HttpResponse, AuthorRepositoryError> {
+    author_repo
+        .delete(name.into_inner().as_str(), if_match(&req))
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
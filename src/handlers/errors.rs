@@ -0,0 +1,476 @@
+use crate::{
+    auth::errors::AuthError,
+    persistency::{
+        api_keys::errors::ApiKeyRepositoryError, authors::errors::AuthorRepositoryError,
+        models::Violation, posts::errors::PostRepositoryError, tags::errors::TagRepositoryError,
+    },
+};
+use actix_web::{
+    error::{JsonPayloadError, PathError, QueryPayloadError},
+    http::StatusCode,
+    HttpRequest, HttpResponse, ResponseError,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+/// JSON envelope returned by every failing API call:
+/// `{ "error": { "code": "post_not_found", "message": "Post not found: abc" } }`.
+///
+/// `code` is stable and meant to be matched by clients, `message` is human readable and may change.
+/// Rejected writes also list every `violations` found, each with its own stable `code`.
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub violations: Vec<Violation>,
+}
+
+fn error_response<E: ResponseError>(
+    error: &E,
+    code: &'static str,
+    violations: &[Violation],
+) -> HttpResponse {
+    HttpResponse::build(error.status_code()).json(ErrorResponse {
+        error: ErrorBody {
+            code,
+            message: error.to_string(),
+            violations: violations.to_vec(),
+        },
+    })
+}
+
+/// Requests actix could not extract, reported in the envelope instead of actix's plain text.
+#[derive(Error, Debug)]
+pub enum RequestError {
+    #[error("Invalid request body: {0}")]
+    Body(String),
+
+    #[error("Invalid query string: {0}")]
+    Query(String),
+
+    #[error("Invalid path: {0}")]
+    Path(String),
+}
+
+impl RequestError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Body(_) => "invalid_body",
+            Self::Query(_) => "invalid_query",
+            Self::Path(_) => "invalid_path",
+        }
+    }
+}
+
+impl ResponseError for RequestError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        error_response(self, self.code(), &[])
+    }
+}
+
+/// Error handler of `web::JsonConfig`.
+pub fn json_error(error: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    RequestError::Body(error.to_string()).into()
+}
+
+/// Error handler of `web::QueryConfig`.
+pub fn query_error(error: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    RequestError::Query(error.to_string()).into()
+}
+
+/// Error handler of `web::PathConfig`.
+pub fn path_error(error: PathError, _req: &HttpRequest) -> actix_web::Error {
+    RequestError::Path(error.to_string()).into()
+}
+
+impl PostRepositoryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database_unavailable",
+            Self::TagLookup(e) => e.code(),
+            Self::AuthorLookup(e) => e.code(),
+            Self::PostCreation => "post_creation_failed",
+            Self::InvalidPost(_) => "invalid_post",
+            Self::PostListing => "post_listing_failed",
+            Self::InvalidSort(_) => "invalid_sort",
+            Self::InvalidCursor(_) => "invalid_cursor",
+            Self::PostCount => "post_count_failed",
+            Self::PostSearch => "post_search_failed",
+            Self::EmptySearchQuery => "empty_search_query",
+            Self::PostGet => "post_get_failed",
+            Self::PostNotFound(_) => "post_not_found",
+            Self::StatusNotAllowed(_) => "status_not_allowed",
+            Self::PostNotOwned(_) => "post_not_owned",
+            Self::AuthorRequired => "author_required",
+            Self::AuthorNotAllowed(_) => "author_not_allowed",
+            Self::SlugAlreadyExists(_) => "slug_already_exists",
+            Self::RevisionMismatch(..) => "revision_mismatch",
+            Self::RevisionNotFound(..) => "revision_not_found",
+            Self::InvalidRevisionRange(..) => "invalid_revision_range",
+            Self::RevisionListing => "revision_listing_failed",
+            Self::PostUpdate => "post_update_failed",
+            Self::PostDeletion => "post_deletion_failed",
+            Self::PostPurge => "post_purge_failed",
+            Self::PostPublish => "post_publish_failed",
+            Self::RelationRepair => "relation_repair_failed",
+        }
+    }
+}
+
+impl ResponseError for PostRepositoryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Database(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::TagLookup(e) => e.status_code(),
+            Self::AuthorLookup(e) => e.status_code(),
+            Self::InvalidPost(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::EmptySearchQuery
+            | Self::InvalidSort(_)
+            | Self::InvalidCursor(_)
+            | Self::InvalidRevisionRange(..) => StatusCode::BAD_REQUEST,
+            Self::PostNotFound(_) | Self::RevisionNotFound(..) => StatusCode::NOT_FOUND,
+            Self::StatusNotAllowed(_)
+            | Self::PostNotOwned(_)
+            | Self::AuthorRequired
+            | Self::AuthorNotAllowed(_) => StatusCode::FORBIDDEN,
+            Self::SlugAlreadyExists(_) => StatusCode::CONFLICT,
+            Self::RevisionMismatch(..) => StatusCode::PRECONDITION_FAILED,
+            Self::PostCreation
+            | Self::PostListing
+            | Self::PostCount
+            | Self::PostSearch
+            | Self::PostGet
+            | Self::RevisionListing
+            | Self::PostUpdate
+            | Self::PostDeletion
+            | Self::PostPurge
+            | Self::PostPublish
+            | Self::RelationRepair => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let violations = match self {
+            Self::InvalidPost(violations) => violations.as_slice(),
+            _ => &[],
+        };
+
+        error_response(self, self.code(), violations)
+    }
+}
+
+impl TagRepositoryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database_unavailable",
+            Self::TagCreation => "tag_creation_failed",
+            Self::InvalidTag(_) => "invalid_tag",
+            Self::TagAlreadyExists(_) => "tag_already_exists",
+            Self::TagListing => "tag_listing_failed",
+            Self::InvalidSort(_) => "invalid_sort",
+            Self::InvalidCursor(_) => "invalid_cursor",
+            Self::TagCount => "tag_count_failed",
+            Self::TagFind => "tag_find_failed",
+            Self::TagGet => "tag_get_failed",
+            Self::TagNotFound(_) => "tag_not_found",
+            Self::TagInUse(..) => "tag_in_use",
+            Self::RevisionMismatch(..) => "revision_mismatch",
+            Self::TagUpdate => "tag_update_failed",
+            Self::TagDeletion => "tag_deletion_failed",
+        }
+    }
+}
+
+impl ResponseError for TagRepositoryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Database(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidSort(_) | Self::InvalidCursor(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidTag(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::TagAlreadyExists(_) | Self::TagInUse(..) => StatusCode::CONFLICT,
+            Self::TagNotFound(_) => StatusCode::NOT_FOUND,
+            Self::RevisionMismatch(..) => StatusCode::PRECONDITION_FAILED,
+            Self::TagCreation
+            | Self::TagListing
+            | Self::TagCount
+            | Self::TagFind
+            | Self::TagGet
+            | Self::TagUpdate
+            | Self::TagDeletion => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let violations = match self {
+            Self::InvalidTag(violations) => violations.as_slice(),
+            _ => &[],
+        };
+
+        error_response(self, self.code(), violations)
+    }
+}
+
+impl AuthorRepositoryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database_unavailable",
+            Self::AuthorCreation => "author_creation_failed",
+            Self::AuthorAlreadyExists(_) => "author_already_exists",
+            Self::AuthorListing => "author_listing_failed",
+            Self::InvalidSort(_) => "invalid_sort",
+            Self::InvalidCursor(_) => "invalid_cursor",
+            Self::AuthorCount => "author_count_failed",
+            Self::AuthorFind => "author_find_failed",
+            Self::AuthorGet => "author_get_failed",
+            Self::AuthorNotFound(_) => "author_not_found",
+            Self::AuthorInUse(..) => "author_in_use",
+            Self::RevisionMismatch(..) => "revision_mismatch",
+            Self::AuthorUpdate => "author_update_failed",
+            Self::AuthorDeletion => "author_deletion_failed",
+        }
+    }
+}
+
+impl ResponseError for AuthorRepositoryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Database(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidSort(_) | Self::InvalidCursor(_) => StatusCode::BAD_REQUEST,
+            Self::AuthorAlreadyExists(_) | Self::AuthorInUse(..) => StatusCode::CONFLICT,
+            Self::AuthorNotFound(_) => StatusCode::NOT_FOUND,
+            Self::RevisionMismatch(..) => StatusCode::PRECONDITION_FAILED,
+            Self::AuthorCreation
+            | Self::AuthorListing
+            | Self::AuthorCount
+            | Self::AuthorFind
+            | Self::AuthorGet
+            | Self::AuthorUpdate
+            | Self::AuthorDeletion => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        error_response(self, self.code(), &[])
+    }
+}
+
+impl ApiKeyRepositoryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "database_unavailable",
+            Self::ApiKeyCreation => "api_key_creation_failed",
+            Self::ApiKeyAlreadyExists(_) => "api_key_already_exists",
+            Self::ApiKeyListing => "api_key_listing_failed",
+            Self::ApiKeyGet => "api_key_get_failed",
+            Self::ApiKeyNotFound(_) => "api_key_not_found",
+            Self::ApiKeyDeletion => "api_key_deletion_failed",
+        }
+    }
+}
+
+impl ResponseError for ApiKeyRepositoryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Database(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::ApiKeyAlreadyExists(_) => StatusCode::CONFLICT,
+            Self::ApiKeyNotFound(_) => StatusCode::NOT_FOUND,
+            Self::ApiKeyCreation | Self::ApiKeyListing | Self::ApiKeyGet | Self::ApiKeyDeletion => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        error_response(self, self.code(), &[])
+    }
+}
+
+impl AuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingCredentials => "missing_credentials",
+            Self::InvalidApiKey => "invalid_api_key",
+            Self::InvalidToken(_) => "invalid_token",
+            Self::InsufficientScope(_) => "insufficient_scope",
+            Self::ApiKeyLookup(e) => e.code(),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingCredentials | Self::InvalidApiKey | Self::InvalidToken(_) => {
+                StatusCode::UNAUTHORIZED
+            }
+            Self::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            Self::ApiKeyLookup(e) => e.status_code(),
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        error_response(self, self.code(), &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::to_bytes, test::TestRequest, web, App};
+    use serde_json::{json, Value};
+
+    async fn body_json(response: HttpResponse) -> Value {
+        let bytes = to_bytes(response.into_body()).await.unwrap();
+
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_post_not_found_response() {
+        let error = PostRepositoryError::PostNotFound("abc".to_string());
+        let response = error.error_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            body_json(response).await,
+            json!({ "error": { "code": "post_not_found", "message": "Post not found: abc" } })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_post_response() {
+        let error = PostRepositoryError::InvalidPost(vec![
+            Violation::new(
+                "title",
+                "title_empty",
+                "Title must not be empty".to_string(),
+            ),
+            Violation::new("tags", "tag_not_found", "Tag not found: zig".to_string()),
+        ]);
+        let response = error.error_response();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            body_json(response).await,
+            json!({
+                "error": {
+                    "code": "invalid_post",
+                    "message": "Invalid post: Title must not be empty; Tag not found: zig",
+                    "violations": [
+                        { "field": "title", "code": "title_empty", "message": "Title must not be empty" },
+                        { "field": "tags", "code": "tag_not_found", "message": "Tag not found: zig" }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_errors_use_the_envelope() {
+        async fn echo(body: web::Json<Value>, _query: web::Query<Vec<(u64, u64)>>) -> HttpResponse {
+            HttpResponse::Ok().json(body.into_inner())
+        }
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().error_handler(json_error))
+                .app_data(web::QueryConfig::default().error_handler(query_error))
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{\"title\":")
+            .to_request();
+        let response = actix_web::test::call_service(&app, req).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = actix_web::test::read_body_json(response).await;
+        assert_eq!(body["error"]["code"], "invalid_body");
+
+        let req = TestRequest::post()
+            .uri("/?limit=ten")
+            .set_json(json!({}))
+            .to_request();
+        let response = actix_web::test::call_service(&app, req).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = actix_web::test::read_body_json(response).await;
+        assert_eq!(body["error"]["code"], "invalid_query");
+    }
+
+    #[test]
+    fn test_tag_error_status_codes() {
+        assert_eq!(
+            TagRepositoryError::TagNotFound("rust".to_string()).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            TagRepositoryError::TagAlreadyExists("rust".to_string()).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            TagRepositoryError::TagInUse("rust".to_string(), 2).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            TagRepositoryError::Database(surrealdb::Error::Api(
+                surrealdb::error::Api::ConnectionUninitialised
+            ))
+            .status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_tag_lookup_errors_keep_their_status() {
+        let error = PostRepositoryError::TagLookup(TagRepositoryError::Database(
+            surrealdb::Error::Api(surrealdb::error::Api::ConnectionUninitialised),
+        ));
+
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.code(), "database_unavailable");
+    }
+
+    #[actix_web::test]
+    async fn test_post_not_owned_response() {
+        let error = PostRepositoryError::PostNotOwned("abc".to_string());
+        let response = error.error_response();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            body_json(response).await,
+            json!({
+                "error": {
+                    "code": "post_not_owned",
+                    "message": "Post abc belongs to another author"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_author_error_status_codes() {
+        assert_eq!(
+            AuthorRepositoryError::AuthorNotFound("ana".to_string()).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            AuthorRepositoryError::AuthorInUse("ana".to_string(), 2).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            PostRepositoryError::AuthorNotAllowed("ana".to_string()).status_code(),
+            StatusCode::FORBIDDEN
+        );
+    }
+}
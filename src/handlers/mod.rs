@@ -1,2 +1,7 @@
+pub mod authors;
+pub mod errors;
+pub mod ownership;
 pub mod posts;
+pub mod preconditions;
 pub mod tags;
+pub mod visibility;
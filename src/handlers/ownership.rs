@@ -0,0 +1,171 @@
+use super::preconditions::if_match;
+use crate::{
+    models::Scope,
+    persistency::{models::Precondition, posts::errors::PostRepositoryError},
+};
+use actix_web::{HttpMessage, HttpRequest};
+
+/// Author a request is authenticated as, put in its extensions by the authentication middleware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedAuthor(pub String);
+
+/// Scopes a request is granted, put in its extensions by the authentication middleware. Requests
+/// without one are granted nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrantedScopes(pub Vec<Scope>);
+
+pub fn has_scope(req: &HttpRequest, scope: Scope) -> bool {
+    req.extensions()
+        .get::<GrantedScopes>()
+        .is_some_and(|GrantedScopes(scopes)| scopes.contains(&scope))
+}
+
+fn authenticated_author(req: &HttpRequest) -> Option<AuthenticatedAuthor> {
+    req.extensions().get::<AuthenticatedAuthor>().cloned()
+}
+
+/// Precondition of a write to a post, from its `If-Match` header, that repositories only apply to
+/// posts of the author the request acts as. Requests with the `admin` scope may write posts of
+/// every author, and others must act as one.
+pub fn write_precondition(req: &HttpRequest) -> Result<Precondition, PostRepositoryError> {
+    owned_precondition(req, if_match(req))
+}
+
+/// `write_precondition` for writes that ignore `If-Match`.
+pub fn owner_precondition(req: &HttpRequest) -> Result<Precondition, PostRepositoryError> {
+    owned_precondition(req, Precondition::ANY)
+}
+
+fn owned_precondition(
+    req: &HttpRequest,
+    precondition: Precondition,
+) -> Result<Precondition, PostRepositoryError> {
+    if has_scope(req, Scope::Admin) {
+        return Ok(precondition);
+    }
+
+    match authenticated_author(req) {
+        Some(AuthenticatedAuthor(author)) => Ok(precondition.owned_by(Some(author))),
+        None => Err(PostRepositoryError::AuthorRequired),
+    }
+}
+
+/// Author a request writes a post as: itself when `author` is `None` and `default_to_self` is set,
+/// and never anyone else, unless it has the `admin` scope.
+pub fn resolve_author(
+    req: &HttpRequest,
+    author: Option<String>,
+    default_to_self: bool,
+) -> Result<Option<String>, PostRepositoryError> {
+    let admin = has_scope(req, Scope::Admin);
+    let Some(AuthenticatedAuthor(own)) = authenticated_author(req) else {
+        return match admin {
+            true => Ok(author),
+            false => Err(PostRepositoryError::AuthorRequired),
+        };
+    };
+
+    match author {
+        Some(author) if author != own && !admin => {
+            Err(PostRepositoryError::AuthorNotAllowed(author))
+        }
+        Some(author) => Ok(Some(author)),
+        None if !default_to_self => Ok(None),
+        None => Ok(Some(own)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, test::TestRequest};
+
+    fn request(author: Option<&str>, scopes: &[Scope]) -> HttpRequest {
+        let req = TestRequest::default()
+            .insert_header((header::IF_MATCH, r#""3""#))
+            .to_http_request();
+        if let Some(author) = author {
+            req.extensions_mut()
+                .insert(AuthenticatedAuthor(author.to_string()));
+        }
+        req.extensions_mut().insert(GrantedScopes(scopes.to_vec()));
+
+        req
+    }
+
+    #[test]
+    fn test_authors_only_write_their_own_posts() {
+        let precondition = write_precondition(&request(Some("ana"), &[Scope::PostsWrite])).unwrap();
+
+        assert!(precondition.allows(3));
+        assert!(!precondition.allows(4));
+        assert!(precondition.allows_owner(Some("ana")));
+        assert!(!precondition.allows_owner(Some("bruno")));
+        assert!(!precondition.allows_owner(None));
+    }
+
+    #[test]
+    fn test_admins_write_every_post() {
+        for author in [None, Some("ana")] {
+            let precondition = write_precondition(&request(author, &[Scope::Admin])).unwrap();
+
+            assert_eq!(precondition, Precondition::at(vec![3]));
+            assert!(precondition.allows_owner(Some("bruno")));
+        }
+    }
+
+    #[test]
+    fn test_requests_without_author_need_the_admin_scope() {
+        let req = request(None, &[Scope::PostsWrite]);
+
+        assert!(matches!(
+            write_precondition(&req),
+            Err(PostRepositoryError::AuthorRequired)
+        ));
+        assert!(matches!(
+            owner_precondition(&req),
+            Err(PostRepositoryError::AuthorRequired)
+        ));
+        assert!(matches!(
+            resolve_author(&req, Some("bruno".to_string()), true),
+            Err(PostRepositoryError::AuthorRequired)
+        ));
+        assert_eq!(
+            resolve_author(
+                &request(None, &[Scope::Admin]),
+                Some("bruno".to_string()),
+                true
+            )
+            .unwrap(),
+            Some("bruno".to_string())
+        );
+    }
+
+    #[test]
+    fn test_authors_write_posts_as_themselves() {
+        let req = request(Some("ana"), &[Scope::PostsWrite]);
+
+        assert_eq!(
+            resolve_author(&req, None, true).unwrap(),
+            Some("ana".to_string())
+        );
+        assert_eq!(resolve_author(&req, None, false).unwrap(), None);
+        assert_eq!(
+            resolve_author(&req, Some("ana".to_string()), false).unwrap(),
+            Some("ana".to_string())
+        );
+        assert!(matches!(
+            resolve_author(&req, Some("bruno".to_string()), true),
+            Err(PostRepositoryError::AuthorNotAllowed(author)) if author == "bruno"
+        ));
+        assert_eq!(
+            resolve_author(
+                &request(Some("ana"), &[Scope::Admin]),
+                Some("bruno".to_string()),
+                true
+            )
+            .unwrap(),
+            Some("bruno".to_string())
+        );
+    }
+}
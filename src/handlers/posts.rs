@@ -1,57 +1,608 @@
-use crate::persistency::{models::FindAllOptions, posts::models::NewPost, traits::PostRepository};
-use actix_web::{web, HttpResponse, Responder};
-use serde_json::json;
+use super::{
+    ownership::{owner_precondition, resolve_author, write_precondition},
+    preconditions::{cacheable_response, content_tag, etag, revision_tag, ReadCacheControl},
+    visibility::{check_listable, check_visible},
+};
+use crate::{
+    models::PostRevision,
+    persistency::{
+        models::FindAllOptions,
+        posts::{
+            errors::PostRepositoryError,
+            models::{
+                NewPost, PostPatch, PostRevisionDiff, PostRevisionDiffQuery, PostsFilter,
+                SavePostOptions, SavePostQuery, SearchPostsQuery, SlugLookup,
+            },
+        },
+        traits::PostRepository,
+    },
+};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
 
 pub async fn create_post<T: PostRepository>(
+    req: HttpRequest,
     post_repo: web::Data<T>,
+    defaults: web::Data<SavePostOptions>,
+    save: web::Query<SavePostQuery>,
     post: web::Json<NewPost>,
-) -> impl Responder {
-    match post_repo.create(post.into_inner()).await {
-        Ok(post) => HttpResponse::Created().json(post),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+) -> Result<HttpResponse, PostRepositoryError> {
+    let mut post = post.into_inner();
+    post.author = resolve_author(&req, post.author, true)?;
+
+    let post = post_repo.create(post, save.resolve(**defaults)).await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header(etag(post.revision))
+        .json(post))
 }
 
 pub async fn find_all_posts<T: PostRepository>(
+    req: HttpRequest,
     post_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
     query: web::Query<FindAllOptions>,
-) -> impl Responder {
-    match post_repo.find_all(query.into_inner()).await {
-        Ok(posts) => HttpResponse::Ok().json(posts),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+    filter: web::Query<PostsFilter>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    check_listable(&req, &filter)?;
+    let posts = post_repo
+        .find_all(query.into_inner(), filter.into_inner())
+        .await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        content_tag(&posts),
+        None,
+        &posts,
+    ))
 }
 
+pub async fn search_posts<T: PostRepository>(
+    post_repo: web::Data<T>,
+    search: web::Query<SearchPostsQuery>,
+    query: web::Query<FindAllOptions>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let posts = post_repo.search(&search.q, query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(posts))
+}
+
+/// Serves post `id`, to clients that may write posts only unless it is published.
 pub async fn get_post<T: PostRepository>(
+    req: HttpRequest,
     post_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
     id: web::Path<String>,
-) -> impl Responder {
-    match post_repo.get(id.into_inner().as_str()).await {
-        Ok(post) => HttpResponse::Ok().json(post),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+) -> Result<HttpResponse, PostRepositoryError> {
+    let post = post_repo.get(id.into_inner().as_str()).await?;
+    check_visible(&req, &post)?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        revision_tag(post.revision),
+        Some(post.updated_at),
+        &post,
+    ))
+}
+
+/// Serves the post going by the slug, or permanently redirects a former slug of a post to its
+/// current one. Posts hidden from the client are not found by either, so former slugs of drafts
+/// do not give their current slug away.
+pub async fn get_post_by_slug<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let slug = slug.into_inner();
+    let not_found = || PostRepositoryError::PostNotFound(slug.clone());
+    let (post, redirect) = match post_repo.get_by_slug(slug.as_str()).await? {
+        SlugLookup::Post(post) => (post, false),
+        SlugLookup::Redirect(current) => match post_repo.get_by_slug(current.as_str()).await {
+            Ok(SlugLookup::Post(post)) => (post, true),
+            // The post was renamed again or removed since the redirect was read.
+            Ok(SlugLookup::Redirect(_)) | Err(PostRepositoryError::PostNotFound(_)) => {
+                return Err(not_found())
+            }
+            Err(error) => return Err(error),
+        },
+    };
+    check_visible(&req, &post).map_err(|_| not_found())?;
+
+    if redirect {
+        return Ok(HttpResponse::MovedPermanently()
+            .insert_header((
+                header::LOCATION,
+                format!("/api/v1/posts/by-slug/{}", post.slug),
+            ))
+            .finish());
     }
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        revision_tag(post.revision),
+        Some(post.updated_at),
+        &post,
+    ))
 }
 
 pub async fn update_post<T: PostRepository>(
+    req: HttpRequest,
     post_repo: web::Data<T>,
     id: web::Path<String>,
+    defaults: web::Data<SavePostOptions>,
+    save: web::Query<SavePostQuery>,
     post: web::Json<NewPost>,
-) -> impl Responder {
-    match post_repo
-        .update(id.into_inner().as_str(), post.into_inner())
-        .await
-    {
-        Ok(post) => HttpResponse::Ok().json(post),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+    let mut post = post.into_inner();
+    post.author = resolve_author(&req, post.author, false)?;
+
+    let post = post_repo
+        .update(
+            id.as_str(),
+            post,
+            save.resolve(**defaults),
+            write_precondition(&req)?,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
+}
+
+pub async fn patch_post<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    id: web::Path<String>,
+    defaults: web::Data<SavePostOptions>,
+    save: web::Query<SavePostQuery>,
+    patch: web::Json<PostPatch>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+    let mut patch = patch.into_inner();
+    patch.author = resolve_author(&req, patch.author, false)?;
+
+    let post = post_repo
+        .patch(
+            id.as_str(),
+            patch,
+            save.resolve(**defaults),
+            write_precondition(&req)?,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
+}
+
+pub async fn add_post_tag<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    path: web::Path<(String, String)>,
+    defaults: web::Data<SavePostOptions>,
+    save: web::Query<SavePostQuery>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let (id, name) = path.into_inner();
+    let post = post_repo
+        .add_tags(
+            id.as_str(),
+            vec![name],
+            save.resolve(**defaults),
+            owner_precondition(&req)?,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
+}
+
+pub async fn remove_post_tag<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let (id, name) = path.into_inner();
+    let post = post_repo
+        .remove_tags(id.as_str(), vec![name], owner_precondition(&req)?)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
+}
+
+pub async fn find_post_revisions<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
+    id: web::Path<String>,
+    query: web::Query<FindAllOptions>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+    check_visible(&req, &post_repo.get(&id).await?)?;
+    let revisions = post_repo.revisions(id.as_str(), query.into_inner()).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        content_tag(&revisions),
+        None,
+        &revisions,
+    ))
+}
+
+pub async fn get_post_revision<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
+    path: web::Path<(String, u64)>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let (id, revision) = path.into_inner();
+    check_visible(&req, &post_repo.get(&id).await?)?;
+    let revision = post_repo.get_revision(id.as_str(), revision).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        revision_tag(revision.revision),
+        Some(revision.created_at),
+        &revision,
+    ))
+}
+
+pub async fn diff_post_revisions<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    path: web::Path<(String, u64)>,
+    query: web::Query<PostRevisionDiffQuery>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let (id, to) = path.into_inner();
+    if let Some(from) = query.from.filter(|from| *from >= to) {
+        return Err(PostRepositoryError::InvalidRevisionRange(from, to));
     }
+    check_visible(&req, &post_repo.get(&id).await?)?;
+
+    let to = post_repo.get_revision(id.as_str(), to).await?;
+    let from = match query.from {
+        Some(from) => post_repo.get_revision(id.as_str(), from).await?,
+        None if to.revision > 1 => post_repo.get_revision(id.as_str(), to.revision - 1).await?,
+        // The first revision is diffed against an empty post, so everything in it is added.
+        None => PostRevision {
+            revision: 0,
+            title: String::new(),
+            content: String::new(),
+            tags: vec![],
+            created_at: to.created_at,
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(PostRevisionDiff::between(&from, &to)))
+}
+
+pub async fn restore_post_revision<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    path: web::Path<(String, u64)>,
+    defaults: web::Data<SavePostOptions>,
+    save: web::Query<SavePostQuery>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let (id, revision) = path.into_inner();
+    let post = post_repo
+        .restore_revision(
+            id.as_str(),
+            revision,
+            save.resolve(**defaults),
+            write_precondition(&req)?,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
 }
 
 pub async fn delete_post<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+
+    post_repo
+        .delete(id.as_str(), write_precondition(&req)?)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn find_deleted_posts<T: PostRepository>(
+    post_repo: web::Data<T>,
+    query: web::Query<FindAllOptions>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let posts = post_repo.find_deleted(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(posts))
+}
+
+pub async fn restore_deleted_post<T: PostRepository>(
+    req: HttpRequest,
+    post_repo: web::Data<T>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+
+    let post = post_repo
+        .restore_deleted(id.as_str(), write_precondition(&req)?)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(post.revision))
+        .json(post))
+}
+
+pub async fn purge_post<T: PostRepository>(
+    req: HttpRequest,
     post_repo: web::Data<T>,
     id: web::Path<String>,
-) -> impl Responder {
-    match post_repo.delete(id.into_inner().as_str()).await {
-        Ok(_) => HttpResponse::NoContent().finish(),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+) -> Result<HttpResponse, PostRepositoryError> {
+    let id = id.into_inner();
+
+    post_repo
+        .purge(id.as_str(), write_precondition(&req)?)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handlers::{ownership::GrantedScopes, preconditions::if_match},
+        models::{Post, PostStatus, Scope},
+        persistency::{
+            authors::memory_authors_repository::MemoryAuthorsRepository,
+            memory::MemoryDatabase,
+            models::Precondition,
+            posts::memory_posts_repository::MemoryPostsRepository,
+            tags::{memory_tags_repository::MemoryTagsRepository, models::NewTag},
+            traits::TagRepository,
+        },
+    };
+    use actix_web::{
+        dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+        http::StatusCode,
+        test::TestRequest,
+        App, HttpMessage,
+    };
+
+    type Posts = MemoryPostsRepository<MemoryTagsRepository, MemoryAuthorsRepository>;
+
+    fn repositories() -> (Posts, MemoryTagsRepository) {
+        let db = MemoryDatabase::default();
+        let tags = MemoryTagsRepository::new(db.clone());
+        let posts =
+            MemoryPostsRepository::new(db.clone(), tags.clone(), MemoryAuthorsRepository::new(db));
+
+        (posts, tags)
+    }
+
+    fn app(
+        posts: Posts,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        App::new()
+            .app_data(web::Data::new(posts))
+            .app_data(web::Data::new(ReadCacheControl("no-cache".to_string())))
+            .route("/api/v1/posts/{id}", web::get().to(get_post::<Posts>))
+            .route(
+                "/api/v1/posts/by-slug/{slug}",
+                web::get().to(get_post_by_slug::<Posts>),
+            )
+            .route(
+                "/api/v1/posts/{id}/revisions/{revision}/diff",
+                web::get().to(diff_post_revisions::<Posts>),
+            )
+    }
+
+    async fn create_post(posts: &Posts, tags: &[&str]) -> Post {
+        posts
+            .create(
+                NewPost {
+                    slug: None,
+                    title: "Hello".to_string(),
+                    content: "World".to_string(),
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                    author: None,
+                    status: None,
+                    publish_at: None,
+                },
+                SavePostOptions {
+                    create_missing_tags: true,
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_post_validators_change_with_renamed_tags() {
+        let (posts, tags) = repositories();
+        let post = create_post(&posts, &["rust"]).await;
+        let app = actix_web::test::init_service(app(posts)).await;
+        let uri = format!("/api/v1/posts/{}", post.id);
+
+        let response =
+            actix_web::test::call_service(&app, TestRequest::get().uri(&uri).to_request()).await;
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+        assert_eq!(etag, r#""1""#);
+        assert!(response.headers().get(header::LAST_MODIFIED).is_some());
+
+        let unchanged = TestRequest::get()
+            .uri(&uri)
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        assert_eq!(
+            actix_web::test::call_service(&app, unchanged)
+                .await
+                .status(),
+            StatusCode::NOT_MODIFIED
+        );
+
+        tags.update(
+            "rust",
+            NewTag {
+                name: "rustlang".to_string(),
+            },
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+        let renamed = TestRequest::get()
+            .uri(&uri)
+            .insert_header((header::IF_NONE_MATCH, etag))
+            .to_request();
+        let response = actix_web::test::call_service(&app, renamed).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+        let body: Post = actix_web::test::read_body_json(response).await;
+        assert_eq!(body.revision, post.revision + 1);
+        assert_eq!(body.tags[0].name, "rustlang");
+
+        let write = TestRequest::put()
+            .uri(&uri)
+            .insert_header((header::IF_MATCH, etag))
+            .to_http_request();
+        assert_eq!(if_match(&write), Precondition::at(vec![body.revision]));
+    }
+
+    #[actix_web::test]
+    async fn test_first_revision_is_diffed_against_an_empty_post() {
+        let (posts, _) = repositories();
+        let post = create_post(&posts, &["rust"]).await;
+        let app = actix_web::test::init_service(app(posts)).await;
+
+        let req = TestRequest::get()
+            .uri(&format!("/api/v1/posts/{}/revisions/1/diff", post.id))
+            .to_request();
+        let response = actix_web::test::call_service(&app, req).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let diff: PostRevisionDiff = actix_web::test::read_body_json(response).await;
+        assert_eq!(diff.from, 0);
+        assert_eq!(diff.to, 1);
+        assert_eq!(diff.title, "+Hello");
+        assert_eq!(diff.content, "+World");
+        assert_eq!(diff.tags.added, vec!["rust"]);
+        assert!(diff.tags.removed.is_empty());
+
+        let req = TestRequest::get()
+            .uri(&format!(
+                "/api/v1/posts/{}/revisions/1/diff?from=1",
+                post.id
+            ))
+            .to_request();
+        assert_eq!(
+            actix_web::test::call_service(&app, req).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_unpublished_posts_are_only_served_to_writers() {
+        let (posts, _) = repositories();
+        let draft = posts
+            .create(
+                NewPost {
+                    slug: None,
+                    title: "Draft".to_string(),
+                    content: "Not yet".to_string(),
+                    tags: Default::default(),
+                    author: None,
+                    status: Some(PostStatus::Draft),
+                    publish_at: None,
+                },
+                SavePostOptions::default(),
+            )
+            .await
+            .unwrap();
+        let app = actix_web::test::init_service(app(posts)).await;
+        let get = |scopes: &[Scope]| {
+            let req = TestRequest::get()
+                .uri(&format!("/api/v1/posts/{}", draft.id))
+                .to_request();
+            req.extensions_mut().insert(GrantedScopes(scopes.to_vec()));
+
+            req
+        };
+
+        let response = actix_web::test::call_service(&app, get(&Scope::READ)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = actix_web::test::call_service(&app, get(&Scope::ALL)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_former_slugs_of_unpublished_posts_are_not_found() {
+        let (posts, _) = repositories();
+        let new_draft = |slug: &str| NewPost {
+            slug: Some(slug.to_string()),
+            title: "Draft".to_string(),
+            content: "Not yet".to_string(),
+            tags: Default::default(),
+            author: None,
+            status: Some(PostStatus::Draft),
+            publish_at: None,
+        };
+        let draft = posts
+            .create(new_draft("hello"), SavePostOptions::default())
+            .await
+            .unwrap();
+        posts
+            .update(
+                &draft.id,
+                new_draft("secret-launch"),
+                SavePostOptions::default(),
+                Precondition::ANY,
+            )
+            .await
+            .unwrap();
+        let app = actix_web::test::init_service(app(posts)).await;
+        let get = |scopes: &[Scope]| {
+            let req = TestRequest::get()
+                .uri("/api/v1/posts/by-slug/hello")
+                .to_request();
+            req.extensions_mut().insert(GrantedScopes(scopes.to_vec()));
+
+            req
+        };
+
+        let response = actix_web::test::call_service(&app, get(&Scope::READ)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::LOCATION).is_none());
+        let body = actix_web::test::read_body(response).await;
+        let body = String::from_utf8_lossy(&body);
+        assert!(!body.contains("secret-launch") && !body.contains(&draft.id));
+
+        let response = actix_web::test::call_service(&app, get(&Scope::ALL)).await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "/api/v1/posts/by-slug/secret-launch"
+        );
     }
 }
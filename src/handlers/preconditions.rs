@@ -0,0 +1,220 @@
+use crate::persistency::models::Precondition;
+use actix_web::{
+    http::header::{
+        self, ETag, EntityTag, HttpDate, IfMatch, IfModifiedSince, IfNoneMatch, LastModified,
+    },
+    HttpMessage, HttpRequest, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+
+/// `Cache-Control` sent with cacheable reads, e.g. `public, max-age=60`.
+#[derive(Debug, Clone)]
+pub struct ReadCacheControl(pub String);
+
+/// Strong entity tag of a revision, e.g. `"3"`, for resources whose body only changes along with
+/// their revision. `If-Match` compares it against the revision a write applies to.
+pub fn revision_tag(revision: u64) -> EntityTag {
+    EntityTag::new_strong(revision.to_string())
+}
+
+/// `ETag` header of a revision, e.g. `ETag: "3"`.
+pub fn etag(revision: u64) -> ETag {
+    ETag(revision_tag(revision))
+}
+
+/// Strong entity tag of a response body without a revision of its own, such as a listing.
+pub fn content_tag(body: &impl Serialize) -> EntityTag {
+    let bytes = serde_json::to_vec(body).unwrap_or_default();
+
+    EntityTag::new_strong(hex::encode(&Sha256::digest(bytes)[..16]))
+}
+
+/// Whether the client's copy is still current. `If-None-Match` takes precedence and compares
+/// entity tags weakly; `If-Modified-Since` is only used without it, and only when the resource
+/// has a modification date.
+fn is_fresh(req: &HttpRequest, tag: &EntityTag, last_modified: Option<DateTime<Utc>>) -> bool {
+    if req.headers().contains_key(header::IF_NONE_MATCH) {
+        return match req.get_header::<IfNoneMatch>() {
+            Some(IfNoneMatch::Any) => true,
+            Some(IfNoneMatch::Items(tags)) => tags.iter().any(|item| item.weak_eq(tag)),
+            None => false,
+        };
+    }
+
+    match (req.get_header::<IfModifiedSince>(), last_modified) {
+        (Some(IfModifiedSince(since)), Some(last_modified)) => {
+            last_modified.timestamp() <= DateTime::<Utc>::from(SystemTime::from(since)).timestamp()
+        }
+        _ => false,
+    }
+}
+
+/// Answers a cacheable read with its validators and `Cache-Control`, and with
+/// `304 Not Modified` instead of `body` when the client's copy is still current.
+pub fn cacheable_response(
+    req: &HttpRequest,
+    cache_control: &ReadCacheControl,
+    tag: EntityTag,
+    last_modified: Option<DateTime<Utc>>,
+    body: &impl Serialize,
+) -> HttpResponse {
+    let fresh = is_fresh(req, &tag, last_modified);
+    let mut response = if fresh {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+
+    response
+        .insert_header(ETag(tag))
+        .insert_header((header::CACHE_CONTROL, cache_control.0.as_str()));
+    if let Some(last_modified) = last_modified {
+        response.insert_header(LastModified(HttpDate::from(SystemTime::from(
+            last_modified,
+        ))));
+    }
+
+    if fresh {
+        response.finish()
+    } else {
+        response.json(body)
+    }
+}
+
+/// Revisions a write may apply to, from its `If-Match` header. `If-Match` compares entity tags
+/// strongly, so weak or malformed tags never match and a malformed header fails every write.
+pub fn if_match(req: &HttpRequest) -> Precondition {
+    if !req.headers().contains_key(header::IF_MATCH) {
+        return Precondition::ANY;
+    }
+
+    match req.get_header::<IfMatch>() {
+        Some(IfMatch::Any) => Precondition::ANY,
+        Some(IfMatch::Items(tags)) => Precondition::at(
+            tags.iter()
+                .filter(|tag| !tag.weak)
+                .filter_map(|tag| tag.tag().parse().ok())
+                .collect(),
+        ),
+        None => Precondition::at(vec![]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use chrono::TimeZone;
+
+    fn precondition(if_match_header: Option<&str>) -> Precondition {
+        let request = match if_match_header {
+            Some(value) => TestRequest::default().insert_header((header::IF_MATCH, value)),
+            None => TestRequest::default(),
+        };
+
+        if_match(&request.to_http_request())
+    }
+
+    #[test]
+    fn test_missing_or_any_if_match_allows_any_revision() {
+        assert_eq!(precondition(None), Precondition::ANY);
+        assert_eq!(precondition(Some("*")), Precondition::ANY);
+    }
+
+    #[test]
+    fn test_if_match_lists_strong_revisions() {
+        assert_eq!(
+            precondition(Some(r#""3", W/"4", "five", "6""#)),
+            Precondition::at(vec![3, 6])
+        );
+    }
+
+    #[test]
+    fn test_malformed_if_match_allows_no_revision() {
+        assert_eq!(precondition(Some("3")), Precondition::at(vec![]));
+    }
+
+    #[test]
+    fn test_etag_round_trips_through_if_match() {
+        let value = etag(7).to_string();
+
+        assert_eq!(precondition(Some(&value)), Precondition::at(vec![7]));
+    }
+
+    fn read(headers: &[(header::HeaderName, &str)]) -> HttpResponse {
+        let request = headers
+            .iter()
+            .fold(TestRequest::default(), |request, (name, value)| {
+                request.insert_header((name.clone(), *value))
+            })
+            .to_http_request();
+        let last_modified = Utc.with_ymd_and_hms(2024, 2, 3, 20, 31, 0).unwrap();
+
+        cacheable_response(
+            &request,
+            &ReadCacheControl("no-cache".to_string()),
+            revision_tag(3),
+            Some(last_modified),
+            &"body",
+        )
+    }
+
+    #[test]
+    fn test_read_without_validators_returns_body() {
+        let response = read(&[]);
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), "\"3\"");
+        assert_eq!(
+            response.headers().get(header::LAST_MODIFIED).unwrap(),
+            "Sat, 03 Feb 2024 20:31:00 GMT"
+        );
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+    }
+
+    #[test]
+    fn test_if_none_match_compares_weakly() {
+        assert_eq!(
+            read(&[(header::IF_NONE_MATCH, r#"W/"3""#)]).status(),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            read(&[(header::IF_NONE_MATCH, r#""2""#)]).status(),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        assert_eq!(
+            read(&[(header::IF_MODIFIED_SINCE, "Sat, 03 Feb 2024 20:31:00 GMT")]).status(),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            read(&[(header::IF_MODIFIED_SINCE, "Sat, 03 Feb 2024 20:30:59 GMT")]).status(),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_if_none_match_takes_precedence_over_if_modified_since() {
+        let response = read(&[
+            (header::IF_NONE_MATCH, r#""2""#),
+            (header::IF_MODIFIED_SINCE, "Sat, 03 Feb 2024 20:31:00 GMT"),
+        ]);
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_content_tag_depends_on_body() {
+        assert_eq!(content_tag(&["a", "b"]), content_tag(&["a", "b"]));
+        assert_ne!(content_tag(&["a", "b"]), content_tag(&["b", "a"]));
+    }
+}
@@ -1,58 +1,109 @@
-use actix_web::{web, HttpResponse, Responder};
-use serde_json::json;
+use actix_web::{web, HttpRequest, HttpResponse};
 
-use crate::persistency::{models::FindAllOptions, tags::models::NewTag, traits::TagRepository};
+use super::preconditions::{
+    cacheable_response, content_tag, etag, if_match, revision_tag, ReadCacheControl,
+};
+use crate::persistency::{
+    models::FindAllOptions,
+    tags::{
+        errors::TagRepositoryError,
+        models::{DeleteTagOptions, NewTag, TagPatch},
+    },
+    traits::TagRepository,
+};
 
 pub async fn create_tag<T: TagRepository>(
     tag_repo: web::Data<T>,
     tag: web::Json<NewTag>,
-) -> impl Responder {
-    match tag_repo.create(tag.into_inner()).await {
-        Ok(tag) => HttpResponse::Created().json(tag),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+) -> Result<HttpResponse, TagRepositoryError> {
+    let tag = tag_repo.create(tag.into_inner()).await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header(etag(tag.revision))
+        .json(tag))
 }
 
 pub async fn find_all_tags<T: TagRepository>(
+    req: HttpRequest,
     tag_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
     query: web::Query<FindAllOptions>,
-) -> impl Responder {
-    match tag_repo.find_all(query.into_inner()).await {
-        Ok(tags) => HttpResponse::Ok().json(tags),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+) -> Result<HttpResponse, TagRepositoryError> {
+    let tags = tag_repo.find_all(query.into_inner()).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        content_tag(&tags),
+        None,
+        &tags,
+    ))
 }
 
 pub async fn get_tag<T: TagRepository>(
+    req: HttpRequest,
     tag_repo: web::Data<T>,
+    cache_control: web::Data<ReadCacheControl>,
     name: web::Path<String>,
-) -> impl Responder {
-    match tag_repo.get(name.into_inner().as_str()).await {
-        Ok(tag) => HttpResponse::Ok().json(tag),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+) -> Result<HttpResponse, TagRepositoryError> {
+    let tag = tag_repo.get(name.into_inner().as_str()).await?;
+
+    Ok(cacheable_response(
+        &req,
+        &cache_control,
+        revision_tag(tag.revision),
+        None,
+        &tag,
+    ))
 }
 
 pub async fn update_tag<T: TagRepository>(
+    req: HttpRequest,
     tag_repo: web::Data<T>,
     name: web::Path<String>,
     tag: web::Json<NewTag>,
-) -> impl Responder {
-    match tag_repo
-        .update(name.into_inner().as_str(), tag.into_inner())
-        .await
-    {
-        Ok(tag) => HttpResponse::Ok().json(tag),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+) -> Result<HttpResponse, TagRepositoryError> {
+    let tag = tag_repo
+        .update(name.into_inner().as_str(), tag.into_inner(), if_match(&req))
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(tag.revision))
+        .json(tag))
+}
+
+pub async fn patch_tag<T: TagRepository>(
+    req: HttpRequest,
+    tag_repo: web::Data<T>,
+    name: web::Path<String>,
+    patch: web::Json<TagPatch>,
+) -> Result<HttpResponse, TagRepositoryError> {
+    let tag = tag_repo
+        .patch(
+            name.into_inner().as_str(),
+            patch.into_inner(),
+            if_match(&req),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(tag.revision))
+        .json(tag))
 }
 
 pub async fn delete_tag<T: TagRepository>(
+    req: HttpRequest,
     tag_repo: web::Data<T>,
     name: web::Path<String>,
-) -> impl Responder {
-    match tag_repo.delete(name.into_inner().as_str()).await {
-        Ok(_) => HttpResponse::NoContent().finish(),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
-    }
+    options: web::Query<DeleteTagOptions>,
+) -> Result<HttpResponse, TagRepositoryError> {
+    tag_repo
+        .delete(
+            name.into_inner().as_str(),
+            options.into_inner(),
+            if_match(&req),
+        )
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
 }
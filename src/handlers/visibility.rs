@@ -0,0 +1,102 @@
+use super::ownership::has_scope;
+use crate::{
+    models::{Post, PostStatus, Scope},
+    persistency::posts::{errors::PostRepositoryError, models::PostsFilter},
+};
+use actix_web::HttpRequest;
+
+/// Hides post `post` from clients that may not write posts unless it is published, as if it did
+/// not exist.
+pub fn check_visible(req: &HttpRequest, post: &Post) -> Result<(), PostRepositoryError> {
+    if post.status == PostStatus::Published || has_scope(req, Scope::PostsWrite) {
+        Ok(())
+    } else {
+        Err(PostRepositoryError::PostNotFound(post.id.clone()))
+    }
+}
+
+/// Refuses to list posts other than published ones to clients that may not write posts.
+pub fn check_listable(req: &HttpRequest, filter: &PostsFilter) -> Result<(), PostRepositoryError> {
+    match filter
+        .status
+        .iter()
+        .find(|status| **status != PostStatus::Published)
+    {
+        Some(status) if !has_scope(req, Scope::PostsWrite) => {
+            Err(PostRepositoryError::StatusNotAllowed(*status))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::ownership::GrantedScopes;
+    use actix_web::{test::TestRequest, HttpMessage};
+    use chrono::Utc;
+
+    fn request(scopes: &[Scope]) -> HttpRequest {
+        let req = TestRequest::default().to_http_request();
+        req.extensions_mut().insert(GrantedScopes(scopes.to_vec()));
+
+        req
+    }
+
+    fn post(status: PostStatus) -> Post {
+        Post {
+            id: "abc".to_string(),
+            slug: "hello".to_string(),
+            title: "Hello".to_string(),
+            content: "World".to_string(),
+            tags: vec![],
+            author: None,
+            status,
+            publish_at: None,
+            published_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            revision: 1,
+        }
+    }
+
+    #[test]
+    fn test_readers_only_see_published_posts() {
+        let reader = request(&[Scope::PostsRead]);
+        let writer = request(&[Scope::PostsRead, Scope::PostsWrite]);
+
+        assert!(check_visible(&reader, &post(PostStatus::Published)).is_ok());
+        for status in [
+            PostStatus::Draft,
+            PostStatus::Scheduled,
+            PostStatus::Archived,
+        ] {
+            assert!(matches!(
+                check_visible(&reader, &post(status)),
+                Err(PostRepositoryError::PostNotFound(id)) if id == "abc"
+            ));
+            assert!(check_visible(&writer, &post(status)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_readers_only_list_published_posts() {
+        let reader = request(&[Scope::PostsRead]);
+        let writer = request(&[Scope::PostsWrite]);
+        let filter = |status: &[PostStatus]| PostsFilter {
+            status: status.to_vec(),
+            ..Default::default()
+        };
+
+        assert!(check_listable(&reader, &filter(&[])).is_ok());
+        assert!(check_listable(&reader, &filter(&[PostStatus::Published])).is_ok());
+        assert!(matches!(
+            check_listable(
+                &reader,
+                &filter(&[PostStatus::Published, PostStatus::Draft])
+            ),
+            Err(PostRepositoryError::StatusNotAllowed(PostStatus::Draft))
+        ));
+        assert!(check_listable(&writer, &filter(&[PostStatus::Draft])).is_ok());
+    }
+}
@@ -1,33 +1,58 @@
-use api::initialize_api;
+use api::{initialize_api, ApiOptions};
+use auth::{
+    api_keys::{generate_api_key, hash_api_key},
+    jwt::JwtAuthenticator,
+    peer_credentials::PeerCredentialsAuthenticator,
+};
+use chrono::Duration;
 use config::{
-    models::Config,
+    models::{ApiBind, ApiKeyCommand, Command, Config, MigrateCommand, SocketOptions},
     strategies::{cli_config_loader::CliConfigLoader, env_config_loader::EnvConfigLoader},
     traits::PartialConfigLoader,
 };
 use logger::initialize_logger;
-use migrations::{exec_migrations, MIGRATIONS};
+use migrations::{migrate_down, migrate_up, migration_status, models::MigrationState, MIGRATIONS};
 use persistency::{
-    posts::surrealdb_posts_repository::SurrealdbPostsRepository,
-    tags::surrealdb_tags_repository::SurrealdbTagsRepository,
-    traits::{PostRepository, TagRepository},
+    api_keys::{
+        memory_api_keys_repository::MemoryApiKeysRepository, models::NewApiKey,
+        surrealdb_api_keys_repository::SurrealdbApiKeysRepository,
+    },
+    authors::{
+        memory_authors_repository::MemoryAuthorsRepository,
+        surrealdb_authors_repository::SurrealdbAuthorsRepository,
+    },
+    memory::{MemoryDatabase, MEMORY_DB_ADDRESS},
+    posts::{
+        memory_posts_repository::MemoryPostsRepository, models::SavePostOptions,
+        surrealdb_posts_repository::SurrealdbPostsRepository,
+    },
+    tags::{
+        memory_tags_repository::MemoryTagsRepository,
+        surrealdb_tags_repository::SurrealdbTagsRepository,
+    },
+    traits::{ApiKeyRepository, AuthorRepository, PostRepository, TagRepository},
 };
-use std::process::exit;
+use std::{io, process::exit};
 use surrealdb::Surreal;
-use tracing::{debug, info};
+use tasks::{publish_scheduled_posts, purge_expired_trash};
+use tracing::{debug, error, info, warn};
+use utils::unix::{group_id, user_id, SocketPermissions};
 
 mod api;
+mod auth;
 mod config;
 mod handlers;
 mod logger;
 mod migrations;
 mod models;
 mod persistency;
+mod tasks;
 mod utils;
 
 fn load_config() -> Config {
     let env_config = EnvConfigLoader::load_partial_config().unwrap_or_else(|e| {
         eprintln!("Failed to load config from environment: {e}");
-        Default::default()
+        exit(1);
     });
     let cli_config = CliConfigLoader::load_partial_config().unwrap_or_else(|e| {
         eprintln!("Failed to load config from CLI: {e}");
@@ -49,13 +74,258 @@ async fn load_db_connection(address: &str) -> Surreal<surrealdb::engine::any::An
 
 async fn create_repositories(
     db: Surreal<surrealdb::engine::any::Any>,
-) -> (impl PostRepository + Clone, impl TagRepository + Clone) {
+) -> (
+    impl PostRepository + Clone,
+    impl TagRepository + Clone,
+    impl AuthorRepository + Clone,
+    impl ApiKeyRepository + Clone,
+) {
     (
-        SurrealdbPostsRepository::new(db.clone(), SurrealdbTagsRepository::new(db.clone())),
+        SurrealdbPostsRepository::new(
+            db.clone(),
+            SurrealdbTagsRepository::new(db.clone()),
+            SurrealdbAuthorsRepository::new(db.clone()),
+        ),
         SurrealdbTagsRepository::new(db.clone()),
+        SurrealdbAuthorsRepository::new(db.clone()),
+        SurrealdbApiKeysRepository::new(db),
     )
 }
 
+fn create_memory_repositories() -> (
+    impl PostRepository + Clone,
+    impl TagRepository + Clone,
+    impl AuthorRepository + Clone,
+    impl ApiKeyRepository + Clone,
+) {
+    let db = MemoryDatabase::default();
+
+    (
+        MemoryPostsRepository::new(
+            db.clone(),
+            MemoryTagsRepository::new(db.clone()),
+            MemoryAuthorsRepository::new(db.clone()),
+        ),
+        MemoryTagsRepository::new(db.clone()),
+        MemoryAuthorsRepository::new(db.clone()),
+        MemoryApiKeysRepository::new(db),
+    )
+}
+
+async fn serve<
+    PR: PostRepository + Clone + Send + 'static,
+    TR: TagRepository + Clone + Send + 'static,
+    AR: AuthorRepository + Clone + Send + 'static,
+    KR: ApiKeyRepository + Clone + Send + 'static,
+>(
+    repositories: (PR, TR, AR, KR),
+    api_bind: ApiBind,
+    options: ApiOptions,
+    trash_retention: Option<Duration>,
+) {
+    if let Some(retention) = trash_retention {
+        info!(
+            "Purging posts kept in the trash for more than {} days",
+            retention.num_days()
+        );
+        actix_web::rt::spawn(purge_expired_trash(repositories.0.clone(), retention));
+    }
+
+    actix_web::rt::spawn(publish_scheduled_posts(repositories.0.clone()));
+
+    info!("Starting server on {:?}", api_bind);
+    initialize_api(repositories, api_bind, options)
+        .await
+        .unwrap();
+}
+
+/// Ids of the users and groups named by `socket`.
+fn resolve_socket_options(
+    socket: &SocketOptions,
+) -> io::Result<(SocketPermissions, Option<PeerCredentialsAuthenticator>)> {
+    let permissions = SocketPermissions {
+        owner: socket.owner.as_deref().map(user_id).transpose()?,
+        group: socket.group.as_deref().map(group_id).transpose()?,
+        mode: socket.mode,
+    };
+
+    let peer_credentials = match socket.authorizes_peers() {
+        true => Some(PeerCredentialsAuthenticator::new(
+            socket
+                .trusted_users
+                .iter()
+                .map(|user| user_id(user))
+                .collect::<io::Result<_>>()?,
+            socket
+                .trusted_groups
+                .iter()
+                .map(|group| group_id(group))
+                .collect::<io::Result<_>>()?,
+            socket.peer_read,
+        )),
+        false => None,
+    };
+
+    Ok((permissions, peer_credentials))
+}
+
+async fn migrate(db_address: &str, command: MigrateCommand) {
+    if db_address == MEMORY_DB_ADDRESS {
+        eprintln!("In-memory databases have no schema to migrate");
+        exit(1);
+    }
+
+    debug!("Connecting to database...");
+    let db = load_db_connection(db_address).await;
+    debug!("Database connected");
+
+    let result = match command {
+        MigrateCommand::Status => migration_status(&db, MIGRATIONS).await.map(|statuses| {
+            for status in statuses {
+                let (state, applied_at) = match status.state {
+                    MigrationState::Pending => ("pending", None),
+                    MigrationState::Applied { applied_at } => ("applied", Some(applied_at)),
+                    MigrationState::Modified { applied_at } => ("modified", Some(applied_at)),
+                    MigrationState::Unknown { applied_at } => ("unknown", Some(applied_at)),
+                };
+                let applied_at = applied_at.map(|at| at.to_rfc3339()).unwrap_or_default();
+
+                println!("{state:<8}  {applied_at:<32}  {}", status.id);
+            }
+        }),
+        MigrateCommand::Up { to } => migrate_up(&db, MIGRATIONS, to.as_deref())
+            .await
+            .map(|executed| executed.iter().for_each(|id| println!("applied  {id}"))),
+        MigrateCommand::Down { to } => migrate_down(&db, MIGRATIONS, to.as_deref())
+            .await
+            .map(|executed| executed.iter().for_each(|id| println!("reverted {id}"))),
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("Migration failed: {e}");
+        exit(1);
+    });
+}
+
+async fn repair(db_address: &str, dry_run: bool) {
+    if db_address == MEMORY_DB_ADDRESS {
+        eprintln!("In-memory databases start empty, there is nothing to repair");
+        exit(1);
+    }
+
+    debug!("Connecting to database...");
+    let db = load_db_connection(db_address).await;
+    debug!("Database connected");
+
+    let (posts, ..) = create_repositories(db).await;
+    let result = if dry_run {
+        posts.find_dangling_relations().await
+    } else {
+        posts.remove_dangling_relations().await
+    };
+    let relations = result.unwrap_or_else(|e| {
+        eprintln!("Repair failed: {e}");
+        exit(1);
+    });
+
+    for relation in &relations {
+        println!("posts:{} -> tags:{}", relation.post, relation.tag);
+    }
+    let verb = if dry_run { "found" } else { "removed" };
+    println!("{verb} {} dangling tag relations", relations.len());
+}
+
+async fn run_api_key_command(
+    api_keys: &impl ApiKeyRepository,
+    authors: &impl AuthorRepository,
+    command: ApiKeyCommand,
+) -> anyhow::Result<()> {
+    match command {
+        ApiKeyCommand::Create {
+            name,
+            scopes,
+            author,
+        } => {
+            if let Some(author) = &author {
+                authors.get(author).await?;
+            }
+            let mut unique_scopes = Vec::with_capacity(scopes.len());
+            for scope in scopes {
+                if !unique_scopes.contains(&scope) {
+                    unique_scopes.push(scope);
+                }
+            }
+
+            let key = generate_api_key();
+            let api_key = api_keys
+                .create(NewApiKey {
+                    name,
+                    key_hash: hash_api_key(&key),
+                    scopes: unique_scopes,
+                    author,
+                })
+                .await?;
+
+            eprintln!(
+                "Created API key {}, store it now as it cannot be shown again:",
+                api_key.name
+            );
+            println!("{key}");
+        }
+        ApiKeyCommand::List => {
+            for api_key in api_keys.find_all().await? {
+                let scopes = api_key
+                    .scopes
+                    .iter()
+                    .map(|scope| scope.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let author = api_key.author.as_deref().unwrap_or("-");
+
+                println!(
+                    "{:<24}  {:<48}  {:<16}  {}",
+                    api_key.name,
+                    scopes,
+                    author,
+                    api_key.created_at.to_rfc3339()
+                );
+            }
+        }
+        ApiKeyCommand::Revoke { name } => {
+            api_keys.delete(&name).await?;
+            println!("revoked  {name}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn manage_api_keys(db_address: &str, command: ApiKeyCommand) {
+    if db_address == MEMORY_DB_ADDRESS {
+        eprintln!(
+            "In-memory databases only live as long as the server, they cannot store API keys"
+        );
+        exit(1);
+    }
+
+    debug!("Connecting to database...");
+    let db = load_db_connection(db_address).await;
+    debug!("Database connected");
+
+    migrate_up(&db, MIGRATIONS, None).await.unwrap_or_else(|e| {
+        eprintln!("Failed to run migrations: {e}");
+        exit(1);
+    });
+
+    let (_, _, authors, api_keys) = create_repositories(db).await;
+    run_api_key_command(&api_keys, &authors, command)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("API key command failed: {e}");
+            exit(1);
+        });
+}
+
 #[actix_web::main]
 async fn main() {
     let config = load_config();
@@ -63,21 +333,91 @@ async fn main() {
     initialize_logger(&config.log_level);
     debug!(?config);
 
-    debug!("Connecting to database...");
-    let db = load_db_connection(&config.db_address).await;
-    debug!("Database connected");
+    match config.command {
+        Command::Serve => {}
+        Command::Migrate(command) => {
+            migrate(&config.db_address, command).await;
+            return;
+        }
+        Command::Repair { dry_run } => {
+            repair(&config.db_address, dry_run).await;
+            return;
+        }
+        Command::ApiKey(command) => {
+            manage_api_keys(&config.db_address, command).await;
+            return;
+        }
+    }
 
-    // TODO: Find more elegant way to do this
-    exec_migrations(&db, MIGRATIONS).await;
+    let jwt = config
+        .jwt
+        .map(JwtAuthenticator::new)
+        .transpose()
+        .unwrap_or_else(|e| {
+            error!("Failed to set up bearer token authentication: {e}");
+            exit(1);
+        });
 
-    debug!("Loading repositories...");
-    let (post_repository, tag_repository) = create_repositories(db).await;
-    debug!("Repositories loaded");
+    if matches!(config.api_bind, ApiBind::Tcp(_)) && config.socket != SocketOptions::default() {
+        warn!("Unix socket settings are ignored when binding to TCP");
+    }
+    let (socket_permissions, peer_credentials) = resolve_socket_options(&config.socket)
+        .unwrap_or_else(|e| {
+            error!("Failed to set up the unix socket: {e}");
+            exit(1);
+        });
 
-    info!("Starting server on {:?}", config.api_bind);
-    initialize_api((post_repository, tag_repository), config.api_bind)
-        .await
-        .unwrap();
+    let options = ApiOptions {
+        save_post_defaults: SavePostOptions {
+            create_missing_tags: config.create_missing_tags,
+        },
+        read_cache_control: config.cache_control,
+        require_auth: config.require_auth,
+        jwt,
+        socket_permissions,
+        peer_credentials,
+    };
+
+    if config.db_address == MEMORY_DB_ADDRESS {
+        if config.require_auth && options.jwt.is_none() && options.peer_credentials.is_none() {
+            warn!("In-memory databases start without API keys, every request will be rejected");
+        }
+
+        debug!("Loading in-memory repositories...");
+        let repositories = create_memory_repositories();
+        debug!("Repositories loaded");
+
+        serve(
+            repositories,
+            config.api_bind,
+            options,
+            config.trash_retention,
+        )
+        .await;
+    } else {
+        debug!("Connecting to database...");
+        let db = load_db_connection(&config.db_address).await;
+        debug!("Database connected");
+
+        debug!("Running migrations...");
+        let executed = migrate_up(&db, MIGRATIONS, None).await.unwrap_or_else(|e| {
+            error!("Failed to run migrations: {e}");
+            exit(1);
+        });
+        debug!("Migrations applied: {executed:?}");
+
+        debug!("Loading repositories...");
+        let repositories = create_repositories(db).await;
+        debug!("Repositories loaded");
+
+        serve(
+            repositories,
+            config.api_bind,
+            options,
+            config.trash_retention,
+        )
+        .await;
+    }
 
     info!("Shutting down...");
 }
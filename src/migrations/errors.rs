@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Database(#[from] surrealdb::Error),
+
+    #[error("Failed to read applied migrations: {0}")]
+    AppliedListing(surrealdb::Error),
+
+    #[error("Migration {id} failed: {source}")]
+    MigrationFailed {
+        id: &'static str,
+        source: surrealdb::Error,
+    },
+
+    #[error("Applied migration {0} was modified after being applied")]
+    ChecksumMismatch(String),
+
+    #[error("Applied migration {0} is unknown to this version")]
+    UnknownMigration(String),
+
+    #[error("No migration with id {0}")]
+    UnknownTarget(String),
+}
@@ -0,0 +1,457 @@
+use self::{
+    errors::MigrationError,
+    models::{Migration, MigrationState, MigrationStatus, SurrealAppliedMigration},
+};
+use std::collections::HashMap;
+use surrealdb::{error::Db, Surreal};
+use tracing::{debug, info};
+
+pub mod errors;
+pub mod models;
+
+macro_rules! migration {
+    ($id:literal) => {
+        Migration {
+            id: $id,
+            up: include_str!(concat!("../../migrations/", $id, "/up.surql")),
+            down: include_str!(concat!("../../migrations/", $id, "/down.surql")),
+        }
+    };
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    migration!("202402032031-create_tags"),
+    migration!("202402032035-create_posts"),
+    migration!("202402032036-create_posts_tags"),
+    migration!("202610181030-create_posts_search"),
+    migration!("202610181100-post_timestamps"),
+    migration!("202610181130-revisions"),
+    migration!("202610181200-create_post_revisions"),
+    migration!("202610181230-post_trash"),
+    migration!("202610181300-post_status"),
+    migration!("202610181330-post_slugs"),
+    migration!("202610181400-create_authors"),
+    migration!("202610181500-create_api_keys"),
+];
+
+/// Picks the statement that actually failed: when a transaction is aborted, every other statement
+/// of it reports `QueryNotExecuted`.
+fn statement_error(mut response: surrealdb::Response) -> Option<surrealdb::Error> {
+    let errors = response.take_errors();
+    let mut errors = errors.into_iter().collect::<Vec<_>>();
+    errors.sort_by_key(|(index, _)| *index);
+
+    let (failed, not_executed) = errors
+        .into_iter()
+        .map(|(_, error)| error)
+        .partition::<Vec<_>, _>(|error| {
+            !matches!(error, surrealdb::Error::Db(Db::QueryNotExecuted))
+        });
+
+    failed.into_iter().chain(not_executed).next()
+}
+
+async fn list_applied_migrations(
+    db: &Surreal<surrealdb::engine::any::Any>,
+) -> Result<Vec<SurrealAppliedMigration>, MigrationError> {
+    db.query(include_str!("./queries/create_migrations_table.surql"))
+        .await?
+        .check()?;
+
+    let applied = db
+        .query(include_str!("./queries/list_applied_migrations.surql"))
+        .await?
+        .take::<Vec<SurrealAppliedMigration>>(0)
+        .map_err(MigrationError::AppliedListing)?;
+
+    Ok(applied)
+}
+
+/// Fails if an applied migration was edited after being applied or is not part of `migrations`.
+fn validate_applied(
+    applied: &[SurrealAppliedMigration],
+    migrations: &[Migration],
+) -> Result<(), MigrationError> {
+    let known = migrations
+        .iter()
+        .map(|migration| (migration.id, migration))
+        .collect::<HashMap<_, _>>();
+
+    for record in applied {
+        let migration = known
+            .get(record.migration_id.as_str())
+            .ok_or_else(|| MigrationError::UnknownMigration(record.migration_id.clone()))?;
+
+        if migration.checksum() != record.checksum {
+            return Err(MigrationError::ChecksumMismatch(
+                record.migration_id.clone(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn find_target<'a>(migrations: &'a [Migration], id: &str) -> Result<&'a Migration, MigrationError> {
+    migrations
+        .iter()
+        .find(|migration| migration.id == id)
+        .ok_or_else(|| MigrationError::UnknownTarget(id.to_string()))
+}
+
+async fn run_migration_script(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migration: &Migration,
+    query: String,
+) -> Result<(), MigrationError> {
+    let response = db
+        .query(query)
+        .bind(("migration_id", migration.id))
+        .bind(("checksum", migration.checksum()))
+        .await?;
+
+    match statement_error(response) {
+        Some(source) => Err(MigrationError::MigrationFailed {
+            id: migration.id,
+            source,
+        }),
+        None => Ok(()),
+    }
+}
+
+async fn apply_migration(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migration: &Migration,
+) -> Result<(), MigrationError> {
+    let query = include_str!("./queries/apply_migration.surql").replace("{up}", migration.up);
+
+    run_migration_script(db, migration, query).await
+}
+
+async fn revert_migration(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migration: &Migration,
+) -> Result<(), MigrationError> {
+    let query = include_str!("./queries/revert_migration.surql").replace("{down}", migration.down);
+
+    run_migration_script(db, migration, query).await
+}
+
+/// Applies, in order, every migration not yet recorded in the `migrations` table, up to and
+/// including `to` when given, each one in its own transaction. Fails without applying anything if
+/// an applied migration was edited or is not part of `migrations`, and returns the ids of the
+/// migrations it applied.
+pub async fn migrate_up(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migrations: &[Migration],
+    to: Option<&str>,
+) -> Result<Vec<&'static str>, MigrationError> {
+    let target = to.map(|id| find_target(migrations, id)).transpose()?;
+    let applied = list_applied_migrations(db).await?;
+    validate_applied(&applied, migrations)?;
+
+    let mut pending = migrations
+        .iter()
+        .filter(|migration| {
+            !applied
+                .iter()
+                .any(|record| record.migration_id == migration.id)
+        })
+        .filter(|migration| target.is_none_or(|target| migration.id <= target.id))
+        .collect::<Vec<_>>();
+    pending.sort_by_key(|migration| migration.id);
+
+    let mut executed = vec![];
+    for migration in pending {
+        debug!("Applying migration {}...", migration.id);
+        apply_migration(db, migration).await?;
+        info!("Applied migration {}", migration.id);
+
+        executed.push(migration.id);
+    }
+
+    Ok(executed)
+}
+
+/// Rolls back, newest first, every applied migration after `to`, or only the latest applied one
+/// when no target is given, each one in its own transaction. Returns the ids of the migrations it
+/// rolled back.
+pub async fn migrate_down(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migrations: &[Migration],
+    to: Option<&str>,
+) -> Result<Vec<&'static str>, MigrationError> {
+    let target = to.map(|id| find_target(migrations, id)).transpose()?;
+    let applied = list_applied_migrations(db).await?;
+    validate_applied(&applied, migrations)?;
+
+    let mut reverting = migrations
+        .iter()
+        .filter(|migration| {
+            applied
+                .iter()
+                .any(|record| record.migration_id == migration.id)
+        })
+        .collect::<Vec<_>>();
+    reverting.sort_by_key(|migration| std::cmp::Reverse(migration.id));
+    match target {
+        Some(target) => reverting.retain(|migration| migration.id > target.id),
+        None => reverting.truncate(1),
+    }
+
+    let mut executed = vec![];
+    for migration in reverting {
+        debug!("Rolling back migration {}...", migration.id);
+        revert_migration(db, migration).await?;
+        info!("Rolled back migration {}", migration.id);
+
+        executed.push(migration.id);
+    }
+
+    Ok(executed)
+}
+
+/// Lists every migration, known or only recorded as applied, in id order.
+pub async fn migration_status(
+    db: &Surreal<surrealdb::engine::any::Any>,
+    migrations: &[Migration],
+) -> Result<Vec<MigrationStatus>, MigrationError> {
+    let applied = list_applied_migrations(db).await?;
+
+    let mut statuses = migrations
+        .iter()
+        .map(|migration| {
+            let record = applied
+                .iter()
+                .find(|record| record.migration_id == migration.id);
+            let state = match record {
+                None => MigrationState::Pending,
+                Some(record) if record.checksum == migration.checksum() => {
+                    MigrationState::Applied {
+                        applied_at: record.applied_at.0,
+                    }
+                }
+                Some(record) => MigrationState::Modified {
+                    applied_at: record.applied_at.0,
+                },
+            };
+
+            MigrationStatus {
+                id: migration.id.to_string(),
+                state,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    statuses.extend(
+        applied
+            .iter()
+            .filter(|record| {
+                !migrations
+                    .iter()
+                    .any(|migration| migration.id == record.migration_id)
+            })
+            .map(|record| MigrationStatus {
+                id: record.migration_id.clone(),
+                state: MigrationState::Unknown {
+                    applied_at: record.applied_at.0,
+                },
+            }),
+    );
+    statuses.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn connect() -> Surreal<surrealdb::engine::any::Any> {
+        let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+        db.use_ns("iemanjad").use_db("posts").await.unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_is_idempotent() {
+        let db = connect().await;
+
+        let first = migrate_up(&db, MIGRATIONS, None).await.unwrap();
+        let second = migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        assert_eq!(first.len(), MIGRATIONS.len());
+        assert!(second.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_applies_only_pending() {
+        let db = connect().await;
+
+        migrate_up(&db, &MIGRATIONS[..2], None).await.unwrap();
+        let executed = migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        assert_eq!(
+            executed,
+            MIGRATIONS[2..]
+                .iter()
+                .map(|migration| migration.id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_rejects_edited_migrations() {
+        let db = connect().await;
+        migrate_up(&db, &MIGRATIONS[..1], None).await.unwrap();
+
+        let edited = [Migration {
+            id: MIGRATIONS[0].id,
+            up: "DEFINE TABLE tags SCHEMALESS;",
+            down: MIGRATIONS[0].down,
+        }];
+        let result = migrate_up(&db, &edited, None).await;
+
+        assert!(
+            matches!(result, Err(MigrationError::ChecksumMismatch(id)) if id == MIGRATIONS[0].id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_rejects_unknown_migrations() {
+        let db = connect().await;
+        migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        let result = migrate_up(&db, &MIGRATIONS[..1], None).await;
+
+        assert!(
+            matches!(result, Err(MigrationError::UnknownMigration(id)) if id == MIGRATIONS[1].id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration_is_not_recorded() {
+        let db = connect().await;
+        let broken = [Migration {
+            id: "000000000000-broken",
+            up: "DEFINE TABLE broken SCHEMAFULL; THROW 'broken';",
+            down: "REMOVE TABLE broken;",
+        }];
+
+        let result = migrate_up(&db, &broken, None).await;
+        let applied = list_applied_migrations(&db).await.unwrap();
+
+        assert!(matches!(
+            result,
+            Err(MigrationError::MigrationFailed { id, .. }) if id == broken[0].id
+        ));
+        assert!(applied.is_empty());
+    }
+
+    fn ids(migrations: &[Migration]) -> Vec<&'static str> {
+        migrations.iter().map(|migration| migration.id).collect()
+    }
+
+    async fn table_names(db: &Surreal<surrealdb::engine::any::Any>) -> Vec<String> {
+        let info = db
+            .query("INFO FOR DB")
+            .await
+            .unwrap()
+            .take::<Option<serde_json::Value>>(0)
+            .unwrap()
+            .unwrap_or_default();
+        let mut names = info["tables"]
+            .as_object()
+            .map(|tables| tables.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        names.sort();
+
+        names
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_stops_at_target() {
+        let db = connect().await;
+
+        let executed = migrate_up(&db, MIGRATIONS, Some(MIGRATIONS[1].id))
+            .await
+            .unwrap();
+
+        assert_eq!(executed, ids(&MIGRATIONS[..2]));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_down_reverts_latest_migration() {
+        let db = connect().await;
+        migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        let executed = migrate_down(&db, MIGRATIONS, None).await.unwrap();
+
+        assert_eq!(executed, vec![MIGRATIONS[MIGRATIONS.len() - 1].id]);
+        assert_eq!(
+            table_names(&db).await,
+            vec![
+                "authors",
+                "migrations",
+                "post_revisions",
+                "post_slug_redirects",
+                "posts",
+                "posts_tags",
+                "tags"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_down_to_target_and_back_up() {
+        let db = connect().await;
+        migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        let reverted = migrate_down(&db, MIGRATIONS, Some(MIGRATIONS[0].id))
+            .await
+            .unwrap();
+        let tables = table_names(&db).await;
+        let reapplied = migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        assert_eq!(
+            reverted,
+            ids(&MIGRATIONS[1..]).into_iter().rev().collect::<Vec<_>>()
+        );
+        assert_eq!(tables, vec!["migrations", "tags"]);
+        assert_eq!(reapplied, ids(&MIGRATIONS[1..]));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_rejects_unknown_targets() {
+        let db = connect().await;
+
+        let up = migrate_up(&db, MIGRATIONS, Some("nope")).await;
+        let down = migrate_down(&db, MIGRATIONS, Some("nope")).await;
+
+        assert!(matches!(up, Err(MigrationError::UnknownTarget(id)) if id == "nope"));
+        assert!(matches!(down, Err(MigrationError::UnknownTarget(id)) if id == "nope"));
+    }
+
+    #[tokio::test]
+    async fn test_migration_status() {
+        let db = connect().await;
+        migrate_up(&db, MIGRATIONS, Some(MIGRATIONS[2].id))
+            .await
+            .unwrap();
+
+        let statuses = migration_status(&db, &MIGRATIONS[1..]).await.unwrap();
+        let states = statuses
+            .iter()
+            .map(|status| match status.state {
+                MigrationState::Pending => "pending",
+                MigrationState::Applied { .. } => "applied",
+                MigrationState::Modified { .. } => "modified",
+                MigrationState::Unknown { .. } => "unknown",
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(states[..3], ["unknown", "applied", "applied"]);
+        assert!(states[3..].iter().all(|state| *state == "pending"));
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A schema change, applied once and recorded in the `migrations` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// Directory name under `migrations/`, migrations are applied in ascending id order.
+    pub id: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+impl Migration {
+    /// Hex encoded SHA-256 of the up script, used to detect edits to already applied migrations.
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.up))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    Pending,
+    Applied {
+        applied_at: DateTime<Utc>,
+    },
+    /// Applied, but its up script changed since.
+    Modified {
+        applied_at: DateTime<Utc>,
+    },
+    /// Applied by another version, with no matching migration in this one.
+    Unknown {
+        applied_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub id: String,
+    pub state: MigrationState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealAppliedMigration {
+    pub migration_id: String,
+    pub checksum: String,
+    pub applied_at: surrealdb::sql::Datetime,
+}
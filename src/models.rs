@@ -1,18 +1,178 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     pub id: String,
     pub name: String,
+    pub revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Author {
+    pub id: String,
+    /// Unique handle of the author, used to refer to them in URLs and posts.
+    pub name: String,
+    pub display_name: String,
+    pub revision: u64,
+}
+
+/// Permission granted to an API key, named `<resource>:<access>`, e.g. `posts:write`, or `admin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Scope {
+    PostsRead,
+    PostsWrite,
+    TagsRead,
+    TagsWrite,
+    AuthorsRead,
+    AuthorsWrite,
+    /// Lets clients that do not act as an author write posts of every author. Routes still need
+    /// their own scope.
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 7] = [
+        Scope::PostsRead,
+        Scope::PostsWrite,
+        Scope::TagsRead,
+        Scope::TagsWrite,
+        Scope::AuthorsRead,
+        Scope::AuthorsWrite,
+        Scope::Admin,
+    ];
+
+    /// Scopes of every read, and nothing else.
+    pub const READ: [Scope; 3] = [Scope::PostsRead, Scope::TagsRead, Scope::AuthorsRead];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::PostsRead => "posts:read",
+            Scope::PostsWrite => "posts:write",
+            Scope::TagsRead => "tags:read",
+            Scope::TagsWrite => "tags:write",
+            Scope::AuthorsRead => "authors:read",
+            Scope::AuthorsWrite => "authors:write",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == scope)
+            .ok_or_else(|| format!("Unknown scope: {scope}"))
+    }
+}
+
+impl TryFrom<String> for Scope {
+    type Error = String;
+
+    fn try_from(scope: String) -> Result<Self, Self::Error> {
+        scope.parse()
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.as_str().to_string()
+    }
+}
+
+/// Credential of an API client. Only a hash of the key itself is stored, so it cannot be shown
+/// again after creation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    /// Unique name of the key, used to revoke it.
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Name of the author requests made with the key act as, if any.
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a post stands in its publication lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    #[default]
+    Published,
+    /// Waiting for its `publish_at`, when the scheduler publishes it.
+    Scheduled,
+    Archived,
+}
+
+impl PostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::Published => "published",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Archived => "archived",
+        }
+    }
+}
+
+impl fmt::Display for PostStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PostStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "draft" => Ok(PostStatus::Draft),
+            "published" => Ok(PostStatus::Published),
+            "scheduled" => Ok(PostStatus::Scheduled),
+            "archived" => Ok(PostStatus::Archived),
+            _ => Err(format!("Unknown post status: {status}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Post {
     pub id: String,
+    /// Unique, URL friendly name of the post, see `GET /api/v1/posts/by-slug/{slug}`.
+    pub slug: String,
     pub title: String,
     pub content: String,
     pub tags: Vec<Tag>,
+    /// Name of the author of the post, if it has one.
+    pub author: Option<String>,
+    pub status: PostStatus,
+    /// When a `scheduled` post gets published.
+    pub publish_at: Option<DateTime<Utc>>,
+    /// When the post was first published.
+    pub published_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub revision: u64,
+}
+
+/// Post as it was at one of its revisions. Tags are kept by name, as they were named back then.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostRevision {
+    pub revision: u64,
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
 }
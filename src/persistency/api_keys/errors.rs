@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ApiKeyRepositoryError {
+    #[error("Database error: {0}")]
+    Database(#[from] surrealdb::Error),
+
+    #[error("Failed to create API key in the database")]
+    ApiKeyCreation,
+
+    #[error("API key already exists: {0}")]
+    ApiKeyAlreadyExists(String),
+
+    #[error("Failed to list API keys from the database")]
+    ApiKeyListing,
+
+    #[error("Failed to fetch API key from the database")]
+    ApiKeyGet,
+
+    #[error("API key not found: {0}")]
+    ApiKeyNotFound(String),
+
+    #[error("Failed to delete API key from the database")]
+    ApiKeyDeletion,
+}
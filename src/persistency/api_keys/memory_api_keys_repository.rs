@@ -0,0 +1,171 @@
+use super::{
+    errors::ApiKeyRepositoryError,
+    models::{MemoryApiKeyEntity, NewApiKey},
+};
+use crate::{
+    models::ApiKey,
+    persistency::{memory::MemoryDatabase, traits::ApiKeyRepository},
+};
+use chrono::Utc;
+
+#[derive(Clone)]
+pub struct MemoryApiKeysRepository {
+    db: MemoryDatabase,
+}
+
+impl MemoryApiKeysRepository {
+    pub fn new(db: MemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+impl ApiKeyRepository for MemoryApiKeysRepository {
+    async fn create(&self, new_api_key: NewApiKey) -> Result<ApiKey, ApiKeyRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        if tables
+            .api_keys
+            .values()
+            .any(|api_key| api_key.name == new_api_key.name)
+        {
+            return Err(ApiKeyRepositoryError::ApiKeyAlreadyExists(new_api_key.name));
+        }
+
+        let api_key = MemoryApiKeyEntity {
+            id: self.db.generate_id(),
+            name: new_api_key.name,
+            key_hash: new_api_key.key_hash,
+            scopes: new_api_key.scopes,
+            author: new_api_key.author,
+            created_at: Utc::now(),
+        };
+        tables.api_keys.insert(api_key.id.clone(), api_key.clone());
+
+        Ok(api_key.into())
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>, ApiKeyRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let mut api_keys = tables
+            .api_keys
+            .values()
+            .cloned()
+            .map(ApiKey::from)
+            .collect::<Vec<_>>();
+        api_keys.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(api_keys)
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        Ok(tables
+            .api_keys
+            .values()
+            .find(|api_key| api_key.key_hash == key_hash)
+            .cloned()
+            .map(ApiKey::from))
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), ApiKeyRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        let before = tables.api_keys.len();
+        tables.api_keys.retain(|_, api_key| api_key.name != name);
+        if tables.api_keys.len() == before {
+            return Err(ApiKeyRepositoryError::ApiKeyNotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::Scope,
+        persistency::{
+            authors::{memory_authors_repository::MemoryAuthorsRepository, models::NewAuthor},
+            models::Precondition,
+            traits::AuthorRepository,
+        },
+    };
+
+    fn new_api_key(name: &str, key_hash: &str) -> NewApiKey {
+        NewApiKey {
+            name: name.to_string(),
+            key_hash: key_hash.to_string(),
+            scopes: vec![Scope::TagsWrite],
+            author: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_find_and_delete() {
+        let api_keys = MemoryApiKeysRepository::new(MemoryDatabase::default());
+        api_keys.create(new_api_key("deploy", "abc")).await.unwrap();
+        api_keys.create(new_api_key("ci", "def")).await.unwrap();
+
+        let found = api_keys.find_by_hash("abc").await.unwrap().unwrap();
+        assert_eq!(found.name, "deploy");
+        assert_eq!(found.scopes, vec![Scope::TagsWrite]);
+        assert!(matches!(
+            api_keys.create(new_api_key("ci", "ghi")).await,
+            Err(ApiKeyRepositoryError::ApiKeyAlreadyExists(name)) if name == "ci"
+        ));
+
+        api_keys.delete("deploy").await.unwrap();
+        let names = api_keys
+            .find_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|api_key| api_key.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["ci"]);
+        assert!(api_keys.find_by_hash("abc").await.unwrap().is_none());
+        assert!(matches!(
+            api_keys.delete("deploy").await,
+            Err(ApiKeyRepositoryError::ApiKeyNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_keys_follow_renamed_authors() {
+        let db = MemoryDatabase::default();
+        let api_keys = MemoryApiKeysRepository::new(db.clone());
+        let authors = MemoryAuthorsRepository::new(db);
+        authors
+            .create(NewAuthor {
+                name: "ana".to_string(),
+                display_name: "Ana".to_string(),
+            })
+            .await
+            .unwrap();
+        api_keys
+            .create(NewApiKey {
+                author: Some("ana".to_string()),
+                ..new_api_key("ci", "abc")
+            })
+            .await
+            .unwrap();
+
+        authors
+            .update(
+                "ana",
+                NewAuthor {
+                    name: "ana.s".to_string(),
+                    display_name: "Ana".to_string(),
+                },
+                Precondition::ANY,
+            )
+            .await
+            .unwrap();
+
+        let found = api_keys.find_by_hash("abc").await.unwrap().unwrap();
+        assert_eq!(found.author.as_deref(), Some("ana.s"));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod errors;
+pub mod memory_api_keys_repository;
+pub mod models;
+pub mod surrealdb_api_keys_repository;
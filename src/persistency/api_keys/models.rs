@@ -0,0 +1,55 @@
+use crate::models::{ApiKey, Scope};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// API key to store, along with the hash of its secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewApiKey {
+    pub name: String,
+    pub key_hash: String,
+    pub scopes: Vec<Scope>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealApiKeyEntityOutput {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub author: Option<String>,
+    pub created_at: surrealdb::sql::Datetime,
+}
+
+impl From<SurrealApiKeyEntityOutput> for ApiKey {
+    fn from(api_key: SurrealApiKeyEntityOutput) -> Self {
+        Self {
+            id: api_key.id,
+            name: api_key.name,
+            scopes: api_key.scopes,
+            author: api_key.author,
+            created_at: api_key.created_at.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryApiKeyEntity {
+    pub id: String,
+    pub name: String,
+    pub key_hash: String,
+    pub scopes: Vec<Scope>,
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MemoryApiKeyEntity> for ApiKey {
+    fn from(api_key: MemoryApiKeyEntity) -> Self {
+        Self {
+            id: api_key.id,
+            name: api_key.name,
+            scopes: api_key.scopes,
+            author: api_key.author,
+            created_at: api_key.created_at,
+        }
+    }
+}
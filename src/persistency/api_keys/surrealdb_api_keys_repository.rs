@@ -0,0 +1,216 @@
+use super::{
+    errors::ApiKeyRepositoryError,
+    models::{NewApiKey, SurrealApiKeyEntityOutput},
+};
+use crate::{
+    models::ApiKey,
+    persistency::{models::SurrealRecord, traits::ApiKeyRepository},
+};
+use surrealdb::{error::Db, Surreal};
+
+#[derive(Clone)]
+pub struct SurrealdbApiKeysRepository {
+    db: Surreal<surrealdb::engine::any::Any>,
+}
+
+impl SurrealdbApiKeysRepository {
+    pub fn new(db: Surreal<surrealdb::engine::any::Any>) -> Self {
+        Self { db }
+    }
+}
+
+fn is_unique_name_violation(error: &surrealdb::Error) -> bool {
+    matches!(error, surrealdb::Error::Db(Db::IndexExists { index, .. }) if index == "unique_api_key_name")
+}
+
+impl ApiKeyRepository for SurrealdbApiKeysRepository {
+    async fn create(&self, new_api_key: NewApiKey) -> Result<ApiKey, ApiKeyRepositoryError> {
+        let name = new_api_key.name.clone();
+        let api_key = self
+            .db
+            .query(include_str!("./queries/create_api_key.surql"))
+            .bind(("api_key", new_api_key))
+            .await
+            .map_err(ApiKeyRepositoryError::Database)?
+            .take::<Vec<SurrealApiKeyEntityOutput>>(0)
+            .map_err(|e| {
+                if is_unique_name_violation(&e) {
+                    ApiKeyRepositoryError::ApiKeyAlreadyExists(name)
+                } else {
+                    ApiKeyRepositoryError::ApiKeyCreation
+                }
+            })?
+            .first()
+            .cloned()
+            .ok_or(ApiKeyRepositoryError::ApiKeyCreation)?;
+
+        Ok(api_key.into())
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>, ApiKeyRepositoryError> {
+        let api_keys = self
+            .db
+            .query(include_str!("./queries/list_api_keys.surql"))
+            .await
+            .map_err(ApiKeyRepositoryError::Database)?
+            .take::<Vec<SurrealApiKeyEntityOutput>>(0)
+            .map_err(|_| ApiKeyRepositoryError::ApiKeyListing)?;
+
+        Ok(api_keys.into_iter().map(ApiKey::from).collect())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+        let api_key = self
+            .db
+            .query(include_str!("./queries/get_api_key_by_hash.surql"))
+            .bind(("key_hash", key_hash))
+            .await
+            .map_err(ApiKeyRepositoryError::Database)?
+            .take::<Vec<SurrealApiKeyEntityOutput>>(0)
+            .map_err(|_| ApiKeyRepositoryError::ApiKeyGet)?
+            .into_iter()
+            .next();
+
+        Ok(api_key.map(ApiKey::from))
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), ApiKeyRepositoryError> {
+        let deleted = self
+            .db
+            .query(include_str!("./queries/delete_api_key.surql"))
+            .bind(("api_key_name", name))
+            .await
+            .map_err(ApiKeyRepositoryError::Database)?
+            .take::<Vec<SurrealRecord>>(0)
+            .map_err(|_| ApiKeyRepositoryError::ApiKeyDeletion)?;
+
+        if deleted.is_empty() {
+            return Err(ApiKeyRepositoryError::ApiKeyNotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        migrations::{migrate_up, MIGRATIONS},
+        models::Scope,
+        persistency::{
+            authors::{
+                models::NewAuthor, surrealdb_authors_repository::SurrealdbAuthorsRepository,
+            },
+            models::Precondition,
+            traits::AuthorRepository,
+        },
+    };
+
+    async fn repositories() -> (SurrealdbApiKeysRepository, SurrealdbAuthorsRepository) {
+        let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+        db.use_ns("iemanjad").use_db("posts").await.unwrap();
+        migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        (
+            SurrealdbApiKeysRepository::new(db.clone()),
+            SurrealdbAuthorsRepository::new(db),
+        )
+    }
+
+    async fn repository() -> SurrealdbApiKeysRepository {
+        repositories().await.0
+    }
+
+    fn new_api_key(name: &str, key_hash: &str) -> NewApiKey {
+        NewApiKey {
+            name: name.to_string(),
+            key_hash: key_hash.to_string(),
+            scopes: vec![Scope::PostsRead, Scope::PostsWrite],
+            author: Some("ana".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_by_hash() {
+        let api_keys = repository().await;
+
+        let created = api_keys.create(new_api_key("ci", "abc")).await.unwrap();
+        let found = api_keys.find_by_hash("abc").await.unwrap().unwrap();
+
+        assert_eq!(found.id, created.id);
+        assert_eq!(found.name, "ci");
+        assert_eq!(found.scopes, vec![Scope::PostsRead, Scope::PostsWrite]);
+        assert_eq!(found.author.as_deref(), Some("ana"));
+        assert!(api_keys.find_by_hash("def").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_names_are_unique() {
+        let api_keys = repository().await;
+        api_keys.create(new_api_key("ci", "abc")).await.unwrap();
+
+        assert!(matches!(
+            api_keys.create(new_api_key("ci", "def")).await,
+            Err(ApiKeyRepositoryError::ApiKeyAlreadyExists(name)) if name == "ci"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete() {
+        let api_keys = repository().await;
+        api_keys.create(new_api_key("deploy", "abc")).await.unwrap();
+        api_keys.create(new_api_key("ci", "def")).await.unwrap();
+
+        let names = |keys: Vec<ApiKey>| keys.into_iter().map(|key| key.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(api_keys.find_all().await.unwrap()),
+            vec!["ci", "deploy"]
+        );
+
+        api_keys.delete("ci").await.unwrap();
+        assert_eq!(names(api_keys.find_all().await.unwrap()), vec!["deploy"]);
+        assert!(api_keys.find_by_hash("def").await.unwrap().is_none());
+        assert!(matches!(
+            api_keys.delete("ci").await,
+            Err(ApiKeyRepositoryError::ApiKeyNotFound(name)) if name == "ci"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_keys_follow_renamed_authors() {
+        let (api_keys, authors) = repositories().await;
+        for name in ["ana", "bruno"] {
+            authors
+                .create(NewAuthor {
+                    name: name.to_string(),
+                    display_name: name.to_uppercase(),
+                })
+                .await
+                .unwrap();
+        }
+        api_keys.create(new_api_key("ci", "abc")).await.unwrap();
+
+        let rename = NewAuthor {
+            name: "bruno".to_string(),
+            display_name: "ANA".to_string(),
+        };
+        assert!(authors
+            .update("ana", rename, Precondition::ANY)
+            .await
+            .is_err());
+        let found = api_keys.find_by_hash("abc").await.unwrap().unwrap();
+        assert_eq!(found.author.as_deref(), Some("ana"));
+        let rename = NewAuthor {
+            name: "ana.s".to_string(),
+            display_name: "ANA".to_string(),
+        };
+        authors
+            .update("ana", rename, Precondition::ANY)
+            .await
+            .unwrap();
+
+        let found = api_keys.find_by_hash("abc").await.unwrap().unwrap();
+        assert_eq!(found.author.as_deref(), Some("ana.s"));
+    }
+}
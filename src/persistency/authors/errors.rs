@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AuthorRepositoryError {
+    #[error("Database error: {0}")]
+    Database(#[from] surrealdb::Error),
+
+    #[error("Failed to create author in the database")]
+    AuthorCreation,
+
+    #[error("Author already exists: {0}")]
+    AuthorAlreadyExists(String),
+
+    #[error("Failed to list authors from the database")]
+    AuthorListing,
+
+    #[error("Unsupported sort order: {0}")]
+    InvalidSort(String),
+
+    #[error("Invalid or expired cursor: {0}")]
+    InvalidCursor(String),
+
+    #[error("Failed to count authors in the database")]
+    AuthorCount,
+
+    #[error("Failed to find authors by names in the database")]
+    AuthorFind,
+
+    #[error("Failed to fetch author from the database")]
+    AuthorGet,
+
+    #[error("Author not found: {0}")]
+    AuthorNotFound(String),
+
+    #[error("Author {0} has {1} posts")]
+    AuthorInUse(String, usize),
+
+    #[error("Author {0} was modified concurrently, it is now at revision {1}")]
+    RevisionMismatch(String, u64),
+
+    #[error("Failed to update author in the database")]
+    AuthorUpdate,
+
+    #[error("Failed to delete author from the database")]
+    AuthorDeletion,
+}
@@ -0,0 +1,224 @@
+use super::{
+    errors::AuthorRepositoryError,
+    models::{AuthorPatch, AuthorSort, FindAuthorsResponse, MemoryAuthorEntity, NewAuthor},
+};
+use crate::{
+    models::Author,
+    persistency::{
+        memory::MemoryDatabase,
+        models::{next_page_cursor, FindAllOptions, Precondition, SortKey},
+        traits::AuthorRepository,
+    },
+};
+
+#[derive(Clone)]
+pub struct MemoryAuthorsRepository {
+    db: MemoryDatabase,
+}
+
+impl MemoryAuthorsRepository {
+    pub fn new(db: MemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+impl AuthorRepository for MemoryAuthorsRepository {
+    async fn create(&self, new_author: NewAuthor) -> Result<Author, AuthorRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        if tables
+            .authors
+            .values()
+            .any(|author| author.name == new_author.name)
+        {
+            return Err(AuthorRepositoryError::AuthorAlreadyExists(new_author.name));
+        }
+
+        let author = MemoryAuthorEntity {
+            id: self.db.generate_id(),
+            name: new_author.name,
+            display_name: new_author.display_name,
+            revision: 1,
+        };
+        tables.authors.insert(author.id.clone(), author.clone());
+
+        Ok(author.into())
+    }
+
+    async fn find_all(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindAuthorsResponse, AuthorRepositoryError> {
+        let sort = AuthorSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+        let tables = self.db.tables().read().await;
+
+        let key = |author: &Author| SortKey::Text(author.name.clone());
+        let mut authors = tables
+            .authors
+            .values()
+            .cloned()
+            .map(Author::from)
+            .collect::<Vec<_>>();
+        authors.sort_by(|a, b| sort.direction.compare((&key(a), &a.id), (&key(b), &b.id)));
+
+        let total = authors.len();
+        let offset = if cursor.is_some() { 0 } else { options.offset };
+        let mut authors = authors
+            .into_iter()
+            .filter(|author| match &cursor {
+                Some((cursor_key, id)) => sort
+                    .direction
+                    .compare((&key(author), &author.id), (cursor_key, id))
+                    .is_gt(),
+                None => true,
+            })
+            .skip(offset)
+            .take(options.limit.saturating_add(1))
+            .collect();
+        let next_cursor =
+            next_page_cursor(&mut authors, options.limit, |author| sort.cursor(author));
+
+        Ok(FindAuthorsResponse {
+            authors,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Author>, AuthorRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let authors = tables
+            .authors
+            .values()
+            .filter(|author| names.contains(&author.name.as_str()))
+            .cloned()
+            .map(|author| author.into())
+            .collect();
+
+        Ok(authors)
+    }
+
+    async fn get(&self, name: &str) -> Result<Author, AuthorRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let author = tables
+            .authors
+            .values()
+            .find(|author| author.name == name)
+            .cloned()
+            .ok_or_else(|| AuthorRepositoryError::AuthorNotFound(name.to_string()))?;
+
+        Ok(author.into())
+    }
+
+    async fn update(
+        &self,
+        name: &str,
+        new_author: NewAuthor,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        if tables
+            .authors
+            .values()
+            .any(|author| author.name == new_author.name && author.name != name)
+        {
+            return Err(AuthorRepositoryError::AuthorAlreadyExists(new_author.name));
+        }
+
+        let author = tables
+            .authors
+            .values_mut()
+            .find(|author| author.name == name)
+            .ok_or_else(|| AuthorRepositoryError::AuthorNotFound(name.to_string()))?;
+        if !precondition.allows(author.revision) {
+            return Err(AuthorRepositoryError::RevisionMismatch(
+                name.to_string(),
+                author.revision,
+            ));
+        }
+        let renamed = author.name != new_author.name;
+        author.name = new_author.name;
+        author.display_name = new_author.display_name;
+        author.revision += 1;
+        let author = author.clone();
+
+        if renamed {
+            tables.touch_posts(|post| post.author_id.as_ref() == Some(&author.id));
+            // API keys act as authors by name, so they follow the rename.
+            for api_key in tables.api_keys.values_mut() {
+                if api_key.author.as_deref() == Some(name) {
+                    api_key.author = Some(author.name.clone());
+                }
+            }
+        }
+
+        Ok(author.into())
+    }
+
+    async fn patch(
+        &self,
+        name: &str,
+        patch: AuthorPatch,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError> {
+        let author = self.get(name).await?;
+        if !precondition.allows(author.revision) {
+            return Err(AuthorRepositoryError::RevisionMismatch(
+                name.to_string(),
+                author.revision,
+            ));
+        }
+        if patch.name.is_none() && patch.display_name.is_none() {
+            return Ok(author);
+        }
+
+        // Pinning the revision read keeps the members left out from overwriting a concurrent write.
+        self.update(
+            name,
+            patch.apply(&author),
+            Precondition::at(vec![author.revision]),
+        )
+        .await
+    }
+
+    async fn delete(
+        &self,
+        name: &str,
+        precondition: Precondition,
+    ) -> Result<(), AuthorRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        let author = tables
+            .authors
+            .values()
+            .find(|author| author.name == name)
+            .ok_or_else(|| AuthorRepositoryError::AuthorNotFound(name.to_string()))?;
+        if !precondition.allows(author.revision) {
+            return Err(AuthorRepositoryError::RevisionMismatch(
+                name.to_string(),
+                author.revision,
+            ));
+        }
+        let id = author.id.clone();
+        // Posts in the trash keep their author, so they still count.
+        let posts = tables
+            .posts
+            .values()
+            .filter(|post| post.author_id.as_ref() == Some(&id))
+            .count();
+        if posts > 0 {
+            return Err(AuthorRepositoryError::AuthorInUse(name.to_string(), posts));
+        }
+        tables.authors.remove(&id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod errors;
+pub mod memory_authors_repository;
+pub mod models;
+pub mod surrealdb_authors_repository;
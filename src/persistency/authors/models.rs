@@ -0,0 +1,161 @@
+use super::errors::AuthorRepositoryError;
+use crate::{
+    models::Author,
+    persistency::models::{parse_sort, Cursor, SortDirection, SortKey},
+    utils::patch::required_member,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAuthor {
+    pub name: String,
+    pub display_name: String,
+}
+
+/// JSON Merge Patch (RFC 7396) of an author. None of its members can be removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthorPatch {
+    #[serde(default, deserialize_with = "required_member")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "required_member")]
+    pub display_name: Option<String>,
+}
+
+impl AuthorPatch {
+    /// `author` with the patch applied.
+    pub fn apply(&self, author: &Author) -> NewAuthor {
+        NewAuthor {
+            name: self.name.clone().unwrap_or_else(|| author.name.clone()),
+            display_name: self
+                .display_name
+                .clone()
+                .unwrap_or_else(|| author.display_name.clone()),
+        }
+    }
+}
+
+/// Validated author listing order, parsed from `FindAllOptions::sort`. Authors are only ordered by
+/// `name`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthorSort {
+    pub direction: SortDirection,
+}
+
+impl Default for AuthorSort {
+    fn default() -> Self {
+        Self {
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+impl TryFrom<Option<&str>> for AuthorSort {
+    type Error = AuthorRepositoryError;
+
+    fn try_from(sort: Option<&str>) -> Result<Self, Self::Error> {
+        let Some(sort) = sort else {
+            return Ok(Self::default());
+        };
+
+        match parse_sort(sort) {
+            ("name", direction) => Ok(Self { direction }),
+            _ => Err(AuthorRepositoryError::InvalidSort(sort.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AuthorSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}name", self.direction.sort_prefix())
+    }
+}
+
+impl AuthorSort {
+    pub fn cursor(&self, author: &Author) -> Cursor {
+        Cursor {
+            sort: self.to_string(),
+            key: author.name.clone(),
+            id: author.id.clone(),
+        }
+    }
+
+    /// Decodes a client supplied cursor into the name and id it points at, rejecting cursors
+    /// issued for another order.
+    pub fn decode_cursor(&self, cursor: &str) -> Result<(SortKey, String), AuthorRepositoryError> {
+        Cursor::decode(cursor)
+            .filter(|decoded| decoded.sort == self.to_string())
+            .map(|decoded| (SortKey::Text(decoded.key), decoded.id))
+            .ok_or_else(|| AuthorRepositoryError::InvalidCursor(cursor.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealAuthorEntityInput {
+    pub name: String,
+    pub display_name: String,
+}
+
+impl From<NewAuthor> for SurrealAuthorEntityInput {
+    fn from(author: NewAuthor) -> Self {
+        Self {
+            name: author.name,
+            display_name: author.display_name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealAuthorEntityOutput {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub revision: u64,
+}
+
+impl From<SurrealAuthorEntityOutput> for Author {
+    fn from(author: SurrealAuthorEntityOutput) -> Self {
+        Self {
+            id: author.id,
+            name: author.name,
+            display_name: author.display_name,
+            revision: author.revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealAuthorEntityWithCountOutput {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub revision: u64,
+    pub post_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryAuthorEntity {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub revision: u64,
+}
+
+impl From<MemoryAuthorEntity> for Author {
+    fn from(author: MemoryAuthorEntity) -> Self {
+        Self {
+            id: author.id,
+            name: author.name,
+            display_name: author.display_name,
+            revision: author.revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindAuthorsResponse {
+    pub authors: Vec<Author>,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+}
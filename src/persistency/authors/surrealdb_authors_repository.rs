@@ -0,0 +1,372 @@
+use super::{
+    errors::AuthorRepositoryError,
+    models::{
+        AuthorPatch, AuthorSort, FindAuthorsResponse, NewAuthor, SurrealAuthorEntityInput,
+        SurrealAuthorEntityOutput, SurrealAuthorEntityWithCountOutput,
+    },
+};
+use crate::{
+    models::Author,
+    persistency::{
+        models::{
+            next_page_cursor, FindAllOptions, Precondition, SortKey, SurrealCountRecord,
+            SurrealRecord,
+        },
+        traits::AuthorRepository,
+    },
+};
+use surrealdb::{error::Db, Response, Surreal};
+
+#[derive(Clone)]
+pub struct SurrealdbAuthorsRepository {
+    db: Surreal<surrealdb::engine::any::Any>,
+}
+
+impl SurrealdbAuthorsRepository {
+    pub fn new(db: Surreal<surrealdb::engine::any::Any>) -> Self {
+        Self { db }
+    }
+}
+
+fn is_unique_name_violation(error: &surrealdb::Error) -> bool {
+    matches!(error, surrealdb::Error::Db(Db::IndexExists { index, .. }) if index == "unique_author_name")
+}
+
+/// Whether a statement of `response` failed because its author took a name in use. Transactions
+/// only report the other statements as not executed, so every error is looked at.
+fn has_unique_name_violation(response: &mut Response) -> bool {
+    response
+        .take_errors()
+        .values()
+        .any(is_unique_name_violation)
+}
+
+/// Condition selecting the authors listed after `(cursor_key, cursor_id)` in `sort` order.
+fn keyset_condition(sort: AuthorSort) -> String {
+    let operator = sort.direction.keyset_operator();
+
+    format!(
+        "(name {operator} $cursor_key OR (name = $cursor_key AND string::split(<string>id, ':')[1] > $cursor_id))"
+    )
+}
+
+impl SurrealdbAuthorsRepository {
+    async fn register_author_in_db(
+        &self,
+        author_entity: SurrealAuthorEntityInput,
+    ) -> Result<SurrealAuthorEntityOutput, AuthorRepositoryError> {
+        let name = author_entity.name.clone();
+        let author = self
+            .db
+            .query(include_str!("./queries/create_author.surql"))
+            .bind(("author", author_entity))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealAuthorEntityOutput>>(0)
+            .map_err(|e| {
+                if is_unique_name_violation(&e) {
+                    AuthorRepositoryError::AuthorAlreadyExists(name)
+                } else {
+                    AuthorRepositoryError::AuthorCreation
+                }
+            })?
+            .first()
+            .cloned()
+            .ok_or(AuthorRepositoryError::AuthorCreation)?;
+
+        Ok(author)
+    }
+
+    async fn list_authors_in_db(
+        &self,
+        limit: usize,
+        offset: usize,
+        sort: AuthorSort,
+        cursor: Option<(SortKey, String)>,
+    ) -> Result<Vec<SurrealAuthorEntityOutput>, AuthorRepositoryError> {
+        let order = format!("name {}", sort.direction.as_surql());
+        let (keyset, offset, cursor_key, cursor_id) = match cursor {
+            Some((key, id)) => (keyset_condition(sort), 0, key.to_cursor_key(), id),
+            None => ("true".to_string(), offset, String::new(), String::new()),
+        };
+        let authors = self
+            .db
+            .query(
+                include_str!("./queries/list_authors.surql")
+                    .replace("{order}", &order)
+                    .replace("{keyset}", &keyset),
+            )
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .bind(("cursor_key", cursor_key))
+            .bind(("cursor_id", cursor_id))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealAuthorEntityOutput>>(0)
+            .map_err(|_| AuthorRepositoryError::AuthorListing)?;
+
+        Ok(authors)
+    }
+
+    async fn count_authors_in_db(&self) -> Result<usize, AuthorRepositoryError> {
+        let total = self
+            .db
+            .query(include_str!("./queries/count_authors.surql"))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealCountRecord>>(0)
+            .map_err(|_| AuthorRepositoryError::AuthorCount)?
+            .first()
+            .unwrap_or_default()
+            .count;
+
+        Ok(total)
+    }
+
+    async fn find_authors_by_names_in_db(
+        &self,
+        names: Vec<&str>,
+    ) -> Result<Vec<SurrealAuthorEntityOutput>, AuthorRepositoryError> {
+        let authors = self
+            .db
+            .query(include_str!("./queries/find_authors_by_names.surql"))
+            .bind(("names", names))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealAuthorEntityOutput>>(0)
+            .map_err(|_| AuthorRepositoryError::AuthorFind)?;
+
+        Ok(authors)
+    }
+
+    async fn get_author_in_db(
+        &self,
+        name: &str,
+    ) -> Result<SurrealAuthorEntityOutput, AuthorRepositoryError> {
+        let author = self
+            .db
+            .query(include_str!("./queries/get_author.surql"))
+            .bind(("author_name", name))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealAuthorEntityOutput>>(0)
+            .map_err(|_| AuthorRepositoryError::AuthorGet)?
+            .first()
+            .cloned()
+            .ok_or_else(|| AuthorRepositoryError::AuthorNotFound(name.to_string()))?;
+
+        Ok(author)
+    }
+
+    /// Error for a conditional write that matched no author: either it does not exist or it is no
+    /// longer at one of the expected revisions.
+    async fn unmatched_write_error(&self, name: &str) -> AuthorRepositoryError {
+        match self.get_author_in_db(name).await {
+            Ok(author) => {
+                AuthorRepositoryError::RevisionMismatch(name.to_string(), author.revision)
+            }
+            Err(error) => error,
+        }
+    }
+
+    async fn update_author_in_db(
+        &self,
+        name: &str,
+        author_entity: SurrealAuthorEntityInput,
+        precondition: &Precondition,
+    ) -> Result<SurrealAuthorEntityOutput, AuthorRepositoryError> {
+        let new_name = author_entity.name.clone();
+        let mut response = self
+            .db
+            .query(include_str!("./queries/update_author.surql"))
+            .bind(("author_old_name", name))
+            .bind(("author_new_name", author_entity.name))
+            .bind(("display_name", author_entity.display_name))
+            .bind(("revisions", precondition.revisions()))
+            .await
+            .map_err(AuthorRepositoryError::Database)?;
+        let author = response
+            .take::<Vec<SurrealAuthorEntityOutput>>(response.num_statements() - 1)
+            .map_err(|_| {
+                if has_unique_name_violation(&mut response) {
+                    AuthorRepositoryError::AuthorAlreadyExists(new_name)
+                } else {
+                    AuthorRepositoryError::AuthorUpdate
+                }
+            })?
+            .first()
+            .cloned();
+
+        match author {
+            Some(author) => Ok(author),
+            None => Err(self.unmatched_write_error(name).await),
+        }
+    }
+
+    /// Error for a delete that matched no author: besides the reasons of `unmatched_write_error`,
+    /// the author may still have posts.
+    async fn unmatched_delete_error(
+        &self,
+        name: &str,
+        precondition: &Precondition,
+    ) -> AuthorRepositoryError {
+        let author = self
+            .db
+            .query(include_str!("./queries/get_author_usage.surql"))
+            .bind(("author_name", name))
+            .await
+            .map_err(AuthorRepositoryError::Database)
+            .and_then(|mut response| {
+                response
+                    .take::<Vec<SurrealAuthorEntityWithCountOutput>>(0)
+                    .map_err(|_| AuthorRepositoryError::AuthorGet)
+            })
+            .map(|authors| authors.first().cloned());
+
+        match author {
+            Ok(Some(author)) if !precondition.allows(author.revision) => {
+                AuthorRepositoryError::RevisionMismatch(name.to_string(), author.revision)
+            }
+            Ok(Some(author)) if author.post_count > 0 => {
+                AuthorRepositoryError::AuthorInUse(name.to_string(), author.post_count)
+            }
+            Ok(_) => AuthorRepositoryError::AuthorNotFound(name.to_string()),
+            Err(error) => error,
+        }
+    }
+
+    async fn delete_author_in_db(
+        &self,
+        name: &str,
+        precondition: &Precondition,
+    ) -> Result<(), AuthorRepositoryError> {
+        let deleted = self
+            .db
+            .query(include_str!("./queries/delete_author.surql"))
+            .bind(("author_name", name))
+            .bind(("revisions", precondition.revisions()))
+            .await
+            .map_err(AuthorRepositoryError::Database)?
+            .take::<Vec<SurrealRecord>>(0)
+            .map_err(|_| AuthorRepositoryError::AuthorDeletion)?;
+
+        if deleted.is_empty() {
+            return Err(self.unmatched_delete_error(name, precondition).await);
+        }
+
+        Ok(())
+    }
+}
+
+impl AuthorRepository for SurrealdbAuthorsRepository {
+    async fn create(&self, new_author: NewAuthor) -> Result<Author, AuthorRepositoryError> {
+        let author_entity = SurrealAuthorEntityInput::from(new_author);
+
+        let created_author = self.register_author_in_db(author_entity).await?.into();
+
+        Ok(created_author)
+    }
+
+    async fn find_all(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindAuthorsResponse, AuthorRepositoryError> {
+        let sort = AuthorSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+
+        let mut authors = self
+            .list_authors_in_db(
+                options.limit.saturating_add(1),
+                options.offset,
+                sort,
+                cursor,
+            )
+            .await?
+            .into_iter()
+            .map(Author::from)
+            .collect();
+        let next_cursor =
+            next_page_cursor(&mut authors, options.limit, |author| sort.cursor(author));
+
+        let total = self.count_authors_in_db().await?;
+
+        Ok(FindAuthorsResponse {
+            authors,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Author>, AuthorRepositoryError> {
+        let authors = self
+            .find_authors_by_names_in_db(names)
+            .await?
+            .into_iter()
+            .map(|author| author.into())
+            .collect();
+
+        Ok(authors)
+    }
+
+    async fn get(&self, name: &str) -> Result<Author, AuthorRepositoryError> {
+        let author = self.get_author_in_db(name).await?.into();
+
+        Ok(author)
+    }
+
+    async fn update(
+        &self,
+        name: &str,
+        new_author: NewAuthor,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError> {
+        let author_entity = SurrealAuthorEntityInput::from(new_author);
+
+        let updated_author = self
+            .update_author_in_db(name, author_entity, &precondition)
+            .await?
+            .into();
+
+        Ok(updated_author)
+    }
+
+    async fn patch(
+        &self,
+        name: &str,
+        patch: AuthorPatch,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError> {
+        let author = self.get(name).await?;
+        if !precondition.allows(author.revision) {
+            return Err(AuthorRepositoryError::RevisionMismatch(
+                name.to_string(),
+                author.revision,
+            ));
+        }
+        if patch.name.is_none() && patch.display_name.is_none() {
+            return Ok(author);
+        }
+
+        // Pinning the revision read keeps the members left out from overwriting a concurrent write.
+        self.update(
+            name,
+            patch.apply(&author),
+            Precondition::at(vec![author.revision]),
+        )
+        .await
+    }
+
+    async fn delete(
+        &self,
+        name: &str,
+        precondition: Precondition,
+    ) -> Result<(), AuthorRepositoryError> {
+        self.delete_author_in_db(name, &precondition).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,2683 @@
+//! Behaviour every `PostRepository` + `TagRepository` + `AuthorRepository` backend must share.
+//! Each scenario is a generic function, and `conformance_suite!` instantiates it once per backend.
+
+use super::{
+    authors::{
+        errors::AuthorRepositoryError,
+        memory_authors_repository::MemoryAuthorsRepository,
+        models::{AuthorPatch, FindAuthorsResponse, NewAuthor},
+        surrealdb_authors_repository::SurrealdbAuthorsRepository,
+    },
+    memory::MemoryDatabase,
+    models::{FindAllOptions, Precondition, Violation},
+    posts::{
+        errors::PostRepositoryError,
+        memory_posts_repository::MemoryPostsRepository,
+        models::{
+            NewPost, PostPatch, PostRevisionDiff, PostsFilter, SavePostOptions,
+            SearchPostsResponse, SlugLookup, TagChanges,
+        },
+        surrealdb_posts_repository::SurrealdbPostsRepository,
+    },
+    tags::{
+        errors::TagRepositoryError,
+        memory_tags_repository::MemoryTagsRepository,
+        models::{DeleteTagOptions, NewTag, TagPatch},
+        surrealdb_tags_repository::SurrealdbTagsRepository,
+    },
+    traits::{AuthorRepository, PostRepository, TagRepository},
+};
+use crate::{
+    migrations::{migrate_up, MIGRATIONS},
+    models::{Post, PostStatus, Tag},
+};
+use chrono::{Duration, Utc};
+
+fn memory_repositories() -> (
+    MemoryPostsRepository<MemoryTagsRepository, MemoryAuthorsRepository>,
+    MemoryTagsRepository,
+    MemoryAuthorsRepository,
+) {
+    let db = MemoryDatabase::default();
+
+    (
+        MemoryPostsRepository::new(
+            db.clone(),
+            MemoryTagsRepository::new(db.clone()),
+            MemoryAuthorsRepository::new(db.clone()),
+        ),
+        MemoryTagsRepository::new(db.clone()),
+        MemoryAuthorsRepository::new(db),
+    )
+}
+
+async fn surrealdb_repositories() -> (
+    SurrealdbPostsRepository<SurrealdbTagsRepository, SurrealdbAuthorsRepository>,
+    SurrealdbTagsRepository,
+    SurrealdbAuthorsRepository,
+) {
+    let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+    db.use_ns("iemanjad").use_db("posts").await.unwrap();
+    migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+    (
+        SurrealdbPostsRepository::new(
+            db.clone(),
+            SurrealdbTagsRepository::new(db.clone()),
+            SurrealdbAuthorsRepository::new(db.clone()),
+        ),
+        SurrealdbTagsRepository::new(db.clone()),
+        SurrealdbAuthorsRepository::new(db),
+    )
+}
+
+macro_rules! conformance_suite {
+    ($($scenario:ident),* $(,)?) => {
+        mod memory_backend {
+            $(
+                #[tokio::test]
+                async fn $scenario() {
+                    let (posts, tags, authors) = super::memory_repositories();
+                    super::$scenario(&posts, &tags, &authors).await;
+                }
+            )*
+        }
+
+        mod surrealdb_backend {
+            $(
+                #[tokio::test]
+                async fn $scenario() {
+                    let (posts, tags, authors) = super::surrealdb_repositories().await;
+                    super::$scenario(&posts, &tags, &authors).await;
+                }
+            )*
+        }
+    };
+}
+
+conformance_suite!(
+    tag_create_and_get,
+    tag_names_are_unique,
+    tag_names_are_validated,
+    tag_update_and_delete,
+    tag_patch,
+    tag_revisions,
+    tag_missing_names_are_not_found,
+    tag_delete_in_use,
+    tag_force_delete_detaches_posts,
+    tag_find_in_names,
+    tag_pagination,
+    tag_sorting,
+    tag_cursor_pagination,
+    post_create_and_get,
+    post_create_rejects_unknown_tags,
+    post_create_missing_tags,
+    post_create_missing_tags_concurrently,
+    post_create_rejects_invalid_missing_tags,
+    post_update_rejects_invalid_posts,
+    post_update_syncs_relations,
+    post_update_missing_tags,
+    post_patch,
+    post_patch_rejects_invalid_posts,
+    post_add_and_remove_tags,
+    post_timestamps,
+    post_revisions,
+    post_revision_history,
+    post_revision_restore,
+    post_delete,
+    post_trash,
+    post_trash_preconditions,
+    post_purge_deleted_before,
+    post_status,
+    post_publish_scheduled,
+    post_rescheduling,
+    post_slugs,
+    post_slug_redirects,
+    post_missing_ids_are_not_found,
+    post_pagination,
+    post_sorting,
+    post_cursor_pagination,
+    post_invalid_cursor,
+    post_filter_by_tags,
+    post_search,
+    post_search_only_published,
+    post_reflects_renamed_tags,
+    author_create_and_get,
+    author_names_are_unique,
+    author_update_and_patch,
+    author_revisions,
+    author_delete_in_use,
+    author_sorting,
+    post_author,
+    post_rejects_unknown_authors,
+    post_writes_check_owner,
+    post_filter_by_author,
+    post_reflects_renamed_authors,
+);
+
+fn new_tag(name: &str) -> NewTag {
+    NewTag {
+        name: name.to_string(),
+    }
+}
+
+fn new_author(name: &str) -> NewAuthor {
+    NewAuthor {
+        name: name.to_string(),
+        display_name: name.to_uppercase(),
+    }
+}
+
+fn new_post(title: &str, tags: &[&str]) -> NewPost {
+    NewPost {
+        slug: None,
+        title: title.to_string(),
+        content: format!("{title} content"),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        author: None,
+        status: None,
+        publish_at: None,
+    }
+}
+
+fn tag_names(tags: &[Tag]) -> Vec<String> {
+    let mut names = tags.iter().map(|tag| tag.name.clone()).collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+fn post_titles(posts: &[Post]) -> Vec<&str> {
+    posts.iter().map(|post| post.title.as_str()).collect()
+}
+
+fn violation_codes(violations: &[Violation]) -> Vec<&str> {
+    violations.iter().map(|violation| violation.code).collect()
+}
+
+async fn create_tags(tags: &impl TagRepository, names: &[&str]) {
+    for name in names {
+        tags.create(new_tag(name)).await.unwrap();
+    }
+}
+
+async fn tag_create_and_get(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let created = tags.create(new_tag("rust")).await.unwrap();
+    let fetched = tags.get("rust").await.unwrap();
+
+    assert_eq!(created.name, "rust");
+    assert_eq!(fetched.id, created.id);
+    assert_eq!(fetched.name, "rust");
+}
+
+async fn tag_names_are_unique(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+
+    let created = tags.create(new_tag("rust")).await;
+    let renamed = tags.update("zig", new_tag("rust"), Precondition::ANY).await;
+
+    assert!(matches!(created, Err(TagRepositoryError::TagAlreadyExists(name)) if name == "rust"));
+    assert!(matches!(renamed, Err(TagRepositoryError::TagAlreadyExists(name)) if name == "rust"));
+}
+
+async fn tag_names_are_validated(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+
+    let created = tags.create(new_tag("rust,zig")).await;
+    let renamed = tags
+        .update("rust", new_tag(" rust"), Precondition::ANY)
+        .await;
+
+    assert!(matches!(
+        created,
+        Err(TagRepositoryError::InvalidTag(violations)) if violation_codes(&violations) == vec!["tag_name_invalid"]
+    ));
+    assert!(matches!(
+        renamed,
+        Err(TagRepositoryError::InvalidTag(violations)) if violation_codes(&violations) == vec!["tag_name_invalid"]
+    ));
+    assert_eq!(tags.get("rust").await.unwrap().name, "rust");
+}
+
+async fn tag_update_and_delete(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let created = tags.create(new_tag("rust")).await.unwrap();
+
+    let updated = tags
+        .update("rust", new_tag("rustlang"), Precondition::ANY)
+        .await
+        .unwrap();
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.name, "rustlang");
+    assert!(matches!(
+        tags.get("rust").await,
+        Err(TagRepositoryError::TagNotFound(_))
+    ));
+
+    tags.delete("rustlang", DeleteTagOptions::default(), Precondition::ANY)
+        .await
+        .unwrap();
+    assert!(matches!(
+        tags.get("rustlang").await,
+        Err(TagRepositoryError::TagNotFound(_))
+    ));
+}
+
+async fn tag_patch(_: &impl PostRepository, tags: &impl TagRepository, _: &impl AuthorRepository) {
+    tags.create(new_tag("rust")).await.unwrap();
+
+    let unchanged = tags
+        .patch("rust", TagPatch::default(), Precondition::ANY)
+        .await
+        .unwrap();
+    let renamed = tags
+        .patch(
+            "rust",
+            TagPatch {
+                name: Some("rustlang".to_string()),
+            },
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(unchanged.name, "rust");
+    assert_eq!(renamed.id, unchanged.id);
+    assert_eq!(renamed.name, "rustlang");
+    assert!(matches!(
+        tags.patch("rust", TagPatch::default(), Precondition::ANY)
+            .await,
+        Err(TagRepositoryError::TagNotFound(_))
+    ));
+}
+
+async fn tag_revisions(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let created = tags.create(new_tag("rust")).await.unwrap();
+    let stale = Precondition::at(vec![created.revision]);
+
+    let renamed = tags
+        .update("rust", new_tag("rustlang"), stale.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(created.revision, 1);
+    assert_eq!(renamed.revision, 2);
+    assert!(matches!(
+        tags.update("rustlang", new_tag("rust"), stale.clone()).await,
+        Err(TagRepositoryError::RevisionMismatch(name, 2)) if name == "rustlang"
+    ));
+    assert!(matches!(
+        tags.patch("rustlang", TagPatch::default(), stale.clone())
+            .await,
+        Err(TagRepositoryError::RevisionMismatch(_, 2))
+    ));
+    assert!(matches!(
+        tags.delete("rustlang", DeleteTagOptions::default(), stale)
+            .await,
+        Err(TagRepositoryError::RevisionMismatch(_, 2))
+    ));
+
+    tags.delete(
+        "rustlang",
+        DeleteTagOptions::default(),
+        Precondition::at(vec![2]),
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        tags.delete(
+            "rustlang",
+            DeleteTagOptions::default(),
+            Precondition::at(vec![2])
+        )
+        .await,
+        Err(TagRepositoryError::TagNotFound(_))
+    ));
+}
+
+async fn tag_delete_in_use(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    posts
+        .create(new_post("live", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    let trashed = posts
+        .create(new_post("trashed", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts.delete(&trashed.id, Precondition::ANY).await.unwrap();
+
+    assert!(matches!(
+        tags.delete("rust", DeleteTagOptions::default(), Precondition::ANY).await,
+        Err(TagRepositoryError::TagInUse(name, 2)) if name == "rust"
+    ));
+    assert!(matches!(
+        tags.delete(
+            "rust",
+            DeleteTagOptions::default(),
+            Precondition::at(vec![7])
+        )
+        .await,
+        Err(TagRepositoryError::RevisionMismatch(_, 1))
+    ));
+    assert_eq!(tags.get("rust").await.unwrap().name, "rust");
+}
+
+async fn tag_force_delete_detaches_posts(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+    let live = posts
+        .create(
+            new_post("live", &["rust", "zig"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    let trashed = posts
+        .create(new_post("trashed", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts.delete(&trashed.id, Precondition::ANY).await.unwrap();
+
+    tags.delete("rust", DeleteTagOptions { force: true }, Precondition::ANY)
+        .await
+        .unwrap();
+    let restored = posts
+        .restore_deleted(&trashed.id, Precondition::ANY)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        tags.get("rust").await,
+        Err(TagRepositoryError::TagNotFound(_))
+    ));
+    let fetched = posts.get(&live.id).await.unwrap();
+    assert_eq!(tag_names(&fetched.tags), vec!["zig"]);
+    assert_eq!(fetched.revision, 2);
+    assert!(restored.tags.is_empty());
+    assert!(posts.find_dangling_relations().await.unwrap().is_empty());
+    assert!(posts.remove_dangling_relations().await.unwrap().is_empty());
+}
+
+async fn tag_missing_names_are_not_found(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    assert!(matches!(
+        tags.get("missing").await,
+        Err(TagRepositoryError::TagNotFound(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        tags.update("missing", new_tag("other"), Precondition::ANY).await,
+        Err(TagRepositoryError::TagNotFound(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        tags.delete("missing", DeleteTagOptions::default(), Precondition::ANY).await,
+        Err(TagRepositoryError::TagNotFound(name)) if name == "missing"
+    ));
+}
+
+async fn tag_find_in_names(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["c", "rust", "zig"]).await;
+
+    let found = tags.find_in_names(vec!["rust", "zig", "go"]).await.unwrap();
+
+    assert_eq!(tag_names(&found), vec!["rust", "zig"]);
+}
+
+async fn tag_pagination(
+    _: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["zig", "c", "rust", "go"]).await;
+
+    let page = tags
+        .find_all(FindAllOptions {
+            limit: 2,
+            offset: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let names = page
+        .tags
+        .iter()
+        .map(|tag| tag.name.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["go", "rust"]);
+    assert_eq!(page.total, 4);
+}
+
+async fn post_create_and_get(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "release"]).await;
+
+    let created = posts
+        .create(
+            new_post("hello", &["rust", "release"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    assert_eq!(fetched.id, created.id);
+    assert_eq!(fetched.title, "hello");
+    assert_eq!(fetched.content, "hello content");
+    assert_eq!(tag_names(&created.tags), vec!["release", "rust"]);
+    assert_eq!(tag_names(&fetched.tags), vec!["release", "rust"]);
+    assert_eq!(fetched.created_at, created.created_at);
+}
+
+async fn post_create_rejects_unknown_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+
+    let result = posts
+        .create(
+            new_post("hello", &["rust", "zig"]),
+            SavePostOptions::default(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PostRepositoryError::InvalidPost(violations)) if violation_codes(&violations) == vec!["tag_not_found"]
+    ));
+    assert_eq!(
+        posts
+            .find_all(
+                FindAllOptions {
+                    limit: 10,
+                    offset: 0,
+                    ..Default::default()
+                },
+                PostsFilter::default()
+            )
+            .await
+            .unwrap()
+            .total,
+        0
+    );
+}
+
+async fn post_create_missing_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let options = SavePostOptions {
+        create_missing_tags: true,
+    };
+
+    let created = posts
+        .create(new_post("hello", &["rust", "zig"]), options)
+        .await
+        .unwrap();
+    let again = posts
+        .create(new_post("again", &["zig"]), options)
+        .await
+        .unwrap();
+
+    assert_eq!(tag_names(&created.tags), vec!["rust", "zig"]);
+    assert_eq!(tag_names(&again.tags), vec!["zig"]);
+    assert_eq!(tags.get("zig").await.unwrap().name, "zig");
+    assert_eq!(
+        tags.find_in_names(vec!["rust", "zig"]).await.unwrap().len(),
+        2
+    );
+}
+
+async fn post_create_missing_tags_concurrently(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let options = SavePostOptions {
+        create_missing_tags: true,
+    };
+
+    let (first, second) = tokio::join!(
+        posts.create(new_post("first", &["zig"]), options),
+        posts.create(new_post("second", &["zig"]), options),
+    );
+
+    assert_eq!(tag_names(&first.unwrap().tags), vec!["zig"]);
+    assert_eq!(tag_names(&second.unwrap().tags), vec!["zig"]);
+    assert_eq!(tags.find_in_names(vec!["zig"]).await.unwrap().len(), 1);
+}
+
+async fn post_create_rejects_invalid_missing_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let result = posts
+        .create(
+            new_post("hello", &["rust,zig"]),
+            SavePostOptions {
+                create_missing_tags: true,
+            },
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PostRepositoryError::InvalidPost(violations)) if violation_codes(&violations) == vec!["tag_name_invalid"]
+    ));
+    assert!(tags
+        .find_in_names(vec!["rust,zig"])
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn post_update_rejects_invalid_posts(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let result = posts
+        .update(
+            &created.id,
+            NewPost {
+                slug: None,
+                title: " ".to_string(),
+                content: "content".to_string(),
+                tags: ["rust", "zig"].iter().map(|tag| tag.to_string()).collect(),
+                author: None,
+                status: None,
+                publish_at: None,
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PostRepositoryError::InvalidPost(violations))
+            if violation_codes(&violations) == vec!["title_empty", "tag_not_found"]
+    ));
+
+    let fetched = posts.get(&created.id).await.unwrap();
+    assert_eq!(fetched.title, "hello");
+    assert_eq!(tag_names(&fetched.tags), vec!["rust"]);
+}
+
+async fn post_update_syncs_relations(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "release", "zig"]).await;
+    let created = posts
+        .create(
+            new_post("hello", &["rust", "release"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let updated = posts
+        .update(
+            &created.id,
+            new_post("bye", &["rust", "zig"]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.title, "bye");
+    assert_eq!(fetched.title, "bye");
+    assert_eq!(fetched.content, "bye content");
+    assert_eq!(tag_names(&updated.tags), vec!["rust", "zig"]);
+    assert_eq!(tag_names(&fetched.tags), vec!["rust", "zig"]);
+}
+
+async fn post_update_missing_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let options = SavePostOptions {
+        create_missing_tags: true,
+    };
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let updated = posts
+        .update(
+            &created.id,
+            new_post("bye", &["rust", "zig"]),
+            options,
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tag_names(&updated.tags), vec!["rust", "zig"]);
+    assert_eq!(tags.get("zig").await.unwrap().name, "zig");
+    assert!(matches!(
+        posts
+            .update(
+                "missing",
+                new_post("bye", &["release"]),
+                options,
+                Precondition::ANY
+            )
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(tags.get("release").await.is_err());
+}
+
+async fn post_patch(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "release"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let retitled = posts
+        .patch(
+            &created.id,
+            PostPatch {
+                title: Some("bye".to_string()),
+                ..Default::default()
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(retitled.title, "bye");
+    assert_eq!(retitled.content, "hello content");
+    assert_eq!(tag_names(&retitled.tags), vec!["rust"]);
+
+    let retagged = posts
+        .patch(
+            &created.id,
+            serde_json::from_str(r#"{"tags": ["release"]}"#).unwrap(),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    assert_eq!(retagged.title, "bye");
+    assert_eq!(tag_names(&retagged.tags), vec!["release"]);
+    assert_eq!(tag_names(&fetched.tags), vec!["release"]);
+
+    let untagged = posts
+        .patch(
+            &created.id,
+            serde_json::from_str(r#"{"tags": null}"#).unwrap(),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert!(untagged.tags.is_empty());
+    assert!(matches!(
+        posts
+            .patch(
+                "missing",
+                PostPatch::default(),
+                SavePostOptions::default(),
+                Precondition::ANY
+            )
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_patch_rejects_invalid_posts(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let result = posts
+        .patch(
+            &created.id,
+            PostPatch {
+                title: Some(" ".to_string()),
+                tags: Some(["zig".to_string()].into()),
+                ..Default::default()
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PostRepositoryError::InvalidPost(violations))
+            if violation_codes(&violations) == vec!["title_empty", "tag_not_found"]
+    ));
+
+    let fetched = posts.get(&created.id).await.unwrap();
+    assert_eq!(fetched.title, "hello");
+    assert_eq!(tag_names(&fetched.tags), vec!["rust"]);
+}
+
+async fn post_add_and_remove_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "release"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let added = posts
+        .add_tags(
+            &created.id,
+            vec!["release".to_string(), "rust".to_string()],
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(added.title, "hello");
+    assert_eq!(tag_names(&added.tags), vec!["release", "rust"]);
+    assert!(matches!(
+        posts
+            .add_tags(
+                &created.id,
+                vec!["zig".to_string()],
+                SavePostOptions::default(),
+                Precondition::ANY,
+            )
+            .await,
+        Err(PostRepositoryError::InvalidPost(violations))
+            if violation_codes(&violations) == vec!["tag_not_found"]
+    ));
+
+    let created_tag = posts
+        .add_tags(
+            &created.id,
+            vec!["zig".to_string()],
+            SavePostOptions {
+                create_missing_tags: true,
+            },
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tag_names(&created_tag.tags), vec!["release", "rust", "zig"]);
+
+    let removed = posts
+        .remove_tags(
+            &created.id,
+            vec!["rust".to_string(), "draft".to_string()],
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    assert_eq!(tag_names(&removed.tags), vec!["release", "zig"]);
+    assert_eq!(tag_names(&fetched.tags), vec!["release", "zig"]);
+    assert_eq!(tags.get("rust").await.unwrap().name, "rust");
+    assert!(matches!(
+        posts
+            .remove_tags("missing", vec!["rust".to_string()], Precondition::ANY)
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_timestamps(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(created.updated_at, created.created_at);
+
+    let updated = posts
+        .update(
+            &created.id,
+            new_post("bye", &["rust"]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(updated.created_at, created.created_at);
+    assert!(updated.updated_at > created.updated_at);
+
+    let patched = posts
+        .add_tags(
+            &created.id,
+            vec!["zig".to_string()],
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    assert_eq!(patched.created_at, created.created_at);
+    assert!(patched.updated_at > updated.updated_at);
+    assert_eq!(fetched.created_at, created.created_at);
+    assert_eq!(fetched.updated_at, patched.updated_at);
+}
+
+async fn post_revisions(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    let stale = Precondition::at(vec![created.revision]);
+
+    let updated = posts
+        .update(
+            &created.id,
+            new_post("bye", &[]),
+            SavePostOptions::default(),
+            stale.clone(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(created.revision, 1);
+    assert_eq!(updated.revision, 2);
+
+    let conflicting_update = posts
+        .update(
+            &created.id,
+            new_post("again", &["rust"]),
+            SavePostOptions::default(),
+            stale.clone(),
+        )
+        .await;
+    let conflicting_patch = posts
+        .patch(
+            &created.id,
+            PostPatch::default(),
+            SavePostOptions::default(),
+            stale.clone(),
+        )
+        .await;
+    let conflicting_delete = posts.delete(&created.id, stale).await;
+    let fetched = posts.get(&created.id).await.unwrap();
+
+    for result in [conflicting_update, conflicting_patch] {
+        assert!(matches!(
+            result,
+            Err(PostRepositoryError::RevisionMismatch(id, 2)) if id == created.id
+        ));
+    }
+    assert!(matches!(
+        conflicting_delete,
+        Err(PostRepositoryError::RevisionMismatch(_, 2))
+    ));
+    assert_eq!(fetched.title, "bye");
+    assert!(fetched.tags.is_empty());
+    assert_eq!(fetched.revision, 2);
+
+    posts
+        .delete(&created.id, Precondition::at(vec![1, 2]))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        posts.delete(&created.id, Precondition::at(vec![2])).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_revision_history(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+    let created = posts
+        .create(
+            new_post("hello", &["zig", "rust"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    posts
+        .update(
+            &created.id,
+            new_post("bye", &["rust"]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let patched = posts
+        .patch(
+            &created.id,
+            PostPatch {
+                content: Some("bye content\nagain".to_string()),
+                ..Default::default()
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    let history = posts
+        .revisions(&created.id, FindAllOptions::default())
+        .await
+        .unwrap();
+    let page = posts
+        .revisions(
+            &created.id,
+            FindAllOptions {
+                limit: 1,
+                offset: 1,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let first = posts.get_revision(&created.id, 1).await.unwrap();
+
+    assert_eq!(history.total, 3);
+    assert_eq!(
+        history
+            .revisions
+            .iter()
+            .map(|revision| revision.revision)
+            .collect::<Vec<_>>(),
+        vec![3, 2, 1]
+    );
+    assert_eq!(history.revisions[0], (&patched).into());
+    assert_eq!(page.total, 3);
+    assert_eq!(page.revisions.len(), 1);
+    assert_eq!(page.revisions[0].title, "bye");
+    assert_eq!(first.title, "hello");
+    assert_eq!(first.content, "hello content");
+    assert_eq!(first.tags, vec!["rust", "zig"]);
+    assert_eq!(first.created_at, created.created_at);
+
+    let diff = PostRevisionDiff::between(&first, &history.revisions[0]);
+
+    assert_eq!(diff.from, 1);
+    assert_eq!(diff.to, 3);
+    assert_eq!(diff.title, "-hello\n+bye");
+    assert_eq!(diff.content, "-hello content\n+bye content\n+again");
+    assert_eq!(
+        diff.tags,
+        TagChanges {
+            added: vec![],
+            removed: vec!["zig".to_string()],
+        }
+    );
+
+    assert!(matches!(
+        posts.get_revision(&created.id, 4).await,
+        Err(PostRepositoryError::RevisionNotFound(id, 4)) if id == created.id
+    ));
+    assert!(matches!(
+        posts.get_revision("missing", 1).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.revisions("missing", FindAllOptions::default()).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+
+    posts.delete(&created.id, Precondition::ANY).await.unwrap();
+
+    assert!(matches!(
+        posts
+            .revisions(&created.id, FindAllOptions::default())
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_revision_restore(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts
+        .update(
+            &created.id,
+            new_post("bye", &["zig"]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    let conflicting = posts
+        .restore_revision(
+            &created.id,
+            1,
+            SavePostOptions::default(),
+            Precondition::at(vec![1]),
+        )
+        .await;
+    let restored = posts
+        .restore_revision(
+            &created.id,
+            1,
+            SavePostOptions::default(),
+            Precondition::at(vec![2]),
+        )
+        .await
+        .unwrap();
+    let history = posts
+        .revisions(&created.id, FindAllOptions::default())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        conflicting,
+        Err(PostRepositoryError::RevisionMismatch(_, 2))
+    ));
+    assert_eq!(restored.revision, 3);
+    assert_eq!(restored.title, "hello");
+    assert_eq!(restored.content, "hello content");
+    assert_eq!(tag_names(&restored.tags), vec!["rust"]);
+    assert_eq!(history.total, 3);
+    assert_eq!(history.revisions[0].title, "hello");
+    assert_eq!(history.revisions[1].title, "bye");
+
+    tags.delete("rust", DeleteTagOptions { force: true }, Precondition::ANY)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        posts
+            .restore_revision(&created.id, 1, SavePostOptions::default(), Precondition::ANY)
+            .await,
+        Err(PostRepositoryError::InvalidPost(violations)) if violations[0].code == "tag_not_found"
+    ));
+    assert!(matches!(
+        posts
+            .restore_revision(
+                &created.id,
+                7,
+                SavePostOptions::default(),
+                Precondition::ANY
+            )
+            .await,
+        Err(PostRepositoryError::RevisionNotFound(_, 7))
+    ));
+}
+
+async fn post_delete(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    posts.delete(&created.id, Precondition::ANY).await.unwrap();
+
+    assert!(matches!(
+        posts.get(&created.id).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.delete(&created.id, Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_trash(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "zig"]).await;
+    let kept = posts
+        .create(
+            new_post("kept hello", &["rust"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    let mut deleted = vec![];
+    for title in ["first hello", "second hello"] {
+        let post = posts
+            .create(new_post(title, &["zig"]), SavePostOptions::default())
+            .await
+            .unwrap();
+        posts.delete(&post.id, Precondition::ANY).await.unwrap();
+        deleted.push(post);
+    }
+
+    let listed = posts
+        .find_all(FindAllOptions::default(), PostsFilter::default())
+        .await
+        .unwrap();
+    let found = posts
+        .search("hello", FindAllOptions::default())
+        .await
+        .unwrap();
+    let trash = posts.find_deleted(FindAllOptions::default()).await.unwrap();
+    let most_used = tags
+        .find_all(FindAllOptions {
+            sort: Some("-post_count".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(post_titles(&listed.posts), vec!["kept hello"]);
+    assert_eq!(listed.total, 1);
+    assert_eq!(found.total, 1);
+    assert_eq!(found.posts[0].post.id, kept.id);
+    assert_eq!(trash.total, 2);
+    assert_eq!(
+        trash
+            .posts
+            .iter()
+            .map(|post| post.post.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["second hello", "first hello"]
+    );
+    assert_eq!(trash.posts[0].post.revision, 2);
+    assert_eq!(tag_names(&trash.posts[0].post.tags), vec!["zig"]);
+    assert_eq!(most_used.tags[0].name, "rust");
+
+    let id = &deleted[0].id;
+    assert!(matches!(
+        posts.get(id).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts
+            .update(
+                id,
+                new_post("again", &[]),
+                SavePostOptions::default(),
+                Precondition::ANY
+            )
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.revisions(id, FindAllOptions::default()).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.delete(id, Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.restore_deleted(&kept.id, Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        posts.purge(&kept.id, Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+
+    let restored = posts.restore_deleted(id, Precondition::ANY).await.unwrap();
+    let history = posts
+        .revisions(id, FindAllOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(restored.title, "first hello");
+    assert_eq!(tag_names(&restored.tags), vec!["zig"]);
+    assert_eq!(restored.revision, 3);
+    assert_eq!(posts.get(id).await.unwrap().revision, 3);
+    assert_eq!(history.total, 3);
+
+    posts
+        .purge(&deleted[1].id, Precondition::ANY)
+        .await
+        .unwrap();
+    let trash = posts.find_deleted(FindAllOptions::default()).await.unwrap();
+
+    assert_eq!(trash.total, 0);
+    assert!(matches!(
+        posts
+            .restore_deleted(&deleted[1].id, Precondition::ANY)
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+}
+
+async fn post_trash_preconditions(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let created = posts
+        .create(new_post("hello", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts
+        .delete(&created.id, Precondition::at(vec![1]))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        posts
+            .restore_deleted(&created.id, Precondition::at(vec![1]))
+            .await,
+        Err(PostRepositoryError::RevisionMismatch(_, 2))
+    ));
+    assert!(matches!(
+        posts.purge(&created.id, Precondition::at(vec![1])).await,
+        Err(PostRepositoryError::RevisionMismatch(_, 2))
+    ));
+
+    posts
+        .purge(&created.id, Precondition::at(vec![2]))
+        .await
+        .unwrap();
+}
+
+async fn post_status(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let create = |title: &str, status| {
+        posts.create(
+            NewPost {
+                status,
+                ..new_post(title, &[])
+            },
+            SavePostOptions::default(),
+        )
+    };
+    let published = create("published", None).await.unwrap();
+    let draft = create("draft", Some(PostStatus::Draft)).await.unwrap();
+
+    let updated = posts
+        .update(
+            &draft.id,
+            new_post("still a draft", &[]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let patch = |status| PostPatch {
+        status: Some(status),
+        ..Default::default()
+    };
+    let publication = posts
+        .patch(
+            &draft.id,
+            patch(PostStatus::Published),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let archived = posts
+        .patch(
+            &draft.id,
+            patch(PostStatus::Archived),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(published.status, PostStatus::Published);
+    assert!(published.published_at.is_some());
+    assert_eq!(draft.status, PostStatus::Draft);
+    assert_eq!(draft.published_at, None);
+    assert_eq!(updated.status, PostStatus::Draft);
+    assert_eq!(publication.status, PostStatus::Published);
+    assert!(publication.published_at.is_some());
+    assert_eq!(archived.status, PostStatus::Archived);
+    assert_eq!(archived.published_at, publication.published_at);
+
+    let find = |status: &[PostStatus]| {
+        posts.find_all(
+            FindAllOptions::default(),
+            PostsFilter {
+                status: status.to_vec(),
+                ..Default::default()
+            },
+        )
+    };
+    let default = find(&[]).await.unwrap();
+    let all = find(&[
+        PostStatus::Draft,
+        PostStatus::Published,
+        PostStatus::Scheduled,
+        PostStatus::Archived,
+    ])
+    .await
+    .unwrap();
+    let archived = find(&[PostStatus::Archived]).await.unwrap();
+    let live = find(&[PostStatus::Draft, PostStatus::Published])
+        .await
+        .unwrap();
+
+    assert_eq!(post_titles(&default.posts), vec!["published"]);
+    assert_eq!(default.total, 1);
+    assert_eq!(all.total, 2);
+    assert_eq!(post_titles(&archived.posts), vec!["still a draft"]);
+    assert_eq!(post_titles(&live.posts), vec!["published"]);
+}
+
+async fn post_publish_scheduled(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let publish_at = Utc::now() + Duration::hours(1);
+    let schedule = |title: &str| {
+        posts.create(
+            NewPost {
+                status: Some(PostStatus::Scheduled),
+                publish_at: Some(publish_at),
+                ..new_post(title, &[])
+            },
+            SavePostOptions::default(),
+        )
+    };
+    let scheduled = schedule("scheduled").await.unwrap();
+    let trashed = schedule("trashed").await.unwrap();
+    posts.delete(&trashed.id, Precondition::ANY).await.unwrap();
+
+    let early = posts.publish_scheduled(Utc::now()).await.unwrap();
+    let published = posts.publish_scheduled(publish_at).await.unwrap();
+    let again = posts.publish_scheduled(publish_at).await.unwrap();
+    let history = posts
+        .revisions(&scheduled.id, FindAllOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(scheduled.status, PostStatus::Scheduled);
+    assert_eq!(scheduled.publish_at, Some(publish_at));
+    assert_eq!(scheduled.published_at, None);
+    assert!(early.is_empty());
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].id, scheduled.id);
+    assert_eq!(published[0].status, PostStatus::Published);
+    assert_eq!(published[0].publish_at, None);
+    assert_eq!(published[0].published_at, Some(publish_at));
+    assert_eq!(published[0].revision, 2);
+    assert!(again.is_empty());
+    assert_eq!(
+        posts.get(&scheduled.id).await.unwrap().status,
+        PostStatus::Published
+    );
+    assert_eq!(history.total, 2);
+}
+
+async fn post_rescheduling(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let publish_at = Utc::now() + Duration::hours(1);
+    let patch = |status, publish_at| PostPatch {
+        status,
+        publish_at,
+        ..Default::default()
+    };
+    let created = posts
+        .create(new_post("hello", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let unscheduled = posts
+        .patch(
+            &created.id,
+            patch(Some(PostStatus::Scheduled), None),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+    let misplaced = posts
+        .patch(
+            &created.id,
+            patch(None, Some(publish_at)),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+    let scheduled = posts
+        .patch(
+            &created.id,
+            patch(Some(PostStatus::Scheduled), Some(publish_at)),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let rescheduled = posts
+        .patch(
+            &created.id,
+            patch(None, Some(publish_at + Duration::hours(1))),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let drafted = posts
+        .patch(
+            &created.id,
+            patch(Some(PostStatus::Draft), None),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        unscheduled,
+        Err(PostRepositoryError::InvalidPost(violations)) if violations[0].code == "publish_at_missing"
+    ));
+    assert!(matches!(
+        misplaced,
+        Err(PostRepositoryError::InvalidPost(violations)) if violations[0].code == "publish_at_unexpected"
+    ));
+    assert_eq!(scheduled.status, PostStatus::Scheduled);
+    assert_eq!(scheduled.publish_at, Some(publish_at));
+    assert_eq!(rescheduled.status, PostStatus::Scheduled);
+    assert_eq!(
+        rescheduled.publish_at,
+        Some(publish_at + Duration::hours(1))
+    );
+    assert_eq!(drafted.status, PostStatus::Draft);
+    assert_eq!(drafted.publish_at, None);
+    assert_eq!(drafted.published_at, created.published_at);
+}
+
+async fn post_slugs(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let create = |title: &str, slug: Option<&str>| {
+        posts.create(
+            NewPost {
+                slug: slug.map(str::to_string),
+                ..new_post(title, &[])
+            },
+            SavePostOptions::default(),
+        )
+    };
+    let first = create("Hello, World!", None).await.unwrap();
+    let second = create("hello world", None).await.unwrap();
+    let accented = create("Ça va être l'été", None).await.unwrap();
+    let requested = create("anything", Some("chosen")).await.unwrap();
+    let taken = create("anything else", Some("hello-world")).await;
+    let invalid = create("anything else", Some("Not A Slug")).await;
+    let updated = posts
+        .update(
+            &second.id,
+            new_post("renamed", &[]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    let conflicting = posts
+        .patch(
+            &second.id,
+            PostPatch {
+                slug: Some("chosen".to_string()),
+                ..Default::default()
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+    let found = posts.get_by_slug("hello-world-2").await.unwrap();
+    let missing = posts.get_by_slug("missing").await;
+
+    assert_eq!(first.slug, "hello-world");
+    assert_eq!(second.slug, "hello-world-2");
+    assert_eq!(accented.slug, "ca-va-etre-l-ete");
+    assert_eq!(requested.slug, "chosen");
+    assert!(
+        matches!(taken, Err(PostRepositoryError::SlugAlreadyExists(slug)) if slug == "hello-world")
+    );
+    assert!(matches!(
+        invalid,
+        Err(PostRepositoryError::InvalidPost(violations)) if violations[0].code == "slug_invalid"
+    ));
+    assert_eq!(updated.slug, "hello-world-2");
+    assert!(
+        matches!(conflicting, Err(PostRepositoryError::SlugAlreadyExists(slug)) if slug == "chosen")
+    );
+    assert!(matches!(found, SlugLookup::Post(post) if post.id == second.id));
+    assert!(matches!(missing, Err(PostRepositoryError::PostNotFound(slug)) if slug == "missing"));
+}
+
+async fn post_slug_redirects(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let rename = |id: String, slug: &'static str| async move {
+        posts
+            .patch(
+                &id,
+                PostPatch {
+                    slug: Some(slug.to_string()),
+                    ..Default::default()
+                },
+                SavePostOptions::default(),
+                Precondition::ANY,
+            )
+            .await
+    };
+    let created = posts
+        .create(new_post("hello", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let renamed = rename(created.id.clone(), "goodbye").await.unwrap();
+    let redirected = posts.get_by_slug("hello").await.unwrap();
+    let other = posts
+        .create(new_post("hello", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    let taken = posts
+        .create(
+            NewPost {
+                slug: Some("hello".to_string()),
+                ..new_post("other", &[])
+            },
+            SavePostOptions::default(),
+        )
+        .await;
+
+    assert_eq!(renamed.slug, "goodbye");
+    assert_eq!(renamed.revision, 2);
+    assert!(matches!(redirected, SlugLookup::Redirect(slug) if slug == "goodbye"));
+    assert_eq!(other.slug, "hello-2");
+    assert!(matches!(taken, Err(PostRepositoryError::SlugAlreadyExists(slug)) if slug == "hello"));
+
+    let reclaimed = rename(created.id.clone(), "hello").await.unwrap();
+    let current = posts.get_by_slug("hello").await.unwrap();
+    let former = posts.get_by_slug("goodbye").await.unwrap();
+
+    assert_eq!(reclaimed.slug, "hello");
+    assert!(matches!(current, SlugLookup::Post(post) if post.id == created.id));
+    assert!(matches!(former, SlugLookup::Redirect(slug) if slug == "hello"));
+
+    posts.delete(&created.id, Precondition::ANY).await.unwrap();
+    let trashed = posts.get_by_slug("hello").await;
+    let trashed_redirect = posts.get_by_slug("goodbye").await;
+    let reused = rename(other.id.clone(), "goodbye").await;
+
+    assert!(matches!(trashed, Err(PostRepositoryError::PostNotFound(_))));
+    assert!(matches!(
+        trashed_redirect,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert!(matches!(
+        reused,
+        Err(PostRepositoryError::SlugAlreadyExists(_))
+    ));
+
+    posts.purge(&created.id, Precondition::ANY).await.unwrap();
+    let reused = rename(other.id.clone(), "goodbye").await.unwrap();
+
+    assert_eq!(reused.slug, "goodbye");
+    assert!(matches!(
+        posts.get_by_slug("hello-2").await.unwrap(),
+        SlugLookup::Redirect(slug) if slug == "goodbye"
+    ));
+}
+
+async fn post_purge_deleted_before(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let live = posts
+        .create(new_post("live", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    let deleted = posts
+        .create(new_post("deleted", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts.delete(&deleted.id, Precondition::ANY).await.unwrap();
+    let deleted_at = posts
+        .find_deleted(FindAllOptions::default())
+        .await
+        .unwrap()
+        .posts[0]
+        .deleted_at;
+
+    let too_early = posts.purge_deleted_before(deleted_at).await.unwrap();
+    let purged = posts
+        .purge_deleted_before(deleted_at + Duration::seconds(1))
+        .await
+        .unwrap();
+    let trash = posts.find_deleted(FindAllOptions::default()).await.unwrap();
+
+    assert_eq!(too_early, 0);
+    assert_eq!(purged, 1);
+    assert_eq!(trash.total, 0);
+    assert!(matches!(
+        posts
+            .revisions(&deleted.id, FindAllOptions::default())
+            .await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert_eq!(posts.get(&live.id).await.unwrap().title, "live");
+}
+
+async fn post_missing_ids_are_not_found(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    assert!(matches!(
+        posts.get("missing").await,
+        Err(PostRepositoryError::PostNotFound(id)) if id == "missing"
+    ));
+    assert!(matches!(
+        posts.update("missing", new_post("hello", &[]), SavePostOptions::default(), Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(id)) if id == "missing"
+    ));
+    assert!(matches!(
+        posts.delete("missing", Precondition::ANY).await,
+        Err(PostRepositoryError::PostNotFound(id)) if id == "missing"
+    ));
+}
+
+async fn post_pagination(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    for title in ["first", "second", "third", "fourth"] {
+        posts
+            .create(new_post(title, &[]), SavePostOptions::default())
+            .await
+            .unwrap();
+    }
+
+    let page = posts
+        .find_all(
+            FindAllOptions {
+                limit: 2,
+                offset: 1,
+                ..Default::default()
+            },
+            PostsFilter::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(post_titles(&page.posts), vec!["second", "third"]);
+    assert_eq!(page.total, 4);
+}
+
+async fn post_cursor_pagination(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    for title in ["b", "a", "b", "c", "b"] {
+        posts
+            .create(new_post(title, &[]), SavePostOptions::default())
+            .await
+            .unwrap();
+    }
+
+    let find = |cursor: Option<String>| {
+        posts.find_all(
+            FindAllOptions {
+                limit: 2,
+                sort: Some("-title".to_string()),
+                cursor,
+                ..Default::default()
+            },
+            PostsFilter::default(),
+        )
+    };
+
+    let mut walked = vec![];
+    let mut cursor = None;
+    loop {
+        let page = find(cursor).await.unwrap();
+        assert_eq!(page.total, 5);
+
+        walked.extend(page.posts);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    let all = posts
+        .find_all(
+            FindAllOptions {
+                sort: Some("-title".to_string()),
+                ..Default::default()
+            },
+            PostsFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(post_titles(&walked), vec!["c", "b", "b", "b", "a"]);
+    assert_eq!(
+        walked.iter().map(|post| &post.id).collect::<Vec<_>>(),
+        all.posts.iter().map(|post| &post.id).collect::<Vec<_>>()
+    );
+
+    // Posts created before the cursor position do not shift the following pages.
+    let first_page = find(None).await.unwrap();
+    posts
+        .create(new_post("d", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    let second_page = find(first_page.next_cursor).await.unwrap();
+    assert_eq!(post_titles(&second_page.posts), vec!["b", "b"]);
+}
+
+async fn post_invalid_cursor(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    for title in ["first", "second"] {
+        posts
+            .create(new_post(title, &[]), SavePostOptions::default())
+            .await
+            .unwrap();
+    }
+
+    let find = |sort: &str, cursor: Option<String>| {
+        posts.find_all(
+            FindAllOptions {
+                limit: 1,
+                sort: Some(sort.to_string()),
+                cursor,
+                ..Default::default()
+            },
+            PostsFilter::default(),
+        )
+    };
+
+    assert!(matches!(
+        find("title", Some("garbage".to_string())).await,
+        Err(PostRepositoryError::InvalidCursor(cursor)) if cursor == "garbage"
+    ));
+
+    let by_title = find("title", None).await.unwrap();
+    assert!(matches!(
+        find("-created_at", by_title.next_cursor).await,
+        Err(PostRepositoryError::InvalidCursor(_))
+    ));
+}
+
+async fn post_reflects_renamed_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust"]).await;
+    let created = posts
+        .create(new_post("hello", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    tags.update("rust", new_tag("rustlang"), Precondition::ANY)
+        .await
+        .unwrap();
+
+    let fetched = posts.get(&created.id).await.unwrap();
+    assert_eq!(tag_names(&fetched.tags), vec!["rustlang"]);
+    assert_eq!(fetched.revision, 2);
+    assert!(fetched.updated_at > created.updated_at);
+    let history = posts
+        .revisions(&created.id, FindAllOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(history.total, 2);
+    assert_eq!(history.revisions[0].tags, vec!["rustlang"]);
+
+    tags.update("rustlang", new_tag("rustlang"), Precondition::ANY)
+        .await
+        .unwrap();
+    assert_eq!(posts.get(&created.id).await.unwrap().revision, 2);
+}
+
+async fn post_filter_by_tags(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["rust", "release", "draft"]).await;
+    posts
+        .create(new_post("rust", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts
+        .create(
+            new_post("rust release", &["rust", "release"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    posts
+        .create(
+            new_post("rust draft", &["rust", "draft"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    posts
+        .create(new_post("untagged", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let find = |tags_any: &[&str], tags_all: &[&str], tags_none: &[&str]| {
+        let filter = PostsFilter {
+            tags_any: tags_any.iter().map(|tag| tag.to_string()).collect(),
+            tags_all: tags_all.iter().map(|tag| tag.to_string()).collect(),
+            tags_none: tags_none.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+
+        posts.find_all(
+            FindAllOptions {
+                limit: 1,
+                offset: 0,
+                ..Default::default()
+            },
+            filter,
+        )
+    };
+
+    let any = find(&["release", "draft"], &[], &[]).await.unwrap();
+    assert_eq!(post_titles(&any.posts), vec!["rust release"]);
+    assert_eq!(any.total, 2);
+
+    let all = find(&[], &["rust", "release"], &[]).await.unwrap();
+    assert_eq!(post_titles(&all.posts), vec!["rust release"]);
+    assert_eq!(all.total, 1);
+
+    let none = find(&[], &[], &["release", "draft"]).await.unwrap();
+    assert_eq!(post_titles(&none.posts), vec!["rust"]);
+    assert_eq!(none.total, 2);
+
+    let combined = find(&["rust"], &[], &["draft"]).await.unwrap();
+    assert_eq!(combined.total, 2);
+}
+
+async fn post_search_only_published(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    for status in [
+        PostStatus::Draft,
+        PostStatus::Published,
+        PostStatus::Archived,
+    ] {
+        posts
+            .create(
+                NewPost {
+                    status: Some(status),
+                    ..new_post(&format!("Rust {status}"), &[])
+                },
+                SavePostOptions::default(),
+            )
+            .await
+            .unwrap();
+    }
+
+    let found = posts
+        .search("rust", FindAllOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(found.total, 1);
+    assert_eq!(found.posts.len(), 1);
+    assert_eq!(found.posts[0].post.title, "Rust published");
+}
+
+async fn post_search(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    let create = |title: &str, content: &str| {
+        posts.create(
+            NewPost {
+                slug: None,
+                title: title.to_string(),
+                content: content.to_string(),
+                tags: Default::default(),
+                author: None,
+                status: None,
+                publish_at: None,
+            },
+            SavePostOptions::default(),
+        )
+    };
+    create("Rust release", "The rust compiler ships today.")
+        .await
+        .unwrap();
+    create("Go", "Go is nice and rust is too.").await.unwrap();
+    for title in ["Zig", "Haskell", "OCaml", "Elixir"] {
+        create(title, "Another language entirely.").await.unwrap();
+    }
+
+    let search = |query: &'static str, limit: usize, offset: usize| {
+        posts.search(
+            query,
+            FindAllOptions {
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+    };
+    let titles = |response: &SearchPostsResponse| {
+        response
+            .posts
+            .iter()
+            .map(|hit| hit.post.title.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let rust = search("rust", 10, 0).await.unwrap();
+    assert_eq!(titles(&rust), vec!["Rust release", "Go"]);
+    assert_eq!(rust.total, 2);
+    assert_eq!(rust.posts[0].highlights.title, "<mark>Rust</mark> release");
+    assert_eq!(
+        rust.posts[0].highlights.content,
+        "The <mark>rust</mark> compiler ships today."
+    );
+    assert!(rust.posts[0].score > rust.posts[1].score);
+
+    let second_page = search("rust", 1, 1).await.unwrap();
+    assert_eq!(titles(&second_page), vec!["Go"]);
+    assert_eq!(second_page.total, 2);
+
+    let all_terms = search("rust release", 10, 0).await.unwrap();
+    assert_eq!(titles(&all_terms), vec!["Rust release"]);
+
+    let stemmed = search("Releases", 10, 0).await.unwrap();
+    assert_eq!(titles(&stemmed), vec!["Rust release"]);
+    assert_eq!(
+        stemmed.posts[0].highlights.title,
+        "Rust <mark>release</mark>"
+    );
+    let shipping = search("shipping", 10, 0).await.unwrap();
+    assert_eq!(
+        shipping.posts[0].highlights.content,
+        "The rust compiler <mark>ships</mark> today."
+    );
+
+    assert_eq!(search("cobol", 10, 0).await.unwrap().total, 0);
+    assert!(matches!(
+        search(" ", 10, 0).await,
+        Err(PostRepositoryError::EmptySearchQuery)
+    ));
+}
+
+async fn post_sorting(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    for title in ["b", "c", "a"] {
+        posts
+            .create(new_post(title, &[]), SavePostOptions::default())
+            .await
+            .unwrap();
+    }
+
+    let find = |sort: &str| {
+        posts.find_all(
+            FindAllOptions {
+                sort: Some(sort.to_string()),
+                ..Default::default()
+            },
+            PostsFilter::default(),
+        )
+    };
+
+    let newest_first = find("-created_at").await.unwrap();
+    assert_eq!(post_titles(&newest_first.posts), vec!["a", "c", "b"]);
+
+    let by_title = find("title").await.unwrap();
+    assert_eq!(post_titles(&by_title.posts), vec!["a", "b", "c"]);
+
+    let by_title_descending = find("-title").await.unwrap();
+    assert_eq!(post_titles(&by_title_descending.posts), vec!["c", "b", "a"]);
+
+    assert!(matches!(
+        find("content").await,
+        Err(PostRepositoryError::InvalidSort(sort)) if sort == "content"
+    ));
+}
+
+async fn tag_sorting(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["c", "rust", "zig"]).await;
+    posts
+        .create(
+            new_post("one", &["rust", "zig"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    posts
+        .create(new_post("two", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let find = |sort: &str| {
+        tags.find_all(FindAllOptions {
+            sort: Some(sort.to_string()),
+            ..Default::default()
+        })
+    };
+    let names = |tags: Vec<Tag>| tags.into_iter().map(|tag| tag.name).collect::<Vec<_>>();
+
+    let by_name_descending = find("-name").await.unwrap();
+    assert_eq!(names(by_name_descending.tags), vec!["zig", "rust", "c"]);
+
+    let most_used = find("-post_count").await.unwrap();
+    assert_eq!(names(most_used.tags), vec!["rust", "zig", "c"]);
+
+    assert!(matches!(
+        find("id").await,
+        Err(TagRepositoryError::InvalidSort(sort)) if sort == "id"
+    ));
+}
+
+async fn tag_cursor_pagination(
+    posts: &impl PostRepository,
+    tags: &impl TagRepository,
+    _: &impl AuthorRepository,
+) {
+    create_tags(tags, &["c", "go", "rust", "zig"]).await;
+    posts
+        .create(
+            new_post("one", &["rust", "zig"]),
+            SavePostOptions::default(),
+        )
+        .await
+        .unwrap();
+    posts
+        .create(new_post("two", &["rust"]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let mut walked = vec![];
+    let mut cursor = None;
+    loop {
+        let page = tags
+            .find_all(FindAllOptions {
+                limit: 1,
+                sort: Some("-post_count".to_string()),
+                cursor,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 4);
+
+        walked.extend(page.tags);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(walked[0].name, "rust");
+    assert_eq!(walked[1].name, "zig");
+    assert_eq!(tag_names(&walked[2..]), vec!["c", "go"]);
+
+    assert!(matches!(
+        tags.find_all(FindAllOptions {
+            cursor: Some("garbage".to_string()),
+            ..Default::default()
+        })
+        .await,
+        Err(TagRepositoryError::InvalidCursor(_))
+    ));
+}
+
+async fn create_authors(authors: &impl AuthorRepository, names: &[&str]) {
+    for name in names {
+        authors.create(new_author(name)).await.unwrap();
+    }
+}
+
+fn authored_post(title: &str, author: &str) -> NewPost {
+    NewPost {
+        author: Some(author.to_string()),
+        ..new_post(title, &[])
+    }
+}
+
+async fn author_create_and_get(
+    _: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    let created = authors.create(new_author("ana")).await.unwrap();
+    let fetched = authors.get("ana").await.unwrap();
+
+    assert_eq!(created.name, "ana");
+    assert_eq!(created.display_name, "ANA");
+    assert_eq!(created.revision, 1);
+    assert_eq!(fetched.id, created.id);
+    assert!(matches!(
+        authors.get("bruno").await,
+        Err(AuthorRepositoryError::AuthorNotFound(name)) if name == "bruno"
+    ));
+}
+
+async fn author_names_are_unique(
+    _: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana", "bruno"]).await;
+
+    let created = authors.create(new_author("ana")).await;
+    let renamed = authors
+        .update("bruno", new_author("ana"), Precondition::ANY)
+        .await;
+
+    assert!(
+        matches!(created, Err(AuthorRepositoryError::AuthorAlreadyExists(name)) if name == "ana")
+    );
+    assert!(
+        matches!(renamed, Err(AuthorRepositoryError::AuthorAlreadyExists(name)) if name == "ana")
+    );
+}
+
+async fn author_update_and_patch(
+    _: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    let created = authors.create(new_author("ana")).await.unwrap();
+
+    let updated = authors
+        .update("ana", new_author("ana.s"), Precondition::ANY)
+        .await
+        .unwrap();
+    let unchanged = authors
+        .patch("ana.s", AuthorPatch::default(), Precondition::ANY)
+        .await
+        .unwrap();
+    let patched = authors
+        .patch(
+            "ana.s",
+            AuthorPatch {
+                display_name: Some("Ana Souza".to_string()),
+                ..Default::default()
+            },
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.name, "ana.s");
+    assert_eq!(unchanged.revision, updated.revision);
+    assert_eq!(patched.name, "ana.s");
+    assert_eq!(patched.display_name, "Ana Souza");
+    assert!(matches!(
+        authors.get("ana").await,
+        Err(AuthorRepositoryError::AuthorNotFound(_))
+    ));
+
+    authors.delete("ana.s", Precondition::ANY).await.unwrap();
+    assert!(matches!(
+        authors.get("ana.s").await,
+        Err(AuthorRepositoryError::AuthorNotFound(_))
+    ));
+}
+
+async fn author_revisions(
+    _: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    let created = authors.create(new_author("ana")).await.unwrap();
+    let stale = Precondition::at(vec![created.revision]);
+
+    let renamed = authors
+        .update("ana", new_author("ana.s"), stale.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(renamed.revision, 2);
+    assert!(matches!(
+        authors.update("ana.s", new_author("ana"), stale.clone()).await,
+        Err(AuthorRepositoryError::RevisionMismatch(name, 2)) if name == "ana.s"
+    ));
+    assert!(matches!(
+        authors
+            .patch("ana.s", AuthorPatch::default(), stale.clone())
+            .await,
+        Err(AuthorRepositoryError::RevisionMismatch(_, 2))
+    ));
+    assert!(matches!(
+        authors.delete("ana.s", stale).await,
+        Err(AuthorRepositoryError::RevisionMismatch(_, 2))
+    ));
+
+    authors
+        .delete("ana.s", Precondition::at(vec![2]))
+        .await
+        .unwrap();
+    assert!(matches!(
+        authors.delete("ana.s", Precondition::at(vec![2])).await,
+        Err(AuthorRepositoryError::AuthorNotFound(_))
+    ));
+}
+
+async fn author_delete_in_use(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana"]).await;
+    let live = posts
+        .create(authored_post("live", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+    let trashed = posts
+        .create(authored_post("trashed", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+    posts.delete(&trashed.id, Precondition::ANY).await.unwrap();
+
+    assert!(matches!(
+        authors.delete("ana", Precondition::ANY).await,
+        Err(AuthorRepositoryError::AuthorInUse(name, 2)) if name == "ana"
+    ));
+
+    posts.purge(&trashed.id, Precondition::ANY).await.unwrap();
+    posts.delete(&live.id, Precondition::ANY).await.unwrap();
+    posts.purge(&live.id, Precondition::ANY).await.unwrap();
+
+    authors.delete("ana", Precondition::ANY).await.unwrap();
+}
+
+async fn author_sorting(
+    _: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["bruno", "ana", "carla"]).await;
+
+    let find = |sort: Option<&str>, cursor: Option<String>| {
+        authors.find_all(FindAllOptions {
+            limit: 2,
+            sort: sort.map(str::to_string),
+            cursor,
+            ..Default::default()
+        })
+    };
+    let names = |page: &FindAuthorsResponse| {
+        page.authors
+            .iter()
+            .map(|author| author.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let first = find(None, None).await.unwrap();
+    assert_eq!(first.total, 3);
+    assert_eq!(names(&first), vec!["ana", "bruno"]);
+
+    let second = find(None, first.next_cursor).await.unwrap();
+    assert_eq!(names(&second), vec!["carla"]);
+    assert_eq!(second.next_cursor, None);
+
+    let descending = find(Some("-name"), None).await.unwrap();
+    assert_eq!(names(&descending), vec!["carla", "bruno"]);
+
+    assert!(matches!(
+        find(Some("id"), None).await,
+        Err(AuthorRepositoryError::InvalidSort(sort)) if sort == "id"
+    ));
+}
+
+async fn post_author(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana", "bruno"]).await;
+
+    let created = posts
+        .create(authored_post("hello", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(created.author.as_deref(), Some("ana"));
+    assert_eq!(
+        posts.get(&created.id).await.unwrap().author.as_deref(),
+        Some("ana")
+    );
+
+    let kept = posts
+        .update(
+            &created.id,
+            new_post("hello again", &[]),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    assert_eq!(kept.author.as_deref(), Some("ana"));
+
+    let reassigned = posts
+        .patch(
+            &created.id,
+            PostPatch {
+                author: Some("bruno".to_string()),
+                ..Default::default()
+            },
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+    assert_eq!(reassigned.author.as_deref(), Some("bruno"));
+
+    posts.delete(&created.id, Precondition::ANY).await.unwrap();
+    let trash = posts.find_deleted(FindAllOptions::default()).await.unwrap();
+    assert_eq!(trash.posts[0].post.author.as_deref(), Some("bruno"));
+
+    let anonymous = posts
+        .create(new_post("anonymous", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(anonymous.author, None);
+}
+
+async fn post_rejects_unknown_authors(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana"]).await;
+
+    let created = posts
+        .create(authored_post("hello", "bruno"), SavePostOptions::default())
+        .await;
+    assert!(matches!(
+        created,
+        Err(PostRepositoryError::InvalidPost(violations))
+            if violation_codes(&violations) == vec!["author_not_found"]
+    ));
+
+    let post = posts
+        .create(authored_post("hello", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+    let updated = posts
+        .update(
+            &post.id,
+            authored_post("hello", "bruno"),
+            SavePostOptions::default(),
+            Precondition::ANY,
+        )
+        .await;
+    assert!(matches!(
+        updated,
+        Err(PostRepositoryError::InvalidPost(violations))
+            if violation_codes(&violations) == vec!["author_not_found"]
+    ));
+    assert_eq!(
+        posts.get(&post.id).await.unwrap().author.as_deref(),
+        Some("ana")
+    );
+}
+
+async fn post_writes_check_owner(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana", "bruno"]).await;
+    let owned_by = |author: &str| Precondition::ANY.owned_by(Some(author.to_string()));
+
+    let post = posts
+        .create(authored_post("hello", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+    let anonymous = posts
+        .create(new_post("anonymous", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let updated = posts
+        .update(
+            &post.id,
+            authored_post("hello again", "ana"),
+            SavePostOptions::default(),
+            owned_by("bruno"),
+        )
+        .await;
+    assert!(matches!(updated, Err(PostRepositoryError::PostNotOwned(id)) if id == post.id));
+    let patched = posts
+        .patch(
+            &anonymous.id,
+            PostPatch::default(),
+            SavePostOptions::default(),
+            owned_by("bruno"),
+        )
+        .await;
+    assert!(matches!(patched, Err(PostRepositoryError::PostNotOwned(_))));
+    assert!(matches!(
+        posts
+            .remove_tags(&post.id, vec!["rust".to_string()], owned_by("bruno"))
+            .await,
+        Err(PostRepositoryError::PostNotOwned(_))
+    ));
+    assert!(matches!(
+        posts.delete(&post.id, owned_by("bruno")).await,
+        Err(PostRepositoryError::PostNotOwned(_))
+    ));
+    assert!(matches!(
+        posts.delete("missing", owned_by("bruno")).await,
+        Err(PostRepositoryError::PostNotFound(_))
+    ));
+    assert_eq!(posts.get(&post.id).await.unwrap().revision, post.revision);
+
+    let updated = posts
+        .update(
+            &post.id,
+            authored_post("hello again", "ana"),
+            SavePostOptions::default(),
+            owned_by("ana"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.title, "hello again");
+    posts.delete(&post.id, owned_by("ana")).await.unwrap();
+
+    assert!(matches!(
+        posts.restore_deleted(&post.id, owned_by("bruno")).await,
+        Err(PostRepositoryError::PostNotOwned(_))
+    ));
+    assert!(matches!(
+        posts.purge(&post.id, owned_by("bruno")).await,
+        Err(PostRepositoryError::PostNotOwned(_))
+    ));
+    posts
+        .restore_deleted(&post.id, owned_by("ana"))
+        .await
+        .unwrap();
+    posts.delete(&post.id, Precondition::ANY).await.unwrap();
+    posts.purge(&post.id, owned_by("ana")).await.unwrap();
+}
+
+async fn post_filter_by_author(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana", "bruno"]).await;
+    for (title, author) in [("one", "ana"), ("two", "bruno"), ("three", "ana")] {
+        posts
+            .create(authored_post(title, author), SavePostOptions::default())
+            .await
+            .unwrap();
+    }
+    posts
+        .create(new_post("anonymous", &[]), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    let find = |author: &str| {
+        posts.find_all(
+            FindAllOptions::default(),
+            PostsFilter {
+                author: Some(author.to_string()),
+                ..Default::default()
+            },
+        )
+    };
+
+    let by_ana = find("ana").await.unwrap();
+    assert_eq!(by_ana.total, 2);
+    assert!(by_ana
+        .posts
+        .iter()
+        .all(|post| post.author.as_deref() == Some("ana")));
+
+    assert_eq!(find("bruno").await.unwrap().total, 1);
+    assert_eq!(find("carla").await.unwrap().total, 0);
+}
+
+async fn post_reflects_renamed_authors(
+    posts: &impl PostRepository,
+    _: &impl TagRepository,
+    authors: &impl AuthorRepository,
+) {
+    create_authors(authors, &["ana"]).await;
+    let created = posts
+        .create(authored_post("hello", "ana"), SavePostOptions::default())
+        .await
+        .unwrap();
+
+    authors
+        .update("ana", new_author("ana.s"), Precondition::ANY)
+        .await
+        .unwrap();
+
+    let fetched = posts.get(&created.id).await.unwrap();
+    assert_eq!(fetched.author.as_deref(), Some("ana.s"));
+    assert_eq!(fetched.revision, 2);
+    assert!(fetched.updated_at > created.updated_at);
+    assert_eq!(
+        posts
+            .revisions(&created.id, FindAllOptions::default())
+            .await
+            .unwrap()
+            .total,
+        2
+    );
+}
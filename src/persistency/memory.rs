@@ -0,0 +1,77 @@
+use super::{
+    api_keys::models::MemoryApiKeyEntity, authors::models::MemoryAuthorEntity,
+    posts::models::MemoryPostEntity, tags::models::MemoryTagEntity,
+};
+use crate::models::PostRevision;
+use chrono::Utc;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::RwLock;
+
+pub const MEMORY_DB_ADDRESS: &str = "memory://";
+
+#[derive(Debug, Default)]
+pub struct MemoryTables {
+    pub posts: HashMap<String, MemoryPostEntity>,
+    pub tags: HashMap<String, MemoryTagEntity>,
+    pub authors: HashMap<String, MemoryAuthorEntity>,
+    pub api_keys: HashMap<String, MemoryApiKeyEntity>,
+    /// Revisions of each post, by post id, oldest first.
+    pub post_revisions: HashMap<String, Vec<PostRevision>>,
+    /// Id of the post each former slug redirects to, by slug.
+    pub post_slug_redirects: HashMap<String, String>,
+}
+
+impl MemoryTables {
+    /// Gives every post matching `touched` a new revision, for writes to tags or authors that
+    /// change how posts read without writing the posts themselves.
+    pub fn touch_posts(&mut self, touched: impl Fn(&MemoryPostEntity) -> bool) {
+        let now = Utc::now();
+
+        for post in self.posts.values_mut().filter(|post| touched(post)) {
+            post.updated_at = now;
+            post.revision += 1;
+
+            let mut tags = post
+                .tag_ids
+                .iter()
+                .filter_map(|id| self.tags.get(id))
+                .map(|tag| tag.name.clone())
+                .collect::<Vec<_>>();
+            tags.sort();
+            self.post_revisions
+                .entry(post.id.clone())
+                .or_default()
+                .push(PostRevision {
+                    revision: post.revision,
+                    title: post.title.clone(),
+                    content: post.content.clone(),
+                    tags,
+                    created_at: now,
+                });
+        }
+    }
+}
+
+/// Shared, process-local storage backing the in-memory repositories. Cloning it yields another
+/// handle to the same tables, the same way cloning a `Surreal` handle shares its connection.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+    tables: Arc<RwLock<MemoryTables>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl MemoryDatabase {
+    pub fn tables(&self) -> &RwLock<MemoryTables> {
+        &self.tables
+    }
+
+    pub fn generate_id(&self) -> String {
+        (self.next_id.fetch_add(1, Ordering::Relaxed) + 1).to_string()
+    }
+}
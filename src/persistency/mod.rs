@@ -1,3 +1,8 @@
+pub mod api_keys;
+pub mod authors;
+#[cfg(test)]
+mod conformance;
+pub mod memory;
 pub mod models;
 pub mod posts;
 pub mod tags;
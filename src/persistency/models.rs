@@ -1,19 +1,246 @@
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{cmp::Ordering, fmt};
+
+/// Largest page a listing returns, whatever `limit` asks for.
+pub const MAX_LIMIT: usize = 100;
 
 fn default_limit() -> usize {
     10
 }
+/// Reads `limit`, capped at `MAX_LIMIT`.
+fn capped_limit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    usize::deserialize(deserializer).map(|limit| limit.min(MAX_LIMIT))
+}
 fn default_offset() -> usize {
     0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FindAllOptions {
-    #[serde(default = "default_limit")]
+    #[serde(default = "default_limit", deserialize_with = "capped_limit")]
     pub limit: usize,
 
     #[serde(default = "default_offset")]
     pub offset: usize,
+
+    /// Field to order by, prefixed with `-` for descending order, e.g. `-created_at`. Each
+    /// repository validates it against the fields it supports.
+    #[serde(default)]
+    pub sort: Option<String>,
+
+    /// Opaque `next_cursor` of a previous page. When given, the listing resumes right after the
+    /// item the cursor points at and `offset` is ignored.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl Default for FindAllOptions {
+    fn default() -> Self {
+        Self {
+            limit: default_limit(),
+            offset: default_offset(),
+            sort: None,
+            cursor: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn as_surql(&self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "ASC",
+            SortDirection::Descending => "DESC",
+        }
+    }
+
+    pub fn sort_prefix(&self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "",
+            SortDirection::Descending => "-",
+        }
+    }
+
+    /// Comparison operator selecting sort keys that come after a cursor in this direction.
+    pub fn keyset_operator(&self) -> &'static str {
+        match self {
+            SortDirection::Ascending => ">",
+            SortDirection::Descending => "<",
+        }
+    }
+
+    /// Orders items by sort key in this direction, breaking ties by ascending id.
+    pub fn compare(&self, a: (&SortKey, &str), b: (&SortKey, &str)) -> Ordering {
+        let ordering = match self {
+            SortDirection::Ascending => a.0.cmp(b.0),
+            SortDirection::Descending => b.0.cmp(a.0),
+        };
+
+        ordering.then_with(|| a.1.cmp(b.1))
+    }
+}
+
+/// Value of the field a listing is ordered by.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    Datetime(DateTime<Utc>),
+    Text(String),
+    Count(usize),
+}
+
+impl SortKey {
+    pub fn to_cursor_key(&self) -> String {
+        match self {
+            SortKey::Datetime(datetime) => datetime.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            SortKey::Text(text) => text.clone(),
+            SortKey::Count(count) => count.to_string(),
+        }
+    }
+
+    pub fn parse_datetime(key: &str) -> Option<SortKey> {
+        DateTime::parse_from_rfc3339(key)
+            .ok()
+            .map(|datetime| SortKey::Datetime(datetime.with_timezone(&Utc)))
+    }
+}
+
+/// Position right after the last item of a page, handed to clients as an opaque string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// `sort` option the page was ordered by, cursors are only valid for that same order.
+    pub sort: String,
+    /// Sort key of the last item of the page.
+    pub key: String,
+    /// Id of the last item of the page, breaking ties between equal sort keys.
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Trims a page fetched with one extra item back to `limit` items, returning the encoded cursor
+/// of its last item when that extra item shows more follow.
+pub fn next_page_cursor<T>(
+    items: &mut Vec<T>,
+    limit: usize,
+    cursor: impl Fn(&T) -> Cursor,
+) -> Option<String> {
+    if items.len() <= limit {
+        return None;
+    }
+
+    items.truncate(limit);
+    items.last().map(|item| cursor(item).encode())
+}
+
+/// Splits a `sort` option into its field name and direction: `title` sorts ascending and
+/// `-title` descending.
+pub fn parse_sort(sort: &str) -> (&str, SortDirection) {
+    match sort.strip_prefix('-') {
+        Some(field) => (field, SortDirection::Descending),
+        None => (sort, SortDirection::Ascending),
+    }
+}
+
+/// One reason a write was rejected, reported to clients as
+/// `{ "field": "title", "code": "title_empty", "message": "Title must not be empty" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(field: &'static str, code: &'static str, message: String) -> Self {
+        Self {
+            field,
+            code,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Joins violations into a single human readable message.
+pub fn describe_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Conditions a write only applies under: the revisions it may apply to, usually from an
+/// `If-Match` header, and for posts the author they must have. Repositories check it in the same
+/// statement as the write, so a concurrent write in between fails the check instead of being
+/// overwritten.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Precondition {
+    revisions: Option<Vec<u64>>,
+    owner: Option<String>,
+}
+
+impl Precondition {
+    /// Allows writes at any revision, to posts by any author.
+    pub const ANY: Precondition = Precondition {
+        revisions: None,
+        owner: None,
+    };
+
+    /// Allows writes at one of `revisions` only.
+    pub fn at(revisions: Vec<u64>) -> Self {
+        Self {
+            revisions: Some(revisions),
+            owner: None,
+        }
+    }
+
+    /// Also allows writes to posts by `owner` only, when it is set.
+    pub fn owned_by(self, owner: Option<String>) -> Self {
+        Self { owner, ..self }
+    }
+
+    pub fn allows(&self, revision: u64) -> bool {
+        self.revisions
+            .as_ref()
+            .is_none_or(|revisions| revisions.contains(&revision))
+    }
+
+    pub fn allows_owner(&self, author: Option<&str>) -> bool {
+        self.owner.is_none() || self.owner.as_deref() == author
+    }
+
+    /// Revisions to bind as `$revisions` in queries, where `NONE` allows any.
+    pub fn revisions(&self) -> Option<&[u64]> {
+        self.revisions.as_deref()
+    }
+
+    /// Author to bind as `$owner` in queries, where `NONE` allows any.
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,3 +258,64 @@ impl Default for &SurrealCountRecord {
         &SurrealCountRecord { count: 0 }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_is_capped() {
+        let parse = |query: &str| {
+            actix_web::web::Query::<FindAllOptions>::from_query(query)
+                .unwrap()
+                .limit
+        };
+
+        assert_eq!(parse(""), 10);
+        assert_eq!(parse("limit=20"), 20);
+        assert_eq!(parse(&format!("limit={}", usize::MAX)), MAX_LIMIT);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            sort: "-created_at".to_string(),
+            key: "2024-02-03T20:31:00.000000000Z".to_string(),
+            id: "abc".to_string(),
+        };
+
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("not a cursor"), None);
+    }
+
+    #[test]
+    fn test_datetime_sort_key_round_trip() {
+        let key = SortKey::Datetime(Utc::now());
+
+        assert_eq!(SortKey::parse_datetime(&key.to_cursor_key()), Some(key));
+        assert_eq!(SortKey::parse_datetime("yesterday"), None);
+    }
+
+    #[test]
+    fn test_compare_breaks_ties_by_id() {
+        let (low, high) = (SortKey::Count(1), SortKey::Count(2));
+
+        assert_eq!(
+            SortDirection::Descending.compare((&high, "b"), (&low, "a")),
+            Ordering::Less
+        );
+        assert_eq!(
+            SortDirection::Descending.compare((&low, "a"), (&low, "b")),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(parse_sort("title"), ("title", SortDirection::Ascending));
+        assert_eq!(
+            parse_sort("-created_at"),
+            ("created_at", SortDirection::Descending)
+        );
+    }
+}
@@ -1,3 +1,11 @@
+use crate::{
+    models::{Post, PostStatus},
+    persistency::{
+        authors::errors::AuthorRepositoryError,
+        models::{describe_violations, Precondition, Violation},
+        tags::errors::TagRepositoryError,
+    },
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,21 +13,93 @@ pub enum PostRepositoryError {
     #[error("Database query failed: {0}")]
     Database(#[from] Box<dyn std::error::Error>),
 
+    #[error("Failed to resolve post tags: {0}")]
+    TagLookup(#[from] TagRepositoryError),
+
+    #[error("Failed to resolve post author: {0}")]
+    AuthorLookup(#[from] AuthorRepositoryError),
+
     #[error("Failed to create post in the database")]
     PostCreation,
 
-    #[error("Tags not found: {0:?}")]
-    TagsNotFound(Vec<String>),
+    #[error("Invalid post: {}", describe_violations(.0))]
+    InvalidPost(Vec<Violation>),
 
     #[error("Failed to list posts from the database")]
     PostListing,
 
+    #[error("Unsupported sort order: {0}")]
+    InvalidSort(String),
+
+    #[error("Invalid or expired cursor: {0}")]
+    InvalidCursor(String),
+
     #[error("Failed to count posts in the database")]
     PostCount,
 
+    #[error("Failed to search posts in the database")]
+    PostSearch,
+
+    #[error("Search query must contain at least one word")]
+    EmptySearchQuery,
+
     #[error("Failed to fetch post from the database")]
     PostGet,
 
+    #[error("Post not found: {0}")]
+    PostNotFound(String),
+
+    #[error("Listing {0} posts needs the posts:write scope")]
+    StatusNotAllowed(PostStatus),
+
+    #[error("Post {0} belongs to another author")]
+    PostNotOwned(String),
+
+    #[error("Writing posts needs an author to act as, or the admin scope")]
+    AuthorRequired,
+
+    #[error("Cannot write posts as author {0}")]
+    AuthorNotAllowed(String),
+
+    #[error("Slug already used by another post: {0}")]
+    SlugAlreadyExists(String),
+
+    #[error("Post {0} was modified concurrently, it is now at revision {1}")]
+    RevisionMismatch(String, u64),
+
+    #[error("Revision {1} of post {0} not found")]
+    RevisionNotFound(String, u64),
+
+    #[error("Cannot diff revision {1} against revision {0}, which is not an earlier one")]
+    InvalidRevisionRange(u64, u64),
+
+    #[error("Failed to fetch post revisions from the database")]
+    RevisionListing,
+
     #[error("Failed to update post in the database")]
     PostUpdate,
+
+    #[error("Failed to delete post from the database")]
+    PostDeletion,
+
+    #[error("Failed to purge posts from the database")]
+    PostPurge,
+
+    #[error("Failed to publish scheduled posts in the database")]
+    PostPublish,
+
+    #[error("Failed to repair tag relations in the database")]
+    RelationRepair,
+}
+
+impl PostRepositoryError {
+    /// Error for a write to `post` that `precondition` does not allow, its author taking
+    /// precedence over its revision.
+    pub fn unmet_precondition(precondition: &Precondition, post: &Post) -> Self {
+        if precondition.allows_owner(post.author.as_deref()) {
+            Self::RevisionMismatch(post.id.clone(), post.revision)
+        } else {
+            Self::PostNotOwned(post.id.clone())
+        }
+    }
 }
@@ -0,0 +1,877 @@
+use super::{
+    errors::PostRepositoryError,
+    models::{
+        DeletedPost, FindDeletedPostsResponse, FindPostRevisionsResponse, FindPostsResponse,
+        MemoryPostEntity, NewPost, PostHighlights, PostPatch, PostSearchHit, PostSort,
+        PostTagRelation, PostsFilter, SavePostOptions, SearchPostsResponse, SlugLookup, SlugOwner,
+    },
+    utils::{create_snippet, highlight_terms, pick_slug, search_terms, slug_candidate},
+    validation::{validate_new_post, ResolvedTags},
+};
+use crate::{
+    models::{Post, PostRevision, PostStatus, Tag},
+    persistency::{
+        memory::{MemoryDatabase, MemoryTables},
+        models::{next_page_cursor, FindAllOptions, Precondition},
+        tags::models::MemoryTagEntity,
+        traits::{AuthorRepository, PostRepository, TagRepository},
+    },
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tracing::info;
+
+#[derive(Clone)]
+pub struct MemoryPostsRepository<TR: TagRepository, AR: AuthorRepository> {
+    db: MemoryDatabase,
+    tags_repository: TR,
+    authors_repository: AR,
+}
+
+impl<TR: TagRepository, AR: AuthorRepository> MemoryPostsRepository<TR, AR> {
+    pub fn new(db: MemoryDatabase, tags_repository: TR, authors_repository: AR) -> Self {
+        Self {
+            db,
+            tags_repository,
+            authors_repository,
+        }
+    }
+}
+
+impl<TR: TagRepository, AR: AuthorRepository> MemoryPostsRepository<TR, AR> {
+    /// Post `id`, unless it does not exist or is in the trash.
+    fn live_post<'a>(
+        posts: &'a HashMap<String, MemoryPostEntity>,
+        id: &str,
+    ) -> Result<&'a MemoryPostEntity, PostRepositoryError> {
+        posts
+            .get(id)
+            .filter(|post| post.deleted_at.is_none())
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))
+    }
+
+    fn live_post_mut<'a>(
+        posts: &'a mut HashMap<String, MemoryPostEntity>,
+        id: &str,
+    ) -> Result<&'a mut MemoryPostEntity, PostRepositoryError> {
+        posts
+            .get_mut(id)
+            .filter(|post| post.deleted_at.is_none())
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))
+    }
+
+    /// Post `id`, unless it does not exist or is not in the trash.
+    fn deleted_post<'a>(
+        posts: &'a HashMap<String, MemoryPostEntity>,
+        id: &str,
+    ) -> Result<&'a MemoryPostEntity, PostRepositoryError> {
+        posts
+            .get(id)
+            .filter(|post| post.deleted_at.is_some())
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))
+    }
+
+    fn deleted_post_mut<'a>(
+        posts: &'a mut HashMap<String, MemoryPostEntity>,
+        id: &str,
+    ) -> Result<&'a mut MemoryPostEntity, PostRepositoryError> {
+        posts
+            .get_mut(id)
+            .filter(|post| post.deleted_at.is_some())
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))
+    }
+
+    /// Fails unless `precondition` allows writing `post`.
+    fn check_precondition(
+        tables: &MemoryTables,
+        post: &MemoryPostEntity,
+        precondition: &Precondition,
+    ) -> Result<(), PostRepositoryError> {
+        let author = post
+            .author_id
+            .as_ref()
+            .and_then(|id| tables.authors.get(id))
+            .map(|author| author.name.as_str());
+
+        match precondition.allows_owner(author) && precondition.allows(post.revision) {
+            true => Ok(()),
+            false => Err(PostRepositoryError::unmet_precondition(
+                precondition,
+                &Self::resolve_post(tables, post),
+            )),
+        }
+    }
+
+    /// `post` along with its tags and the name of its author.
+    fn resolve_post(tables: &MemoryTables, post: &MemoryPostEntity) -> Post {
+        let tags = post
+            .tag_ids
+            .iter()
+            .filter_map(|id| tables.tags.get(id))
+            .cloned()
+            .map(|tag| tag.into())
+            .collect::<Vec<Tag>>();
+        let author = post
+            .author_id
+            .as_ref()
+            .and_then(|id| tables.authors.get(id))
+            .map(|author| author.name.clone());
+
+        Post::from((post.clone(), tags, author))
+    }
+
+    /// Tags of posts, trashed ones included, that point at tags which no longer exist.
+    fn dangling_relations(tables: &MemoryTables) -> Vec<PostTagRelation> {
+        let mut dangling = tables
+            .posts
+            .values()
+            .flat_map(|post| {
+                post.tag_ids
+                    .iter()
+                    .filter(|tag_id| !tables.tags.contains_key(*tag_id))
+                    .map(|tag_id| PostTagRelation {
+                        post: post.id.clone(),
+                        tag: tag_id.clone(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        dangling.sort_by(|a, b| (&a.post, &a.tag).cmp(&(&b.post, &b.tag)));
+
+        dangling
+    }
+
+    /// Slug to save `new_post` with, as post `post_id` when updating it.
+    fn slug_for(
+        tables: &MemoryTables,
+        new_post: &NewPost,
+        post_id: Option<&str>,
+    ) -> Result<String, PostRepositoryError> {
+        let owners = tables
+            .posts
+            .values()
+            .map(|post| (&post.slug, &post.id))
+            .chain(&tables.post_slug_redirects)
+            .map(|(slug, post)| SlugOwner {
+                slug: slug.clone(),
+                post: post.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        pick_slug(
+            slug_candidate(new_post),
+            new_post.slug.is_some(),
+            &owners,
+            post_id,
+        )
+    }
+
+    /// Moves post `id` to `slug`, redirecting its current slug there.
+    fn rename_slug(tables: &mut MemoryTables, id: &str, slug: String) {
+        let Some(post) = tables.posts.get_mut(id) else {
+            return;
+        };
+        if post.slug == slug {
+            return;
+        }
+
+        tables.post_slug_redirects.remove(&slug);
+        let previous = std::mem::replace(&mut post.slug, slug);
+        tables.post_slug_redirects.insert(previous, id.to_string());
+    }
+
+    /// Keeps a snapshot of `post` as of its current revision.
+    fn record_revision(tables: &mut MemoryTables, post: &Post) {
+        tables
+            .post_revisions
+            .entry(post.id.clone())
+            .or_default()
+            .push(post.into());
+    }
+
+    /// Creates the missing tags of a validated post, reusing any created concurrently since it was
+    /// validated, and returns every tag of the post.
+    fn upsert_tags(&self, tables: &mut MemoryTables, tags: ResolvedTags) -> Vec<Tag> {
+        let mut resolved = tags.existing;
+
+        for name in tags.missing {
+            let tag = match tables.tags.values().find(|tag| tag.name == name) {
+                Some(tag) => tag.clone(),
+                None => {
+                    let tag = MemoryTagEntity {
+                        id: self.db.generate_id(),
+                        name,
+                        revision: 1,
+                    };
+                    tables.tags.insert(tag.id.clone(), tag.clone());
+
+                    tag
+                }
+            };
+
+            resolved.push(tag.into());
+        }
+
+        resolved
+    }
+
+    /// Validates `post` once patched, then writes the patch, adding and removing only the changed
+    /// tags.
+    async fn apply_patch(
+        &self,
+        post: Post,
+        patch: PostPatch,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let patched = patch.apply(&post);
+        let resolved = validate_new_post(
+            &patched,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+        let tag_changes = patch.tag_changes(&post);
+
+        let mut guard = self.db.tables().write().await;
+        let tables = &mut *guard;
+
+        Self::check_precondition(
+            tables,
+            Self::live_post(&tables.posts, &post.id)?,
+            &precondition,
+        )?;
+        if patch.slug.is_some() {
+            let slug = Self::slug_for(tables, &patched, Some(&post.id))?;
+            Self::rename_slug(tables, &post.id, slug);
+        }
+        let added_ids = self
+            .upsert_tags(tables, resolved.tags)
+            .into_iter()
+            .filter(|tag| tag_changes.added.contains(&tag.name))
+            .map(|tag| tag.id)
+            .collect::<Vec<_>>();
+
+        let entity = Self::live_post_mut(&mut tables.posts, &post.id)?;
+        if let Some(title) = patch.title {
+            entity.title = title;
+        }
+        if let Some(content) = patch.content {
+            entity.content = content;
+        }
+        if let Some(author) = resolved.author {
+            entity.author_id = Some(author.id);
+        }
+        let now = Utc::now();
+        entity.set_status(patch.status, patch.publish_at, now);
+        entity.tag_ids.retain(|id| {
+            tables
+                .tags
+                .get(id)
+                .is_none_or(|tag| !tag_changes.removed.contains(&tag.name))
+        });
+        for id in added_ids {
+            if !entity.tag_ids.contains(&id) {
+                entity.tag_ids.push(id);
+            }
+        }
+        entity.updated_at = now;
+        entity.revision += 1;
+
+        info!("Patched post {}: {entity:?}", post.id);
+
+        let entity = entity.clone();
+        let post = Self::resolve_post(tables, &entity);
+        Self::record_revision(tables, &post);
+
+        Ok(post)
+    }
+}
+
+impl<TR: TagRepository, AR: AuthorRepository> PostRepository for MemoryPostsRepository<TR, AR> {
+    async fn create(
+        &self,
+        new_post: NewPost,
+        options: SavePostOptions,
+    ) -> Result<Post, PostRepositoryError> {
+        let resolved = validate_new_post(
+            &new_post,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+
+        let mut tables = self.db.tables().write().await;
+        let slug = Self::slug_for(&tables, &new_post, None)?;
+        let tags = self.upsert_tags(&mut tables, resolved.tags);
+
+        let now = Utc::now();
+        let mut post = MemoryPostEntity {
+            id: self.db.generate_id(),
+            slug,
+            title: new_post.title,
+            content: new_post.content,
+            tag_ids: tags.iter().map(|tag| tag.id.clone()).collect(),
+            author_id: resolved.author.map(|author| author.id),
+            status: PostStatus::default(),
+            publish_at: None,
+            published_at: None,
+            created_at: now,
+            updated_at: now,
+            revision: 1,
+            deleted_at: None,
+        };
+        post.set_status(new_post.status, new_post.publish_at, now);
+
+        tables.posts.insert(post.id.clone(), post.clone());
+
+        info!("Created post: {post:?}");
+
+        let post = Self::resolve_post(&tables, &post);
+        Self::record_revision(&mut tables, &post);
+
+        Ok(post)
+    }
+
+    async fn find_all(
+        &self,
+        options: FindAllOptions,
+        filter: PostsFilter,
+    ) -> Result<FindPostsResponse, PostRepositoryError> {
+        let sort = PostSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+        let tables = self.db.tables().read().await;
+
+        let mut posts = tables
+            .posts
+            .values()
+            .filter(|post| post.deleted_at.is_none())
+            .map(|post| Self::resolve_post(&tables, post))
+            .filter(|post| {
+                let tag_names = post
+                    .tags
+                    .iter()
+                    .map(|tag| tag.name.as_str())
+                    .collect::<Vec<_>>();
+
+                filter.matches(post.status, post.author.as_deref(), &tag_names)
+            })
+            .collect::<Vec<_>>();
+        posts.sort_by(|a, b| {
+            sort.direction
+                .compare((&sort.field.key(a), &a.id), (&sort.field.key(b), &b.id))
+        });
+
+        let total = posts.len();
+        let offset = if cursor.is_some() { 0 } else { options.offset };
+        let mut posts = posts
+            .into_iter()
+            .filter(|post| match &cursor {
+                Some((key, id)) => sort
+                    .direction
+                    .compare((&sort.field.key(post), &post.id), (key, id))
+                    .is_gt(),
+                None => true,
+            })
+            .skip(offset)
+            .take(options.limit.saturating_add(1))
+            .collect();
+        let next_cursor = next_page_cursor(&mut posts, options.limit, |post| sort.cursor(post));
+
+        Ok(FindPostsResponse {
+            posts,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: FindAllOptions,
+    ) -> Result<SearchPostsResponse, PostRepositoryError> {
+        let terms = search_terms(query);
+        if terms.is_empty() {
+            return Err(PostRepositoryError::EmptySearchQuery);
+        }
+
+        let tables = self.db.tables().read().await;
+
+        let contains_all_terms = |text: &str| {
+            let words = search_terms(text);
+            terms.iter().all(|term| words.contains(term))
+        };
+
+        let mut hits = tables
+            .posts
+            .values()
+            .filter(|post| post.deleted_at.is_none() && post.status == PostStatus::Published)
+            .filter(|post| contains_all_terms(&post.title) || contains_all_terms(&post.content))
+            .map(|post| {
+                let (title, title_matches) = highlight_terms(&post.title, &terms);
+                let (content, content_matches) = highlight_terms(&post.content, &terms);
+
+                PostSearchHit {
+                    post: Self::resolve_post(&tables, post),
+                    score: (2 * title_matches + content_matches) as f32,
+                    highlights: PostHighlights {
+                        title,
+                        content: create_snippet(&content),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        let total = hits.len();
+        let posts = hits
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .collect();
+
+        Ok(SearchPostsResponse { posts, total })
+    }
+
+    async fn get(&self, id: &str) -> Result<Post, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let post = Self::live_post(&tables.posts, id)?;
+
+        Ok(Self::resolve_post(&tables, post))
+    }
+
+    async fn get_by_slug(&self, slug: &str) -> Result<SlugLookup, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let live_posts = || {
+            tables
+                .posts
+                .values()
+                .filter(|post| post.deleted_at.is_none())
+        };
+        if let Some(post) = live_posts().find(|post| post.slug == slug) {
+            return Ok(SlugLookup::Post(Self::resolve_post(&tables, post)));
+        }
+
+        tables
+            .post_slug_redirects
+            .get(slug)
+            .and_then(|id| live_posts().find(|post| &post.id == id))
+            .map(|post| SlugLookup::Redirect(post.slug.clone()))
+            .ok_or_else(|| PostRepositoryError::PostNotFound(slug.to_string()))
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        new_post: NewPost,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let resolved = validate_new_post(
+            &new_post,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+
+        let mut tables = self.db.tables().write().await;
+
+        Self::check_precondition(&tables, Self::live_post(&tables.posts, id)?, &precondition)?;
+        if new_post.slug.is_some() {
+            let slug = Self::slug_for(&tables, &new_post, Some(id))?;
+            Self::rename_slug(&mut tables, id, slug);
+        }
+        let tags = self.upsert_tags(&mut tables, resolved.tags);
+
+        let post = Self::live_post_mut(&mut tables.posts, id)?;
+        post.title = new_post.title;
+        post.content = new_post.content;
+        post.tag_ids = tags.iter().map(|tag| tag.id.clone()).collect();
+        if let Some(author) = resolved.author {
+            post.author_id = Some(author.id);
+        }
+        let now = Utc::now();
+        post.set_status(new_post.status, new_post.publish_at, now);
+        post.updated_at = now;
+        post.revision += 1;
+
+        info!("Updated post {id}: {post:?}");
+
+        let post = post.clone();
+        let post = Self::resolve_post(&tables, &post);
+        Self::record_revision(&mut tables, &post);
+
+        Ok(post)
+    }
+
+    async fn patch(
+        &self,
+        id: &str,
+        patch: PostPatch,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+
+        self.apply_patch(post, patch, options, precondition).await
+    }
+
+    async fn add_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+        let patch = PostPatch::adding_tags(&post, tags);
+
+        self.apply_patch(post, patch, options, precondition).await
+    }
+
+    async fn remove_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+        let patch = PostPatch::removing_tags(&post, &tags);
+
+        self.apply_patch(post, patch, SavePostOptions::default(), precondition)
+            .await
+    }
+
+    async fn revisions(
+        &self,
+        id: &str,
+        options: FindAllOptions,
+    ) -> Result<FindPostRevisionsResponse, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        Self::live_post(&tables.posts, id)?;
+        let revisions = tables
+            .post_revisions
+            .get(id)
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))?;
+
+        Ok(FindPostRevisionsResponse {
+            revisions: revisions
+                .iter()
+                .rev()
+                .skip(options.offset)
+                .take(options.limit)
+                .cloned()
+                .collect(),
+            total: revisions.len(),
+        })
+    }
+
+    async fn get_revision(
+        &self,
+        id: &str,
+        revision: u64,
+    ) -> Result<PostRevision, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        Self::live_post(&tables.posts, id)?;
+        tables
+            .post_revisions
+            .get(id)
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))?
+            .iter()
+            .find(|candidate| candidate.revision == revision)
+            .cloned()
+            .ok_or_else(|| PostRepositoryError::RevisionNotFound(id.to_string(), revision))
+    }
+
+    async fn restore_revision(
+        &self,
+        id: &str,
+        revision: u64,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let revision = self.get_revision(id, revision).await?;
+
+        self.update(id, revision.into(), options, precondition)
+            .await
+    }
+
+    async fn delete(
+        &self,
+        id: &str,
+        precondition: Precondition,
+    ) -> Result<(), PostRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        Self::check_precondition(&tables, Self::live_post(&tables.posts, id)?, &precondition)?;
+        let post = Self::live_post_mut(&mut tables.posts, id)?;
+        let now = Utc::now();
+        post.deleted_at = Some(now);
+        post.updated_at = now;
+        post.revision += 1;
+
+        info!("Moved post {id} to the trash");
+
+        let post = post.clone();
+        let post = Self::resolve_post(&tables, &post);
+        Self::record_revision(&mut tables, &post);
+
+        Ok(())
+    }
+
+    async fn find_deleted(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindDeletedPostsResponse, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let mut posts = tables
+            .posts
+            .values()
+            .filter_map(|post| {
+                Some(DeletedPost {
+                    deleted_at: post.deleted_at?,
+                    post: Self::resolve_post(&tables, post),
+                })
+            })
+            .collect::<Vec<_>>();
+        posts.sort_by(|a, b| {
+            b.deleted_at
+                .cmp(&a.deleted_at)
+                .then_with(|| a.post.id.cmp(&b.post.id))
+        });
+
+        let total = posts.len();
+        let posts = posts
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .collect();
+
+        Ok(FindDeletedPostsResponse { posts, total })
+    }
+
+    async fn restore_deleted(
+        &self,
+        id: &str,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        Self::check_precondition(
+            &tables,
+            Self::deleted_post(&tables.posts, id)?,
+            &precondition,
+        )?;
+        let post = Self::deleted_post_mut(&mut tables.posts, id)?;
+        post.deleted_at = None;
+        post.updated_at = Utc::now();
+        post.revision += 1;
+
+        info!("Restored post {id} from the trash");
+
+        let post = post.clone();
+        let post = Self::resolve_post(&tables, &post);
+        Self::record_revision(&mut tables, &post);
+
+        Ok(post)
+    }
+
+    async fn purge(&self, id: &str, precondition: Precondition) -> Result<(), PostRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        Self::check_precondition(
+            &tables,
+            Self::deleted_post(&tables.posts, id)?,
+            &precondition,
+        )?;
+        tables.posts.remove(id);
+        tables.post_revisions.remove(id);
+        tables.post_slug_redirects.retain(|_, post| post != id);
+
+        info!("Purged post {id}");
+
+        Ok(())
+    }
+
+    async fn purge_deleted_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, PostRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        let expired = tables
+            .posts
+            .values()
+            .filter(|post| {
+                post.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < cutoff)
+            })
+            .map(|post| post.id.clone())
+            .collect::<Vec<_>>();
+        for id in &expired {
+            tables.posts.remove(id);
+            tables.post_revisions.remove(id);
+        }
+        tables
+            .post_slug_redirects
+            .retain(|_, post| !expired.contains(post));
+
+        info!("Purged posts deleted before {cutoff}: {expired:?}");
+
+        Ok(expired.len())
+    }
+
+    async fn publish_scheduled(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Post>, PostRepositoryError> {
+        let mut guard = self.db.tables().write().await;
+        let tables = &mut *guard;
+
+        let mut published = vec![];
+        for post in tables.posts.values_mut().filter(|post| {
+            post.deleted_at.is_none()
+                && post.status == PostStatus::Scheduled
+                && post.publish_at.is_some_and(|publish_at| publish_at <= now)
+        }) {
+            post.published_at = post.published_at.or(post.publish_at);
+            post.set_status(Some(PostStatus::Published), None, now);
+            post.updated_at = Utc::now();
+            post.revision += 1;
+
+            published.push(post.clone());
+        }
+        let published = published
+            .into_iter()
+            .map(|post| Self::resolve_post(tables, &post))
+            .collect::<Vec<_>>();
+        for post in &published {
+            Self::record_revision(tables, post);
+        }
+
+        if !published.is_empty() {
+            info!("Published posts scheduled until {now}: {published:?}");
+        }
+
+        Ok(published)
+    }
+
+    async fn find_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        Ok(Self::dangling_relations(&tables))
+    }
+
+    async fn remove_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        let dangling = Self::dangling_relations(&tables);
+        let MemoryTables { posts, tags, .. } = &mut *tables;
+        for post in posts.values_mut() {
+            post.tag_ids.retain(|tag_id| tags.contains_key(tag_id));
+        }
+
+        info!("Removed dangling tag relations: {dangling:?}");
+
+        Ok(dangling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistency::{
+        authors::memory_authors_repository::MemoryAuthorsRepository,
+        tags::{memory_tags_repository::MemoryTagsRepository, models::NewTag},
+    };
+
+    fn create_repository() -> (
+        MemoryPostsRepository<MemoryTagsRepository, MemoryAuthorsRepository>,
+        MemoryTagsRepository,
+    ) {
+        let db = MemoryDatabase::default();
+
+        (
+            MemoryPostsRepository::new(
+                db.clone(),
+                MemoryTagsRepository::new(db.clone()),
+                MemoryAuthorsRepository::new(db.clone()),
+            ),
+            MemoryTagsRepository::new(db),
+        )
+    }
+
+    fn new_post(title: &str, tags: &[&str]) -> NewPost {
+        NewPost {
+            slug: None,
+            title: title.to_string(),
+            content: "content".to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            author: None,
+            status: None,
+            publish_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_unknown_tags() {
+        let (posts, tags) = create_repository();
+        tags.create(NewTag {
+            name: "rust".to_string(),
+        })
+        .await
+        .unwrap();
+
+        let result = posts
+            .create(
+                new_post("title", &["rust", "zig"]),
+                SavePostOptions::default(),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(PostRepositoryError::InvalidPost(violations)) if violations.len() == 1 && violations[0].code == "tag_not_found"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_renamed_tags_are_reflected_in_posts() {
+        let (posts, tags) = create_repository();
+        tags.create(NewTag {
+            name: "rust".to_string(),
+        })
+        .await
+        .unwrap();
+
+        let post = posts
+            .create(new_post("title", &["rust"]), SavePostOptions::default())
+            .await
+            .unwrap();
+        tags.update(
+            "rust",
+            NewTag {
+                name: "rustlang".to_string(),
+            },
+            Precondition::ANY,
+        )
+        .await
+        .unwrap();
+
+        let post = posts.get(&post.id).await.unwrap();
+
+        assert_eq!(post.tags.len(), 1);
+        assert_eq!(post.tags[0].name, "rustlang");
+    }
+}
@@ -1,4 +1,6 @@
 pub mod errors;
+pub mod memory_posts_repository;
 pub mod models;
 pub mod surrealdb_posts_repository;
 pub mod utils;
+pub mod validation;
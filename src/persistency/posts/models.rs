@@ -1,41 +1,361 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt};
+use surrealdb::sql::Thing;
 
-use crate::models::{Post, Tag};
+use super::{errors::PostRepositoryError, utils::create_snippet};
+use crate::{
+    models::{Post, PostRevision, PostStatus, Tag},
+    persistency::models::{parse_sort, Cursor, SortDirection, SortKey},
+    utils::{
+        diff::line_diff,
+        patch::{clearable_member, required_member},
+        query::comma_separated,
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewPost {
+    /// Generated from the title of new posts unless given, and kept by updated ones.
+    #[serde(default)]
+    pub slug: Option<String>,
     pub title: String,
     pub content: String,
     pub tags: HashSet<String>,
+    /// Name of the author of the post. Updated posts keep theirs unless given one.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// New posts are published unless given a status, and updated ones keep theirs.
+    #[serde(default)]
+    pub status: Option<PostStatus>,
+    /// When to publish the post, only with the `scheduled` status.
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
+/// JSON Merge Patch (RFC 7396) of a post. Absent members are left untouched, `"tags": null`
+/// removes every tag, and `title`, `content` and `author` cannot be removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostPatch {
+    #[serde(default, deserialize_with = "required_member")]
+    pub slug: Option<String>,
+
+    #[serde(default, deserialize_with = "required_member")]
+    pub title: Option<String>,
+
+    #[serde(default, deserialize_with = "required_member")]
+    pub content: Option<String>,
+
+    #[serde(default, deserialize_with = "clearable_member")]
+    pub tags: Option<HashSet<String>>,
+
+    #[serde(default, deserialize_with = "required_member")]
+    pub author: Option<String>,
+
+    #[serde(default, deserialize_with = "required_member")]
+    pub status: Option<PostStatus>,
+
+    /// Reschedules a `scheduled` post, or schedules one along with `"status": "scheduled"`.
+    #[serde(default, deserialize_with = "required_member")]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+/// Tags a patch adds to and removes from a post, by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl PostPatch {
+    /// Patch tagging `post` with `tags` as well.
+    pub fn adding_tags(post: &Post, tags: Vec<String>) -> Self {
+        Self {
+            tags: Some(
+                post.tags
+                    .iter()
+                    .map(|tag| tag.name.clone())
+                    .chain(tags)
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Patch removing `tags` from `post`, ignoring the ones it is not tagged with.
+    pub fn removing_tags(post: &Post, tags: &[String]) -> Self {
+        Self {
+            tags: Some(
+                post.tags
+                    .iter()
+                    .map(|tag| tag.name.clone())
+                    .filter(|name| !tags.contains(name))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// `post` once patched, to be validated like any other post.
+    pub fn apply(&self, post: &Post) -> NewPost {
+        let status = self.status.unwrap_or(post.status);
+        let publish_at = match self.publish_at {
+            Some(publish_at) => Some(publish_at),
+            None if status == PostStatus::Scheduled => post.publish_at,
+            None => None,
+        };
+
+        NewPost {
+            slug: self.slug.clone(),
+            title: self.title.clone().unwrap_or_else(|| post.title.clone()),
+            content: self.content.clone().unwrap_or_else(|| post.content.clone()),
+            tags: self
+                .tags
+                .clone()
+                .unwrap_or_else(|| post.tags.iter().map(|tag| tag.name.clone()).collect()),
+            author: self.author.clone(),
+            status: Some(status),
+            publish_at,
+        }
+    }
+
+    /// Tags added and removed when patching `post`, sorted by name. Only these relations are
+    /// written, so concurrent changes to the post's other tags are kept.
+    pub fn tag_changes(&self, post: &Post) -> TagChanges {
+        let Some(tags) = &self.tags else {
+            return TagChanges::default();
+        };
+        let current = post
+            .tags
+            .iter()
+            .map(|tag| tag.name.clone())
+            .collect::<HashSet<_>>();
+
+        let mut added = tags.difference(&current).cloned().collect::<Vec<_>>();
+        let mut removed = current.difference(tags).cloned().collect::<Vec<_>>();
+        added.sort();
+        removed.sort();
+
+        TagChanges { added, removed }
+    }
+}
+
+/// Options for creating or updating a post.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavePostOptions {
+    /// Creates the post's tags that do not exist yet, along with the post, instead of rejecting
+    /// it.
+    #[serde(default)]
+    pub create_missing_tags: bool,
+}
+
+/// Per-request overrides of the server's `SavePostOptions`, e.g. `?create_missing_tags=true`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavePostQuery {
+    #[serde(default)]
+    pub create_missing_tags: Option<bool>,
+}
+
+impl SavePostQuery {
+    pub fn resolve(&self, defaults: SavePostOptions) -> SavePostOptions {
+        SavePostOptions {
+            create_missing_tags: self
+                .create_missing_tags
+                .unwrap_or(defaults.create_missing_tags),
+        }
+    }
+}
+
+/// Narrows post listings down by their status, author and tags. Every list is comma separated in
+/// the query string and empty lists are ignored, except for `status`, which defaults to published
+/// posts, e.g. `?status=draft,scheduled&tags_all=rust,release`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostsFilter {
+    /// Posts tagged with at least one of these tags.
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub tags_any: Vec<String>,
+
+    /// Posts tagged with every one of these tags.
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub tags_all: Vec<String>,
+
+    /// Posts tagged with none of these tags.
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub tags_none: Vec<String>,
+
+    /// Posts in one of these statuses, e.g. `?status=draft,scheduled`, see `statuses`.
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub status: Vec<PostStatus>,
+
+    /// Posts by the author with this name, e.g. `?author=jane`.
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+impl PostsFilter {
+    /// Statuses of the posts to list, only published ones unless `status` says otherwise.
+    pub fn statuses(&self) -> Vec<PostStatus> {
+        match self.status.is_empty() {
+            true => vec![PostStatus::Published],
+            false => self.status.clone(),
+        }
+    }
+
+    pub fn matches(&self, status: PostStatus, author: Option<&str>, tag_names: &[&str]) -> bool {
+        let has = |name: &String| tag_names.contains(&name.as_str());
+
+        (self.status.contains(&status) || self.status.is_empty() && status == PostStatus::Published)
+            && (self.author.is_none() || self.author.as_deref() == author)
+            && (self.tags_any.is_empty() || self.tags_any.iter().any(has))
+            && self.tags_all.iter().all(has)
+            && !self.tags_none.iter().any(has)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostSortField {
+    CreatedAt,
+    UpdatedAt,
+    Title,
+}
+
+impl PostSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostSortField::CreatedAt => "created_at",
+            PostSortField::UpdatedAt => "updated_at",
+            PostSortField::Title => "title",
+        }
+    }
+
+    pub fn key(&self, post: &Post) -> SortKey {
+        match self {
+            PostSortField::CreatedAt => SortKey::Datetime(post.created_at),
+            PostSortField::UpdatedAt => SortKey::Datetime(post.updated_at),
+            PostSortField::Title => SortKey::Text(post.title.clone()),
+        }
+    }
+
+    pub fn parse_cursor_key(&self, key: &str) -> Option<SortKey> {
+        match self {
+            PostSortField::CreatedAt | PostSortField::UpdatedAt => SortKey::parse_datetime(key),
+            PostSortField::Title => Some(SortKey::Text(key.to_string())),
+        }
+    }
+}
+
+/// Validated post listing order, parsed from `FindAllOptions::sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostSort {
+    pub field: PostSortField,
+    pub direction: SortDirection,
+}
+
+impl Default for PostSort {
+    fn default() -> Self {
+        Self {
+            field: PostSortField::CreatedAt,
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+impl TryFrom<Option<&str>> for PostSort {
+    type Error = PostRepositoryError;
+
+    fn try_from(sort: Option<&str>) -> Result<Self, Self::Error> {
+        let Some(sort) = sort else {
+            return Ok(Self::default());
+        };
+
+        let (field, direction) = parse_sort(sort);
+        let field = [
+            PostSortField::CreatedAt,
+            PostSortField::UpdatedAt,
+            PostSortField::Title,
+        ]
+        .into_iter()
+        .find(|candidate| candidate.as_str() == field)
+        .ok_or_else(|| PostRepositoryError::InvalidSort(sort.to_string()))?;
+
+        Ok(Self { field, direction })
+    }
+}
+
+impl fmt::Display for PostSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.direction.sort_prefix(), self.field.as_str())
+    }
+}
+
+impl PostSort {
+    pub fn cursor(&self, post: &Post) -> Cursor {
+        Cursor {
+            sort: self.to_string(),
+            key: self.field.key(post).to_cursor_key(),
+            id: post.id.clone(),
+        }
+    }
+
+    /// Decodes a client supplied cursor into the sort key and id it points at, rejecting cursors
+    /// issued for another order.
+    pub fn decode_cursor(&self, cursor: &str) -> Result<(SortKey, String), PostRepositoryError> {
+        Cursor::decode(cursor)
+            .filter(|decoded| decoded.sort == self.to_string())
+            .and_then(|decoded| {
+                let key = self.field.parse_cursor_key(&decoded.key)?;
+
+                Some((key, decoded.id))
+            })
+            .ok_or_else(|| PostRepositoryError::InvalidCursor(cursor.to_string()))
+    }
+}
+
+/// Post as written to the database. `created_at` and `updated_at` are left to the schema, which
+/// sets both on creation, refreshes `updated_at` on every write and never changes `created_at`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurrealPostEntityInput {
+    /// `None` keeps the slug of an updated post.
+    pub slug: Option<String>,
     pub title: String,
     pub content: String,
-    pub created_at: surrealdb::sql::Datetime,
-    pub updated_at: surrealdb::sql::Datetime,
+    /// `authors` record of the author, `None` keeps the author of an updated post.
+    pub author: Option<Thing>,
+    /// `None` publishes a new post and keeps the status of an updated one.
+    pub status: Option<PostStatus>,
+    pub publish_at: Option<surrealdb::sql::Datetime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurrealPostEntityOutput {
     pub id: String,
+    pub slug: String,
     pub title: String,
     pub content: String,
+    pub author: Option<String>,
+    pub status: PostStatus,
+    pub publish_at: Option<surrealdb::sql::Datetime>,
+    pub published_at: Option<surrealdb::sql::Datetime>,
     pub created_at: surrealdb::sql::Datetime,
     pub updated_at: surrealdb::sql::Datetime,
+    pub revision: u64,
 }
 
 impl From<(SurrealPostEntityOutput, Vec<Tag>)> for Post {
     fn from((post, tags): (SurrealPostEntityOutput, Vec<Tag>)) -> Self {
         Self {
             id: post.id,
+            slug: post.slug,
             title: post.title,
             content: post.content,
             tags,
+            author: post.author,
+            status: post.status,
+            publish_at: post.publish_at.map(|publish_at| publish_at.0),
+            published_at: post.published_at.map(|published_at| published_at.0),
             created_at: post.created_at.0,
             updated_at: post.updated_at.0,
+            revision: post.revision,
         }
     }
 }
@@ -43,22 +363,132 @@ impl From<(SurrealPostEntityOutput, Vec<Tag>)> for Post {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurrealPostEntityWithTagsOutput {
     pub id: String,
+    pub slug: String,
     pub title: String,
     pub content: String,
     tags: Vec<Tag>,
+    author: Option<String>,
+    pub status: PostStatus,
+    pub publish_at: Option<surrealdb::sql::Datetime>,
+    pub published_at: Option<surrealdb::sql::Datetime>,
     pub created_at: surrealdb::sql::Datetime,
     pub updated_at: surrealdb::sql::Datetime,
+    pub revision: u64,
 }
 
 impl From<SurrealPostEntityWithTagsOutput> for Post {
     fn from(post: SurrealPostEntityWithTagsOutput) -> Self {
         Self {
             id: post.id,
+            slug: post.slug,
             title: post.title,
             content: post.content,
             tags: post.tags,
+            author: post.author,
+            status: post.status,
+            publish_at: post.publish_at.map(|publish_at| publish_at.0),
+            published_at: post.published_at.map(|published_at| published_at.0),
             created_at: post.created_at.0,
             updated_at: post.updated_at.0,
+            revision: post.revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealDeletedPostOutput {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    tags: Vec<Tag>,
+    author: Option<String>,
+    pub status: PostStatus,
+    pub publish_at: Option<surrealdb::sql::Datetime>,
+    pub published_at: Option<surrealdb::sql::Datetime>,
+    pub created_at: surrealdb::sql::Datetime,
+    pub updated_at: surrealdb::sql::Datetime,
+    pub revision: u64,
+    pub deleted_at: surrealdb::sql::Datetime,
+}
+
+impl From<SurrealDeletedPostOutput> for DeletedPost {
+    fn from(post: SurrealDeletedPostOutput) -> Self {
+        Self {
+            post: Post {
+                id: post.id,
+                slug: post.slug,
+                title: post.title,
+                content: post.content,
+                tags: post.tags,
+                author: post.author,
+                status: post.status,
+                publish_at: post.publish_at.map(|publish_at| publish_at.0),
+                published_at: post.published_at.map(|published_at| published_at.0),
+                created_at: post.created_at.0,
+                updated_at: post.updated_at.0,
+                revision: post.revision,
+            },
+            deleted_at: post.deleted_at.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryPostEntity {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    pub tag_ids: Vec<String>,
+    /// Id of the author of the post, if it has one.
+    pub author_id: Option<String>,
+    pub status: PostStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub revision: u64,
+    /// When the post was moved to the trash, if it was.
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl MemoryPostEntity {
+    /// Moves the post to `status`, or keeps its current one when `None`, the way the schema does:
+    /// only scheduled posts keep a `publish_at`, and `published_at` is set when first published.
+    pub fn set_status(
+        &mut self,
+        status: Option<PostStatus>,
+        publish_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) {
+        self.status = status.unwrap_or(self.status);
+        self.publish_at = match self.status {
+            PostStatus::Scheduled => publish_at.or(self.publish_at),
+            _ => None,
+        };
+        if self.status == PostStatus::Published {
+            self.published_at.get_or_insert(now);
+        }
+    }
+}
+
+/// Post from its entity, its tags and the name of its author.
+impl From<(MemoryPostEntity, Vec<Tag>, Option<String>)> for Post {
+    fn from((post, tags, author): (MemoryPostEntity, Vec<Tag>, Option<String>)) -> Self {
+        Self {
+            id: post.id,
+            slug: post.slug,
+            title: post.title,
+            content: post.content,
+            tags,
+            author,
+            status: post.status,
+            publish_at: post.publish_at,
+            published_at: post.published_at,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            revision: post.revision,
         }
     }
 }
@@ -67,4 +497,220 @@ impl From<SurrealPostEntityWithTagsOutput> for Post {
 pub struct FindPostsResponse {
     pub posts: Vec<Post>,
     pub total: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Post in the trash. It is left out of every other read until restored, and purged for good
+/// once kept there longer than the configured retention.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedPost {
+    #[serde(flatten)]
+    pub post: Post,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Posts in the trash, most recently deleted first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindDeletedPostsResponse {
+    pub posts: Vec<DeletedPost>,
+    pub total: usize,
+}
+
+/// Post a slug points at, either as its current slug or as a redirect from an older one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlugOwner {
+    pub slug: String,
+    pub post: String,
+}
+
+/// Outcome of looking a post up by slug.
+#[derive(Debug, Clone)]
+pub enum SlugLookup {
+    Post(Post),
+    /// The slug used to be the post's, which now goes by this one.
+    Redirect(String),
+}
+
+/// Relation between a post and one of its tags, by their ids. Repairs report the relations
+/// whose post or tag no longer exists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostTagRelation {
+    pub post: String,
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPostsQuery {
+    pub q: String,
+}
+
+/// Title and content excerpt of a search hit, with matched terms wrapped in `<mark>` tags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostHighlights {
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostSearchHit {
+    #[serde(flatten)]
+    pub post: Post,
+    pub score: f32,
+    pub highlights: PostHighlights,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPostsResponse {
+    pub posts: Vec<PostSearchHit>,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealPostSearchOutput {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    tags: Vec<Tag>,
+    author: Option<String>,
+    pub status: PostStatus,
+    pub publish_at: Option<surrealdb::sql::Datetime>,
+    pub published_at: Option<surrealdb::sql::Datetime>,
+    pub created_at: surrealdb::sql::Datetime,
+    pub updated_at: surrealdb::sql::Datetime,
+    pub revision: u64,
+    pub score: f32,
+    pub title_highlight: String,
+    pub content_highlight: String,
+}
+
+impl From<SurrealPostSearchOutput> for PostSearchHit {
+    fn from(hit: SurrealPostSearchOutput) -> Self {
+        Self {
+            post: Post {
+                id: hit.id,
+                slug: hit.slug,
+                title: hit.title,
+                content: hit.content,
+                tags: hit.tags,
+                author: hit.author,
+                status: hit.status,
+                publish_at: hit.publish_at.map(|publish_at| publish_at.0),
+                published_at: hit.published_at.map(|published_at| published_at.0),
+                created_at: hit.created_at.0,
+                updated_at: hit.updated_at.0,
+                revision: hit.revision,
+            },
+            score: hit.score,
+            highlights: PostHighlights {
+                title: hit.title_highlight,
+                content: create_snippet(&hit.content_highlight),
+            },
+        }
+    }
+}
+
+impl From<&Post> for PostRevision {
+    fn from(post: &Post) -> Self {
+        let mut tags = post
+            .tags
+            .iter()
+            .map(|tag| tag.name.clone())
+            .collect::<Vec<_>>();
+        tags.sort();
+
+        Self {
+            revision: post.revision,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            tags,
+            created_at: post.updated_at,
+        }
+    }
+}
+
+/// Post as of `revision`, to restore it.
+impl From<PostRevision> for NewPost {
+    fn from(revision: PostRevision) -> Self {
+        Self {
+            slug: None,
+            title: revision.title,
+            content: revision.content,
+            tags: revision.tags.into_iter().collect(),
+            author: None,
+            status: None,
+            publish_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealPostRevisionOutput {
+    pub revision: u64,
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub created_at: surrealdb::sql::Datetime,
+}
+
+impl From<SurrealPostRevisionOutput> for PostRevision {
+    fn from(revision: SurrealPostRevisionOutput) -> Self {
+        Self {
+            revision: revision.revision,
+            title: revision.title,
+            content: revision.content,
+            tags: revision.tags,
+            created_at: revision.created_at.0,
+        }
+    }
+}
+
+/// Revisions of a post, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindPostRevisionsResponse {
+    pub revisions: Vec<PostRevision>,
+    pub total: usize,
+}
+
+/// Revision to compare another one against, e.g. `?from=2`. Defaults to the one right before it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostRevisionDiffQuery {
+    #[serde(default)]
+    pub from: Option<u64>,
+}
+
+/// Changes made to a post between two of its revisions. `title` and `content` are line diffs, see
+/// `line_diff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostRevisionDiff {
+    pub from: u64,
+    pub to: u64,
+    pub title: String,
+    pub content: String,
+    pub tags: TagChanges,
+}
+
+impl PostRevisionDiff {
+    pub fn between(from: &PostRevision, to: &PostRevision) -> Self {
+        let added = to
+            .tags
+            .iter()
+            .filter(|tag| !from.tags.contains(tag))
+            .cloned()
+            .collect();
+        let removed = from
+            .tags
+            .iter()
+            .filter(|tag| !to.tags.contains(tag))
+            .cloned()
+            .collect();
+
+        Self {
+            from: from.revision,
+            to: to.revision,
+            title: line_diff(&from.title, &to.title),
+            content: line_diff(&from.content, &to.content),
+            tags: TagChanges { added, removed },
+        }
+    }
 }
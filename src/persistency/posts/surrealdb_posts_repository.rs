@@ -1,56 +1,126 @@
 use super::{
     errors::PostRepositoryError,
     models::{
-        FindPostsResponse, NewPost, SurrealPostEntityInput, SurrealPostEntityOutput,
-        SurrealPostEntityWithTagsOutput,
+        DeletedPost, FindDeletedPostsResponse, FindPostRevisionsResponse, FindPostsResponse,
+        NewPost, PostPatch, PostSort, PostSortField, PostTagRelation, PostsFilter, SavePostOptions,
+        SearchPostsResponse, SlugLookup, SlugOwner, SurrealDeletedPostOutput,
+        SurrealPostEntityInput, SurrealPostEntityWithTagsOutput, SurrealPostRevisionOutput,
+        SurrealPostSearchOutput, TagChanges,
     },
-    utils::create_post_entity,
+    utils::{create_post_entity, pick_slug, search_terms, slug_candidate},
+    validation::validate_new_post,
 };
 use crate::{
-    models::{Post, Tag},
+    models::{Author, Post, PostRevision},
     persistency::{
-        models::{FindAllOptions, SurrealCountRecord},
-        traits::{PostRepository, TagRepository},
+        models::{
+            next_page_cursor, FindAllOptions, Precondition, SortKey, SurrealCountRecord,
+            SurrealRecord,
+        },
+        tags::models::{NewTag, SurrealTagEntityInput},
+        traits::{AuthorRepository, PostRepository, TagRepository},
     },
-    utils::tag::tags_diff_set,
 };
-use surrealdb::Surreal;
+use chrono::{DateTime, Utc};
+use surrealdb::{error::Db, sql::Thing, Response, Surreal};
 use tracing::{debug, info};
 
+/// Condition selecting the posts listed after `(cursor_key, cursor_id)` in `sort` order.
+fn keyset_condition(sort: PostSort) -> String {
+    let field = sort.field.as_str();
+    let operator = sort.direction.keyset_operator();
+    let value = match sort.field {
+        PostSortField::CreatedAt | PostSortField::UpdatedAt => "<datetime>$cursor_key",
+        PostSortField::Title => "$cursor_key",
+    };
+
+    format!(
+        "({field} {operator} {value} OR ({field} = {value} AND string::split(<string>id, ':')[1] > $cursor_id))"
+    )
+}
+
+/// Whether a statement of `response` failed because its post took a slug in use. Transactions
+/// only report the other statements as not executed, so every error is looked at.
+fn has_slug_violation(response: &mut Response) -> bool {
+    response.take_errors().values().any(|error| {
+        matches!(error, surrealdb::Error::Db(Db::IndexExists { index, .. }) if index == "unique_post_slug")
+    })
+}
+
+/// Error for a post write whose result could not be read, `fallback` unless its slug was taken
+/// concurrently.
+fn write_error(
+    response: &mut Response,
+    slug: Option<&str>,
+    fallback: PostRepositoryError,
+) -> PostRepositoryError {
+    match slug {
+        Some(slug) if has_slug_violation(response) => {
+            PostRepositoryError::SlugAlreadyExists(slug.to_string())
+        }
+        _ => fallback,
+    }
+}
+
+fn author_record(author: &Author) -> Thing {
+    Thing::from(("authors", author.id.as_str()))
+}
+
+fn new_tag_entities(names: Vec<String>) -> Vec<SurrealTagEntityInput> {
+    names
+        .into_iter()
+        .map(|name| SurrealTagEntityInput::from(NewTag { name }))
+        .collect()
+}
+
 #[derive(Clone)]
-pub struct SurrealdbPostsRepository<TR: TagRepository> {
+pub struct SurrealdbPostsRepository<TR: TagRepository, AR: AuthorRepository> {
     db: Surreal<surrealdb::engine::any::Any>,
     tags_repository: TR,
+    authors_repository: AR,
 }
 
-impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
-    pub fn new(db: Surreal<surrealdb::engine::any::Any>, tags_repository: TR) -> Self {
+impl<TR: TagRepository, AR: AuthorRepository> SurrealdbPostsRepository<TR, AR> {
+    pub fn new(
+        db: Surreal<surrealdb::engine::any::Any>,
+        tags_repository: TR,
+        authors_repository: AR,
+    ) -> Self {
         Self {
             db,
             tags_repository,
+            authors_repository,
         }
     }
 }
 
-impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
+impl<TR: TagRepository, AR: AuthorRepository> SurrealdbPostsRepository<TR, AR> {
+    /// Creates the post, its missing tags and its tag relations in a single transaction.
     async fn register_post_in_db(
         &self,
         post_entity: SurrealPostEntityInput,
-    ) -> Result<SurrealPostEntityOutput, PostRepositoryError> {
-        debug!("Creating post...");
+        tag_names: &[&str],
+        new_tags: &[SurrealTagEntityInput],
+    ) -> Result<SurrealPostEntityWithTagsOutput, PostRepositoryError> {
+        debug!("Creating post with tags {tag_names:?}, creating {new_tags:?}...");
 
         let result = self
             .db
             .query(include_str!("./queries/create_post.surql"))
-            .bind(("post", post_entity))
+            .bind(("post", &post_entity))
+            .bind(("tag_names", tag_names))
+            .bind(("new_tags", new_tags))
             .await;
 
         debug!("Created post: {result:?}");
 
-        let post = result
-            .map_err(|e| PostRepositoryError::Database(e.into()))?
-            .take::<Vec<SurrealPostEntityOutput>>(0)
-            .map_err(|_| PostRepositoryError::PostCreation)?
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let post = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(response.num_statements() - 1)
+            .map_err(|_| {
+                let slug = post_entity.slug.as_deref();
+                write_error(&mut response, slug, PostRepositoryError::PostCreation)
+            })?
             .first()
             .cloned()
             .ok_or(PostRepositoryError::PostCreation)?;
@@ -60,46 +130,82 @@ impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
         Ok(post)
     }
 
-    async fn sync_relations_in_db(
+    /// Posts going or redirecting by `candidate` or by a suffixed `candidate`.
+    async fn find_slug_owners_in_db(
         &self,
-        post_id: &str,
-        tags: &[Tag],
-    ) -> Result<(), PostRepositoryError> {
-        debug!("Syncing relations for post {post_id} with tags {tags:?}...");
+        candidate: &str,
+    ) -> Result<Vec<SlugOwner>, PostRepositoryError> {
+        debug!("Finding owners of slug {candidate:?}...");
 
-        let post_id = format!("posts:{}", post_id);
-        let tag_ids = tags
-            .iter()
-            .map(|tag| format!("tags:{}", tag.id))
-            .collect::<Vec<_>>();
+        let result = self
+            .db
+            .query(include_str!("./queries/find_slug_owners.surql"))
+            .bind(("slug", candidate))
+            .bind(("prefix", format!("{candidate}-")))
+            .await;
 
-        debug!("Syncing relations for post {post_id} with tags {tag_ids:?}...");
+        debug!("Found owners of slug {candidate:?}: {result:?}");
 
-        let response = self
-            .db
-            .query(include_str!("./queries/sync_relations.surql"))
-            .bind(("post_id", post_id.as_str()))
-            .bind(("tag_ids", &tag_ids))
-            .await
-            .map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let mut owners = response
+            .take::<Vec<SlugOwner>>(0)
+            .map_err(|_| PostRepositoryError::PostGet)?;
+        owners.extend(
+            response
+                .take::<Vec<SlugOwner>>(1)
+                .map_err(|_| PostRepositoryError::PostGet)?,
+        );
 
-        info!("Synced relations for post {post_id} with tags {tag_ids:?}: {response:?}");
+        Ok(owners)
+    }
 
-        Ok(())
+    /// Slug to save `new_post` with, `None` to keep the one of updated post `post_id`.
+    async fn resolve_slug(
+        &self,
+        new_post: &NewPost,
+        post_id: Option<&str>,
+    ) -> Result<Option<String>, PostRepositoryError> {
+        if post_id.is_some() && new_post.slug.is_none() {
+            return Ok(None);
+        }
+
+        let candidate = slug_candidate(new_post);
+        let owners = self.find_slug_owners_in_db(&candidate).await?;
+
+        pick_slug(candidate, new_post.slug.is_some(), &owners, post_id).map(Some)
     }
 
     async fn list_posts_in_db(
         &self,
         limit: usize,
         offset: usize,
+        sort: PostSort,
+        filter: &PostsFilter,
+        cursor: Option<(SortKey, String)>,
     ) -> Result<Vec<SurrealPostEntityWithTagsOutput>, PostRepositoryError> {
-        debug!("Listing posts...");
+        debug!("Listing posts matching {filter:?} by {sort:?} after {cursor:?}...");
 
+        let order = format!("{} {}", sort.field.as_str(), sort.direction.as_surql());
+        let (keyset, offset, cursor_key, cursor_id) = match cursor {
+            Some((key, id)) => (keyset_condition(sort), 0, key.to_cursor_key(), id),
+            None => ("true".to_string(), offset, String::new(), String::new()),
+        };
         let result = self
             .db
-            .query(include_str!("./queries/list_posts.surql"))
+            .query(
+                include_str!("./queries/list_posts.surql")
+                    .replace("{order}", &order)
+                    .replace("{keyset}", &keyset),
+            )
             .bind(("limit", limit))
             .bind(("offset", offset))
+            .bind(("cursor_key", cursor_key))
+            .bind(("cursor_id", cursor_id))
+            .bind(("tags_any", &filter.tags_any))
+            .bind(("tags_all", &filter.tags_all))
+            .bind(("tags_none", &filter.tags_none))
+            .bind(("status", filter.statuses()))
+            .bind(("author", filter.author.as_deref()))
             .await;
 
         debug!("Listed posts: {result:?}");
@@ -114,12 +220,17 @@ impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
         Ok(posts)
     }
 
-    async fn count_posts_in_db(&self) -> Result<usize, PostRepositoryError> {
-        debug!("Counting posts...");
+    async fn count_posts_in_db(&self, filter: &PostsFilter) -> Result<usize, PostRepositoryError> {
+        debug!("Counting posts matching {filter:?}...");
 
         let result = self
             .db
             .query(include_str!("./queries/count_posts.surql"))
+            .bind(("tags_any", &filter.tags_any))
+            .bind(("tags_all", &filter.tags_all))
+            .bind(("tags_none", &filter.tags_none))
+            .bind(("status", filter.statuses()))
+            .bind(("author", filter.author.as_deref()))
             .await;
 
         debug!("Counted posts: {result:?}");
@@ -137,11 +248,96 @@ impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
         Ok(total)
     }
 
+    async fn search_posts_in_db(
+        &self,
+        query: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SurrealPostSearchOutput>, PostRepositoryError> {
+        debug!("Searching posts for {query:?}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/search_posts.surql"))
+            .bind(("query", query))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await;
+
+        debug!("Searched posts: {result:?}");
+
+        let posts = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealPostSearchOutput>>(0)
+            .map_err(|_| PostRepositoryError::PostSearch)?;
+
+        info!("Searched posts for {query:?}: {posts:?}");
+
+        Ok(posts)
+    }
+
+    async fn count_search_posts_in_db(&self, query: &str) -> Result<usize, PostRepositoryError> {
+        debug!("Counting posts matching {query:?}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/count_search_posts.surql"))
+            .bind(("query", query))
+            .await;
+
+        debug!("Counted posts: {result:?}");
+
+        let total = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealCountRecord>>(0)
+            .map_err(|_| PostRepositoryError::PostCount)?
+            .first()
+            .unwrap_or_default()
+            .count;
+
+        info!("Counted posts matching {query:?}: {total}");
+
+        Ok(total)
+    }
+
+    /// The live post going by `slug`, or the slug of the live post redirected to from it.
+    async fn get_post_by_slug_in_db(
+        &self,
+        slug: &str,
+    ) -> Result<(Option<SurrealPostEntityWithTagsOutput>, Option<String>), PostRepositoryError>
+    {
+        debug!("Fetching post by slug {slug:?}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/get_post_by_slug.surql"))
+            .bind(("slug", slug))
+            .await;
+
+        debug!("Fetched post by slug {slug:?}: {result:?}");
+
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let post = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(0)
+            .map_err(|_| PostRepositoryError::PostGet)?
+            .first()
+            .cloned();
+        let redirect = response
+            .take::<Vec<String>>(1)
+            .map_err(|_| PostRepositoryError::PostGet)?
+            .first()
+            .cloned();
+
+        info!("Fetched post by slug {slug:?}: {post:?}, redirecting to {redirect:?}");
+
+        Ok((post, redirect))
+    }
+
     async fn get_post_in_db(
         &self,
-        post_id: &str,
+        id: &str,
     ) -> Result<SurrealPostEntityWithTagsOutput, PostRepositoryError> {
-        let post_id = format!("posts:{post_id}");
+        let post_id = format!("posts:{id}");
 
         debug!("Fetching post {post_id}...");
 
@@ -159,103 +355,596 @@ impl<TR: TagRepository> SurrealdbPostsRepository<TR> {
             .map_err(|_| PostRepositoryError::PostGet)?
             .first()
             .cloned()
-            .ok_or(PostRepositoryError::PostGet)?;
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))?;
 
         info!("Fetched post: {post:?}");
 
         Ok(post)
     }
 
+    /// Error for a conditional write that matched no post: either it does not exist, belongs to
+    /// another author or is no longer at one of the expected revisions.
+    async fn unmatched_write_error(
+        &self,
+        id: &str,
+        precondition: &Precondition,
+    ) -> PostRepositoryError {
+        match self.get_post_in_db(id).await {
+            Ok(post) => PostRepositoryError::unmet_precondition(precondition, &post.into()),
+            Err(error) => error,
+        }
+    }
+
+    /// Updates the post, creates its missing tags and replaces its tag relations in a single
+    /// transaction.
     async fn update_post_in_db(
         &self,
-        post_id: &str,
+        id: &str,
         post_entity: &SurrealPostEntityInput,
-    ) -> Result<SurrealPostEntityOutput, PostRepositoryError> {
-        let post_id = format!("posts:{post_id}");
+        tag_names: &[&str],
+        new_tags: &[SurrealTagEntityInput],
+        precondition: &Precondition,
+    ) -> Result<SurrealPostEntityWithTagsOutput, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
 
-        debug!("Updating post {post_id}: {post_entity:?}...");
+        debug!("Updating post {post_id} with tags {tag_names:?}, creating {new_tags:?}: {post_entity:?}...");
 
-        let response = self
+        let result = self
             .db
             .query(include_str!("./queries/update_posts.surql"))
-            .bind(("id", post_id.as_str()))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("slug", post_entity.slug.as_deref()))
             .bind(("title", post_entity.title.as_str()))
             .bind(("content", post_entity.content.as_str()))
+            .bind(("author", &post_entity.author))
+            .bind(("status", post_entity.status))
+            .bind(("publish_at", &post_entity.publish_at))
+            .bind(("tag_names", tag_names))
+            .bind(("new_tags", new_tags))
+            .bind(("revisions", precondition.revisions()))
+            .bind(("owner", precondition.owner()))
             .await;
 
-        debug!("Updated post {post_id}: {response:?}");
+        debug!("Updated post {post_id}: {result:?}");
 
-        let post = response
-            .map_err(|e| PostRepositoryError::Database(e.into()))?
-            .take::<Vec<SurrealPostEntityOutput>>(0)
-            .map_err(|_| PostRepositoryError::PostUpdate)?
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let Some(post) = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(response.num_statements() - 1)
+            .map_err(|_| {
+                let slug = post_entity.slug.as_deref();
+                write_error(&mut response, slug, PostRepositoryError::PostUpdate)
+            })?
             .first()
             .cloned()
-            .ok_or(PostRepositoryError::PostUpdate)?;
+        else {
+            return Err(self.unmatched_write_error(id, precondition).await);
+        };
 
         debug!("Updated post {post_id}: {post:?}");
 
         Ok(post)
     }
 
-    async fn delete_post_in_db(&self, post_id: &str) -> Result<(), PostRepositoryError> {
-        let post_id = format!("posts:{post_id}");
+    /// Sets the patched fields of the post, creates its missing tags and adds and removes only the
+    /// changed tag relations in a single transaction.
+    async fn patch_post_in_db(
+        &self,
+        id: &str,
+        patch: &PostPatch,
+        author: Option<Thing>,
+        tag_changes: &TagChanges,
+        new_tags: &[SurrealTagEntityInput],
+        precondition: &Precondition,
+    ) -> Result<SurrealPostEntityWithTagsOutput, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
 
-        debug!("Deleting post {post_id}...");
+        debug!("Patching post {post_id} with {tag_changes:?}, creating {new_tags:?}: {patch:?}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/patch_post.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("slug", patch.slug.as_deref()))
+            .bind(("title", patch.title.as_deref()))
+            .bind(("content", patch.content.as_deref()))
+            .bind(("author", author))
+            .bind(("status", patch.status))
+            .bind((
+                "publish_at",
+                patch.publish_at.map(surrealdb::sql::Datetime::from),
+            ))
+            .bind(("added_tags", &tag_changes.added))
+            .bind(("removed_tags", &tag_changes.removed))
+            .bind(("new_tags", new_tags))
+            .bind(("revisions", precondition.revisions()))
+            .bind(("owner", precondition.owner()))
+            .await;
+
+        debug!("Patched post {post_id}: {result:?}");
+
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let Some(post) = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(response.num_statements() - 1)
+            .map_err(|_| {
+                let slug = patch.slug.as_deref();
+                write_error(&mut response, slug, PostRepositoryError::PostUpdate)
+            })?
+            .first()
+            .cloned()
+        else {
+            return Err(self.unmatched_write_error(id, precondition).await);
+        };
+
+        info!("Patched post {post_id}: {post:?}");
+
+        Ok(post)
+    }
+
+    /// Validates `post` once patched, then writes the patch.
+    async fn apply_patch(
+        &self,
+        post: Post,
+        patch: PostPatch,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let patched = patch.apply(&post);
+        let resolved = validate_new_post(
+            &patched,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+        let slug = self.resolve_slug(&patched, Some(&post.id)).await?;
+        let patch = PostPatch { slug, ..patch };
+        let tag_changes = patch.tag_changes(&post);
+        let new_tags = new_tag_entities(resolved.tags.missing);
+        let author = resolved.author.as_ref().map(author_record);
+
+        let patched_post = self
+            .patch_post_in_db(
+                &post.id,
+                &patch,
+                author,
+                &tag_changes,
+                &new_tags,
+                &precondition,
+            )
+            .await?;
+
+        Ok(patched_post.into())
+    }
+
+    async fn list_post_revisions_in_db(
+        &self,
+        id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SurrealPostRevisionOutput>, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Listing revisions of post {post_id}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/list_post_revisions.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await;
+
+        debug!("Listed revisions of post {post_id}: {result:?}");
+
+        let revisions = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealPostRevisionOutput>>(0)
+            .map_err(|_| PostRepositoryError::RevisionListing)?;
+
+        info!("Listed revisions of post {post_id}: {revisions:?}");
+
+        Ok(revisions)
+    }
+
+    async fn count_post_revisions_in_db(&self, id: &str) -> Result<usize, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Counting revisions of post {post_id}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/count_post_revisions.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .await;
+
+        debug!("Counted revisions of post {post_id}: {result:?}");
+
+        let total = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealCountRecord>>(0)
+            .map_err(|_| PostRepositoryError::RevisionListing)?
+            .first()
+            .unwrap_or_default()
+            .count;
+
+        info!("Counted revisions of post {post_id}: {total}");
+
+        Ok(total)
+    }
+
+    async fn get_post_revision_in_db(
+        &self,
+        id: &str,
+        revision: u64,
+    ) -> Result<Option<SurrealPostRevisionOutput>, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Fetching revision {revision} of post {post_id}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/get_post_revision.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("revision", revision))
+            .await;
+
+        debug!("Fetched revision {revision} of post {post_id}: {result:?}");
+
+        let revision = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealPostRevisionOutput>>(0)
+            .map_err(|_| PostRepositoryError::RevisionListing)?
+            .first()
+            .cloned();
+
+        info!("Fetched revision of post {post_id}: {revision:?}");
+
+        Ok(revision)
+    }
+
+    async fn delete_post_in_db(
+        &self,
+        id: &str,
+        precondition: &Precondition,
+    ) -> Result<(), PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Moving post {post_id} to the trash...");
 
         let response = self
             .db
             .query(include_str!("./queries/delete_post.surql"))
             .bind(("post_id", post_id.as_str()))
+            .bind(("revisions", precondition.revisions()))
+            .bind(("owner", precondition.owner()))
             .await;
 
-        debug!("Deleted post {post_id}: {response:?}");
+        debug!("Moved post {post_id} to the trash: {response:?}");
 
-        response.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let mut response = response.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let deleted = response
+            .take::<Vec<SurrealRecord>>(response.num_statements() - 1)
+            .map_err(|_| PostRepositoryError::PostDeletion)?;
+        if deleted.is_empty() {
+            return Err(self.unmatched_write_error(id, precondition).await);
+        }
 
-        info!("Deleted post {post_id}");
+        info!("Moved post {post_id} to the trash");
 
         Ok(())
     }
-}
 
-impl<TR: TagRepository> PostRepository for SurrealdbPostsRepository<TR> {
-    async fn create(&self, new_post: NewPost) -> Result<Post, PostRepositoryError> {
-        let tags = self
-            .tags_repository
-            .find_in_names(new_post.tags.iter().map(|tag| tag.as_str()).collect())
+    async fn list_deleted_posts_in_db(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SurrealDeletedPostOutput>, PostRepositoryError> {
+        debug!("Listing deleted posts...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/list_deleted_posts.surql"))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await;
+
+        debug!("Listed deleted posts: {result:?}");
+
+        let posts = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealDeletedPostOutput>>(0)
+            .map_err(|_| PostRepositoryError::PostListing)?;
+
+        info!("Listed deleted posts: {posts:?}");
+
+        Ok(posts)
+    }
+
+    async fn count_deleted_posts_in_db(&self) -> Result<usize, PostRepositoryError> {
+        debug!("Counting deleted posts...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/count_deleted_posts.surql"))
+            .await;
+
+        debug!("Counted deleted posts: {result:?}");
+
+        let total = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealCountRecord>>(0)
+            .map_err(|_| PostRepositoryError::PostCount)?
+            .first()
+            .unwrap_or_default()
+            .count;
+
+        info!("Counted deleted posts: {total}");
+
+        Ok(total)
+    }
+
+    async fn get_deleted_post_in_db(
+        &self,
+        id: &str,
+    ) -> Result<SurrealDeletedPostOutput, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Fetching deleted post {post_id}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/get_deleted_post.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .await;
+
+        debug!("Fetched deleted post: {result:?}");
+
+        let post = result
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<SurrealDeletedPostOutput>>(0)
+            .map_err(|_| PostRepositoryError::PostGet)?
+            .first()
+            .cloned()
+            .ok_or_else(|| PostRepositoryError::PostNotFound(id.to_string()))?;
+
+        info!("Fetched deleted post: {post:?}");
+
+        Ok(post)
+    }
+
+    /// Same as `unmatched_write_error`, for writes to a post in the trash.
+    async fn unmatched_trash_write_error(
+        &self,
+        id: &str,
+        precondition: &Precondition,
+    ) -> PostRepositoryError {
+        match self.get_deleted_post_in_db(id).await {
+            Ok(post) => {
+                PostRepositoryError::unmet_precondition(precondition, &DeletedPost::from(post).post)
+            }
+            Err(error) => error,
+        }
+    }
+
+    async fn restore_deleted_post_in_db(
+        &self,
+        id: &str,
+        precondition: &Precondition,
+    ) -> Result<SurrealPostEntityWithTagsOutput, PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Restoring post {post_id} from the trash...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/restore_deleted_post.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("revisions", precondition.revisions()))
+            .bind(("owner", precondition.owner()))
+            .await;
+
+        debug!("Restored post {post_id}: {result:?}");
+
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let Some(post) = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(response.num_statements() - 1)
+            .map_err(|_| PostRepositoryError::PostUpdate)?
+            .first()
+            .cloned()
+        else {
+            return Err(self.unmatched_trash_write_error(id, precondition).await);
+        };
+
+        info!("Restored post {post_id} from the trash: {post:?}");
+
+        Ok(post)
+    }
+
+    /// Deletes the post in the trash for good, along with its tag relations and revisions.
+    async fn purge_post_in_db(
+        &self,
+        id: &str,
+        precondition: &Precondition,
+    ) -> Result<(), PostRepositoryError> {
+        let post_id = format!("posts:{id}");
+
+        debug!("Purging post {post_id}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/purge_post.surql"))
+            .bind(("post_id", post_id.as_str()))
+            .bind(("revisions", precondition.revisions()))
+            .bind(("owner", precondition.owner()))
+            .await;
+
+        debug!("Purged post {post_id}: {result:?}");
+
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let purged = response
+            .take::<Vec<SurrealRecord>>(response.num_statements() - 1)
+            .map_err(|_| PostRepositoryError::PostPurge)?;
+        if purged.is_empty() {
+            return Err(self.unmatched_trash_write_error(id, precondition).await);
+        }
+
+        info!("Purged post {post_id}");
+
+        Ok(())
+    }
+
+    async fn purge_deleted_posts_in_db(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, PostRepositoryError> {
+        debug!("Purging posts deleted before {cutoff}...");
+
+        let result = self
+            .db
+            .query(include_str!("./queries/purge_deleted_posts.surql"))
+            .bind(("cutoff", cutoff))
+            .await;
+
+        debug!("Purged posts deleted before {cutoff}: {result:?}");
+
+        let mut response = result.map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let purged = response
+            .take::<Vec<SurrealRecord>>(response.num_statements() - 1)
+            .map_err(|_| PostRepositoryError::PostPurge)?;
+
+        info!("Purged posts deleted before {cutoff}: {purged:?}");
+
+        Ok(purged.len())
+    }
+
+    async fn publish_scheduled_posts_in_db(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<SurrealPostEntityWithTagsOutput>, PostRepositoryError> {
+        let mut response = self
+            .db
+            .query(include_str!("./queries/publish_scheduled_posts.surql"))
+            .bind(("now", now))
             .await
             .map_err(|e| PostRepositoryError::Database(e.into()))?;
+        let published = response
+            .take::<Vec<SurrealPostEntityWithTagsOutput>>(response.num_statements() - 1)
+            .map_err(|_| PostRepositoryError::PostPublish)?;
 
-        if tags.len() != new_post.tags.len() {
-            let diff = tags_diff_set(tags, &new_post.tags);
-            return Err(PostRepositoryError::TagsNotFound(
-                diff.into_iter().collect(),
-            ));
+        if !published.is_empty() {
+            info!("Published posts scheduled until {now}: {published:?}");
         }
 
-        let post_entity = create_post_entity(new_post.title, new_post.content, chrono::Utc::now());
+        Ok(published)
+    }
+
+    /// Runs `query`, which finds or removes the `posts_tags` edges whose post or tag is gone.
+    async fn dangling_relations_in_db(
+        &self,
+        query: &str,
+    ) -> Result<Vec<PostTagRelation>, PostRepositoryError> {
+        let relations = self
+            .db
+            .query(query)
+            .await
+            .map_err(|e| PostRepositoryError::Database(e.into()))?
+            .take::<Vec<PostTagRelation>>(0)
+            .map_err(|_| PostRepositoryError::RelationRepair)?;
+
+        Ok(relations)
+    }
+}
+
+impl<TR: TagRepository, AR: AuthorRepository> PostRepository for SurrealdbPostsRepository<TR, AR> {
+    async fn create(
+        &self,
+        new_post: NewPost,
+        options: SavePostOptions,
+    ) -> Result<Post, PostRepositoryError> {
+        let resolved = validate_new_post(
+            &new_post,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+        let tag_names = new_post
+            .tags
+            .iter()
+            .map(|tag| tag.as_str())
+            .collect::<Vec<_>>();
+        let new_tags = new_tag_entities(resolved.tags.missing);
+        let author = resolved.author.as_ref().map(author_record);
+
+        // `published_at` is computed before the `status` default applies, so spell it out.
+        let post_entity = SurrealPostEntityInput {
+            slug: self.resolve_slug(&new_post, None).await?,
+            author,
+            status: Some(new_post.status.unwrap_or_default()),
+            ..create_post_entity(&new_post)
+        };
 
-        let created_post = self.register_post_in_db(post_entity).await?;
-        self.sync_relations_in_db(&created_post.id, &tags).await?;
+        let created_post = self
+            .register_post_in_db(post_entity, &tag_names, &new_tags)
+            .await?;
 
-        Ok((created_post, tags).into())
+        Ok(created_post.into())
     }
 
     async fn find_all(
         &self,
         options: FindAllOptions,
+        filter: PostsFilter,
     ) -> Result<FindPostsResponse, PostRepositoryError> {
-        let posts = self
-            .list_posts_in_db(options.limit, options.offset)
+        let sort = PostSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+
+        let mut posts = self
+            .list_posts_in_db(
+                options.limit.saturating_add(1),
+                options.offset,
+                sort,
+                &filter,
+                cursor,
+            )
             .await?
             .into_iter()
             .map(|post| post.into())
             .collect();
+        let next_cursor = next_page_cursor(&mut posts, options.limit, |post| sort.cursor(post));
+
+        let total = self.count_posts_in_db(&filter).await?;
+
+        Ok(FindPostsResponse {
+            posts,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: FindAllOptions,
+    ) -> Result<SearchPostsResponse, PostRepositoryError> {
+        if search_terms(query).is_empty() {
+            return Err(PostRepositoryError::EmptySearchQuery);
+        }
+
+        let posts = self
+            .search_posts_in_db(query, options.limit, options.offset)
+            .await?
+            .into_iter()
+            .map(|hit| hit.into())
+            .collect();
 
-        let total = self.count_posts_in_db().await?;
+        let total = self.count_search_posts_in_db(query).await?;
 
-        Ok(FindPostsResponse { posts, total })
+        Ok(SearchPostsResponse { posts, total })
     }
 
     async fn get(&self, id: &str) -> Result<Post, PostRepositoryError> {
@@ -264,29 +953,393 @@ impl<TR: TagRepository> PostRepository for SurrealdbPostsRepository<TR> {
         Ok(post.into())
     }
 
-    async fn update(&self, id: &str, new_post: NewPost) -> Result<Post, PostRepositoryError> {
-        let post_entity = create_post_entity(new_post.title, new_post.content, chrono::Utc::now());
+    async fn get_by_slug(&self, slug: &str) -> Result<SlugLookup, PostRepositoryError> {
+        match self.get_post_by_slug_in_db(slug).await? {
+            (Some(post), _) => Ok(SlugLookup::Post(post.into())),
+            (None, Some(redirect)) => Ok(SlugLookup::Redirect(redirect)),
+            (None, None) => Err(PostRepositoryError::PostNotFound(slug.to_string())),
+        }
+    }
 
-        let tags = self
-            .tags_repository
-            .find_in_names(
-                new_post
-                    .tags
-                    .iter()
-                    .map(|tag| tag.as_str())
-                    .collect::<Vec<_>>(),
-            )
+    async fn update(
+        &self,
+        id: &str,
+        new_post: NewPost,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let resolved = validate_new_post(
+            &new_post,
+            options,
+            &self.tags_repository,
+            &self.authors_repository,
+        )
+        .await?;
+        let tag_names = new_post
+            .tags
+            .iter()
+            .map(|tag| tag.as_str())
+            .collect::<Vec<_>>();
+        let new_tags = new_tag_entities(resolved.tags.missing);
+        let author = resolved.author.as_ref().map(author_record);
+
+        let post_entity = SurrealPostEntityInput {
+            slug: self.resolve_slug(&new_post, Some(id)).await?,
+            author,
+            ..create_post_entity(&new_post)
+        };
+        let updated_post = self
+            .update_post_in_db(id, &post_entity, &tag_names, &new_tags, &precondition)
+            .await?;
+
+        Ok(updated_post.into())
+    }
+
+    async fn patch(
+        &self,
+        id: &str,
+        patch: PostPatch,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+
+        self.apply_patch(post, patch, options, precondition).await
+    }
+
+    async fn add_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+        let patch = PostPatch::adding_tags(&post, tags);
+
+        self.apply_patch(post, patch, options, precondition).await
+    }
+
+    async fn remove_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let post = self.get(id).await?;
+        let patch = PostPatch::removing_tags(&post, &tags);
+
+        self.apply_patch(post, patch, SavePostOptions::default(), precondition)
             .await
-            .map_err(|_| PostRepositoryError::PostUpdate)?;
-        let updated_post = self.update_post_in_db(id, &post_entity).await?;
-        self.sync_relations_in_db(&updated_post.id, &tags).await?;
+    }
+
+    async fn revisions(
+        &self,
+        id: &str,
+        options: FindAllOptions,
+    ) -> Result<FindPostRevisionsResponse, PostRepositoryError> {
+        // Every post has at least the revision it was created at.
+        let total = self.count_post_revisions_in_db(id).await?;
+        if total == 0 {
+            return Err(PostRepositoryError::PostNotFound(id.to_string()));
+        }
+
+        let revisions = self
+            .list_post_revisions_in_db(id, options.limit, options.offset)
+            .await?
+            .into_iter()
+            .map(|revision| revision.into())
+            .collect();
+
+        Ok(FindPostRevisionsResponse { revisions, total })
+    }
+
+    async fn get_revision(
+        &self,
+        id: &str,
+        revision: u64,
+    ) -> Result<PostRevision, PostRepositoryError> {
+        match self.get_post_revision_in_db(id, revision).await? {
+            Some(found) => Ok(found.into()),
+            None => {
+                self.get_post_in_db(id).await?;
+
+                Err(PostRepositoryError::RevisionNotFound(
+                    id.to_string(),
+                    revision,
+                ))
+            }
+        }
+    }
+
+    async fn restore_revision(
+        &self,
+        id: &str,
+        revision: u64,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let revision = self.get_revision(id, revision).await?;
 
-        Ok((updated_post, tags).into())
+        self.update(id, revision.into(), options, precondition)
+            .await
     }
 
-    async fn delete(&self, id: &str) -> Result<(), PostRepositoryError> {
-        self.delete_post_in_db(id).await?;
+    async fn delete(
+        &self,
+        id: &str,
+        precondition: Precondition,
+    ) -> Result<(), PostRepositoryError> {
+        self.delete_post_in_db(id, &precondition).await?;
 
         Ok(())
     }
+
+    async fn find_deleted(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindDeletedPostsResponse, PostRepositoryError> {
+        let posts = self
+            .list_deleted_posts_in_db(options.limit, options.offset)
+            .await?
+            .into_iter()
+            .map(|post| post.into())
+            .collect();
+
+        let total = self.count_deleted_posts_in_db().await?;
+
+        Ok(FindDeletedPostsResponse { posts, total })
+    }
+
+    async fn restore_deleted(
+        &self,
+        id: &str,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError> {
+        let restored_post = self.restore_deleted_post_in_db(id, &precondition).await?;
+
+        Ok(restored_post.into())
+    }
+
+    async fn purge(&self, id: &str, precondition: Precondition) -> Result<(), PostRepositoryError> {
+        self.purge_post_in_db(id, &precondition).await
+    }
+
+    async fn purge_deleted_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, PostRepositoryError> {
+        self.purge_deleted_posts_in_db(cutoff).await
+    }
+
+    async fn publish_scheduled(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Post>, PostRepositoryError> {
+        let published = self.publish_scheduled_posts_in_db(now).await?;
+
+        Ok(published.into_iter().map(Post::from).collect())
+    }
+
+    async fn find_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError> {
+        self.dangling_relations_in_db(include_str!("./queries/find_dangling_relations.surql"))
+            .await
+    }
+
+    async fn remove_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError> {
+        let removed = self
+            .dangling_relations_in_db(include_str!("./queries/remove_dangling_relations.surql"))
+            .await?;
+
+        info!("Removed dangling tag relations: {removed:?}");
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        migrations::{migrate_up, MIGRATIONS},
+        persistency::{
+            authors::surrealdb_authors_repository::SurrealdbAuthorsRepository,
+            tags::{models::NewTag, surrealdb_tags_repository::SurrealdbTagsRepository},
+        },
+    };
+    use std::collections::HashSet;
+
+    async fn repositories() -> (
+        Surreal<surrealdb::engine::any::Any>,
+        SurrealdbPostsRepository<SurrealdbTagsRepository, SurrealdbAuthorsRepository>,
+    ) {
+        let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+        db.use_ns("iemanjad").use_db("posts").await.unwrap();
+        migrate_up(&db, MIGRATIONS, None).await.unwrap();
+
+        let tags = SurrealdbTagsRepository::new(db.clone());
+        for name in ["rust", "zig"] {
+            tags.create(NewTag {
+                name: name.to_string(),
+            })
+            .await
+            .unwrap();
+        }
+
+        let authors = SurrealdbAuthorsRepository::new(db.clone());
+
+        (db.clone(), SurrealdbPostsRepository::new(db, tags, authors))
+    }
+
+    fn new_post(title: &str, tags: &[&str]) -> NewPost {
+        NewPost {
+            slug: None,
+            title: title.to_string(),
+            content: format!("{title} content"),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            author: None,
+            status: None,
+            publish_at: None,
+        }
+    }
+
+    /// Makes every write to `posts_tags` fail, so a post write fails after the post itself was
+    /// written but before its relations are.
+    async fn break_relations(db: &Surreal<surrealdb::engine::any::Any>) {
+        db.query("DEFINE FIELD in ON TABLE posts_tags TYPE record<posts> ASSERT false")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_schema_keeps_created_at() {
+        let (db, posts) = repositories().await;
+        let created = posts
+            .create(new_post("hello", &["rust"]), SavePostOptions::default())
+            .await
+            .unwrap();
+
+        db.query("UPDATE (<record>$post_id) SET created_at = d'2000-01-01T00:00:00Z'")
+            .bind(("post_id", format!("posts:{}", created.id)))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let fetched = posts.get(&created.id).await.unwrap();
+
+        assert_eq!(fetched.created_at, created.created_at);
+        assert!(fetched.updated_at > created.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_create_rolls_back_post_when_relations_fail() {
+        let (db, posts) = repositories().await;
+        break_relations(&db).await;
+
+        let result = posts
+            .create(new_post("hello", &["rust"]), SavePostOptions::default())
+            .await;
+        let listed = posts
+            .find_all(FindAllOptions::default(), PostsFilter::default())
+            .await
+            .unwrap();
+
+        assert!(matches!(result, Err(PostRepositoryError::PostCreation)));
+        assert_eq!(listed.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_rolls_back_post_when_relations_fail() {
+        let (db, posts) = repositories().await;
+        let created = posts
+            .create(new_post("hello", &["rust"]), SavePostOptions::default())
+            .await
+            .unwrap();
+        break_relations(&db).await;
+
+        let result = posts
+            .update(
+                &created.id,
+                new_post("goodbye", &["zig"]),
+                SavePostOptions::default(),
+                Precondition::ANY,
+            )
+            .await;
+        let fetched = posts.get(&created.id).await.unwrap();
+        let tags = fetched
+            .tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<HashSet<_>>();
+
+        assert!(matches!(result, Err(PostRepositoryError::PostUpdate)));
+        assert_eq!(fetched.title, "hello");
+        assert_eq!(tags, HashSet::from(["rust"]));
+    }
+
+    #[tokio::test]
+    async fn test_update_of_missing_post_creates_no_relations() {
+        let (db, posts) = repositories().await;
+
+        let result = posts
+            .update(
+                "missing",
+                new_post("hello", &["rust"]),
+                SavePostOptions::default(),
+                Precondition::ANY,
+            )
+            .await;
+        let relations = db
+            .query("SELECT count() FROM posts_tags GROUP ALL")
+            .await
+            .unwrap()
+            .take::<Vec<SurrealCountRecord>>(0)
+            .unwrap();
+
+        assert!(matches!(result, Err(PostRepositoryError::PostNotFound(id)) if id == "missing"));
+        assert!(relations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_dangling_relations() {
+        let (db, posts) = repositories().await;
+        let created = posts
+            .create(new_post("hello", &["rust"]), SavePostOptions::default())
+            .await
+            .unwrap();
+        let rust = created.tags[0].id.clone();
+
+        db.query("RELATE posts:gone->posts_tags->(<record>$tag_id)")
+            .query("RELATE (<record>$post_id)->posts_tags->tags:gone")
+            .bind(("tag_id", format!("tags:{rust}")))
+            .bind(("post_id", format!("posts:{}", created.id)))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let mut expected = vec![
+            PostTagRelation {
+                post: "gone".to_string(),
+                tag: rust,
+            },
+            PostTagRelation {
+                post: created.id.clone(),
+                tag: "gone".to_string(),
+            },
+        ];
+        expected.sort_by(|a, b| a.post.cmp(&b.post));
+
+        let found = posts.find_dangling_relations().await.unwrap();
+        let removed = posts.remove_dangling_relations().await.unwrap();
+
+        assert_eq!(found, expected);
+        assert_eq!(removed, expected);
+        assert!(posts.find_dangling_relations().await.unwrap().is_empty());
+        assert_eq!(
+            posts.get(&created.id).await.unwrap().tags[0].id,
+            created.tags[0].id
+        );
+    }
 }
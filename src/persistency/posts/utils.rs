@@ -1,16 +1,154 @@
-use super::models::SurrealPostEntityInput;
+use super::{
+    errors::PostRepositoryError,
+    models::{NewPost, SlugOwner, SurrealPostEntityInput},
+};
+use crate::utils::slug::{available_slug, slugify};
+use any_ascii::any_ascii;
+use rust_stemmers::{Algorithm, Stemmer};
+use std::collections::HashSet;
 
-pub fn create_post_entity(
-    title: String,
-    content: String,
-    now: chrono::DateTime<chrono::Utc>,
-) -> SurrealPostEntityInput {
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+/// Number of words kept on each side of the first highlighted term by `create_snippet`.
+const SNIPPET_RADIUS: usize = 12;
+
+pub fn create_post_entity(new_post: &NewPost) -> SurrealPostEntityInput {
     SurrealPostEntityInput {
-        title,
-        content,
-        created_at: surrealdb::sql::Datetime(now),
-        updated_at: surrealdb::sql::Datetime(now),
+        slug: new_post.slug.clone(),
+        title: new_post.title.clone(),
+        content: new_post.content.clone(),
+        author: None,
+        status: new_post.status,
+        publish_at: new_post.publish_at.map(Into::into),
+    }
+}
+
+/// Slug a post asks for, or the one derived from its title when it asks for none.
+pub fn slug_candidate(new_post: &NewPost) -> String {
+    new_post
+        .slug
+        .clone()
+        .unwrap_or_else(|| slugify(&new_post.title))
+}
+
+/// Slug to save post `post_id`, `None` when creating it, with. `owners` hold every slug starting
+/// with `candidate`. A requested slug is refused when another post goes or redirects by it, while
+/// a derived one gets a free suffix instead.
+pub fn pick_slug(
+    candidate: String,
+    requested: bool,
+    owners: &[SlugOwner],
+    post_id: Option<&str>,
+) -> Result<String, PostRepositoryError> {
+    let taken = owners
+        .iter()
+        .filter(|owner| Some(owner.post.as_str()) != post_id)
+        .map(|owner| owner.slug.clone())
+        .collect::<HashSet<_>>();
+
+    match requested {
+        true if taken.contains(&candidate) => {
+            Err(PostRepositoryError::SlugAlreadyExists(candidate))
+        }
+        true => Ok(candidate),
+        false => Ok(available_slug(&candidate, &taken)),
+    }
+}
+
+/// Splits a search query into the terms it is made of, analyzed the way the `posts_analyzer` of
+/// the database does: words and numbers are split apart, then lowercased, folded to ASCII and
+/// stemmed in English, so "Releases" and "released" both become "releas".
+pub fn search_terms(query: &str) -> Vec<String> {
+    words(query)
+        .into_iter()
+        .filter_map(|(start, end)| analyze(&query[start..end]))
+        .collect()
+}
+
+/// Wraps every word of `text` whose analyzed form is one of `terms` in highlight marks,
+/// returning the highlighted text and how many words were highlighted.
+pub fn highlight_terms(text: &str, terms: &[String]) -> (String, usize) {
+    let mut highlighted = String::with_capacity(text.len());
+    let mut matches = 0;
+    let mut copied = 0;
+
+    for (start, end) in words(text) {
+        let word = &text[start..end];
+        if analyze(word).is_some_and(|term| terms.contains(&term)) {
+            matches += 1;
+            highlighted.push_str(&text[copied..start]);
+            highlighted.push_str(HIGHLIGHT_START);
+            highlighted.push_str(word);
+            highlighted.push_str(HIGHLIGHT_END);
+            copied = end;
+        }
     }
+    highlighted.push_str(&text[copied..]);
+
+    (highlighted, matches)
+}
+
+/// Byte ranges of the runs of letters and of digits in `text`, as split by the `blank` and
+/// `class` tokenizers, leaving out punctuation.
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let class = |c: char| match c {
+        c if c.is_alphabetic() => Some(1),
+        c if c.is_numeric() => Some(2),
+        _ => None,
+    };
+
+    let mut words = Vec::new();
+    let mut current: Option<(usize, u8)> = None;
+    for (position, c) in text.char_indices() {
+        let next = class(c);
+        match current {
+            Some((start, run)) if Some(run) != next => {
+                words.push((start, position));
+                current = next.map(|run| (position, run));
+            }
+            None => current = next.map(|run| (position, run)),
+            _ => {}
+        }
+    }
+    if let Some((start, _)) = current {
+        words.push((start, text.len()));
+    }
+
+    words
+}
+
+/// Term a word is indexed by, `None` when nothing is left of it.
+fn analyze(word: &str) -> Option<String> {
+    let folded = any_ascii(&word.to_lowercase());
+    let term = Stemmer::create(Algorithm::English)
+        .stem(&folded)
+        .into_owned();
+
+    (!term.is_empty()).then_some(term)
+}
+
+/// Cuts highlighted text down to the words surrounding its first highlight, marking truncated
+/// ends with an ellipsis. Text without highlights is cut from its start.
+pub fn create_snippet(highlighted: &str) -> String {
+    let words = highlighted.split_whitespace().collect::<Vec<_>>();
+    let first_match = words
+        .iter()
+        .position(|word| word.contains(HIGHLIGHT_START))
+        .unwrap_or(0);
+
+    let start = first_match.saturating_sub(SNIPPET_RADIUS);
+    let end = (first_match + SNIPPET_RADIUS + 1).min(words.len());
+
+    let mut snippet = words[start..end].join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "… ");
+    }
+    if end < words.len() {
+        snippet.push_str(" …");
+    }
+
+    snippet
 }
 
 #[cfg(test)]
@@ -19,25 +157,103 @@ mod tests {
 
     #[test]
     fn test_create_post_entity() {
-        let now = chrono::Utc::now();
-        let post_entity = create_post_entity("title".to_string(), "content".to_string(), now);
+        let post_entity = create_post_entity(&NewPost {
+            slug: None,
+            title: "title".to_string(),
+            content: "content".to_string(),
+            tags: ["rust".to_string()].into(),
+            author: None,
+            status: None,
+            publish_at: None,
+        });
 
         assert_eq!(post_entity.title, "title");
         assert_eq!(post_entity.content, "content");
-        assert_eq!(post_entity.created_at, surrealdb::sql::Datetime(now));
-        assert_eq!(post_entity.updated_at, surrealdb::sql::Datetime(now));
 
         assert!(serde_json::to_string(&post_entity).is_ok());
 
         assert_eq!(
             serde_json::to_string(&post_entity).unwrap(),
             serde_json::to_string(&SurrealPostEntityInput {
+                slug: None,
                 title: "title".to_string(),
                 content: "content".to_string(),
-                created_at: surrealdb::sql::Datetime(now),
-                updated_at: surrealdb::sql::Datetime(now),
+                author: None,
+                status: None,
+                publish_at: None,
             })
             .unwrap()
         );
     }
+
+    #[test]
+    fn test_pick_slug() {
+        let owners = [
+            SlugOwner {
+                slug: "hello".to_string(),
+                post: "1".to_string(),
+            },
+            SlugOwner {
+                slug: "hello-2".to_string(),
+                post: "2".to_string(),
+            },
+        ];
+        let pick = |requested, post_id| pick_slug("hello".to_string(), requested, &owners, post_id);
+
+        assert_eq!(pick(false, None).unwrap(), "hello-3");
+        assert_eq!(pick(false, Some("1")).unwrap(), "hello");
+        assert_eq!(pick(true, Some("1")).unwrap(), "hello");
+        assert!(matches!(
+            pick(true, Some("2")),
+            Err(PostRepositoryError::SlugAlreadyExists(slug)) if slug == "hello"
+        ));
+    }
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms("Rust, release!"), vec!["rust", "releas"]);
+        assert_eq!(search_terms("Releases RELEASED"), vec!["releas", "releas"]);
+        assert_eq!(search_terms("Café rust2024"), vec!["cafe", "rust", "2024"]);
+        assert!(search_terms(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_highlight_terms() {
+        let terms = search_terms("rust");
+        let (highlighted, matches) = highlight_terms("Rust is rusty, rust!", &terms);
+
+        assert_eq!(
+            highlighted,
+            "<mark>Rust</mark> is rusty, <mark>rust</mark>!"
+        );
+        assert_eq!(matches, 2);
+
+        let terms = search_terms("shipping");
+        let (highlighted, matches) = highlight_terms("It ships, it shipped.", &terms);
+
+        assert_eq!(
+            highlighted,
+            "It <mark>ships</mark>, it <mark>shipped</mark>."
+        );
+        assert_eq!(matches, 2);
+    }
+
+    #[test]
+    fn test_create_snippet() {
+        let words = (0..40).map(|i| i.to_string()).collect::<Vec<_>>();
+        let mut text = words.clone();
+        text[20] = "<mark>match</mark>".to_string();
+
+        let snippet = create_snippet(&text.join(" "));
+
+        assert_eq!(
+            snippet,
+            format!(
+                "… {} <mark>match</mark> {} …",
+                words[8..20].join(" "),
+                words[21..33].join(" ")
+            )
+        );
+        assert_eq!(create_snippet("short text"), "short text");
+    }
 }
@@ -0,0 +1,273 @@
+use super::{
+    errors::PostRepositoryError,
+    models::{NewPost, SavePostOptions},
+};
+use crate::{
+    models::{Author, PostStatus, Tag},
+    persistency::{
+        models::Violation,
+        tags::validation::check_tag_name,
+        traits::{AuthorRepository, TagRepository},
+    },
+    utils::{
+        slug::{is_valid_slug, MAX_SLUG_LENGTH},
+        tag::tags_diff_set,
+    },
+};
+
+/// Maximum title length, in characters.
+pub const MAX_TITLE_LENGTH: usize = 256;
+/// Maximum content length, in characters.
+pub const MAX_CONTENT_LENGTH: usize = 65_536;
+/// Maximum number of tags on a single post.
+pub const MAX_TAGS: usize = 16;
+
+/// Checks the parts of a post that need no lookups.
+pub fn check_new_post(new_post: &NewPost) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    if let Some(slug) = &new_post.slug {
+        if slug.chars().count() > MAX_SLUG_LENGTH {
+            violations.push(Violation::new(
+                "slug",
+                "slug_too_long",
+                format!("Slug must be at most {MAX_SLUG_LENGTH} characters long"),
+            ));
+        } else if !is_valid_slug(slug) {
+            violations.push(Violation::new(
+                "slug",
+                "slug_invalid",
+                "Slug must be lowercase letters and digits, in words joined by hyphens".to_string(),
+            ));
+        }
+    }
+    if new_post.title.trim().is_empty() {
+        violations.push(Violation::new(
+            "title",
+            "title_empty",
+            "Title must not be empty".to_string(),
+        ));
+    }
+    if new_post.title.chars().count() > MAX_TITLE_LENGTH {
+        violations.push(Violation::new(
+            "title",
+            "title_too_long",
+            format!("Title must be at most {MAX_TITLE_LENGTH} characters long"),
+        ));
+    }
+    if new_post.content.chars().count() > MAX_CONTENT_LENGTH {
+        violations.push(Violation::new(
+            "content",
+            "content_too_long",
+            format!("Content must be at most {MAX_CONTENT_LENGTH} characters long"),
+        ));
+    }
+    if new_post.tags.len() > MAX_TAGS {
+        violations.push(Violation::new(
+            "tags",
+            "too_many_tags",
+            format!("A post can have at most {MAX_TAGS} tags"),
+        ));
+    }
+    match (new_post.status, new_post.publish_at) {
+        (Some(PostStatus::Scheduled), None) => violations.push(Violation::new(
+            "publish_at",
+            "publish_at_missing",
+            "Scheduled posts need a publish_at".to_string(),
+        )),
+        (status, Some(_)) if status != Some(PostStatus::Scheduled) => {
+            violations.push(Violation::new(
+                "publish_at",
+                "publish_at_unexpected",
+                "Only scheduled posts have a publish_at".to_string(),
+            ))
+        }
+        _ => {}
+    }
+
+    violations
+}
+
+/// Tags of a validated post.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedTags {
+    pub existing: Vec<Tag>,
+    /// Names of the tags to create along with the post, only ever set with
+    /// `SavePostOptions::create_missing_tags`.
+    pub missing: Vec<String>,
+}
+
+/// Tags and author of a validated post.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedPost {
+    pub tags: ResolvedTags,
+    /// Only set when the post names its author.
+    pub author: Option<Author>,
+}
+
+/// Validates a post about to be created or updated and resolves its tags and author. Every
+/// violation found is reported at once through `PostRepositoryError::InvalidPost`.
+pub async fn validate_new_post(
+    new_post: &NewPost,
+    options: SavePostOptions,
+    tags_repository: &impl TagRepository,
+    authors_repository: &impl AuthorRepository,
+) -> Result<ResolvedPost, PostRepositoryError> {
+    let mut violations = check_new_post(new_post);
+
+    let author = match &new_post.author {
+        Some(name) => {
+            let author = authors_repository
+                .find_in_names(vec![name.as_str()])
+                .await?
+                .into_iter()
+                .next();
+            if author.is_none() {
+                violations.push(Violation::new(
+                    "author",
+                    "author_not_found",
+                    format!("Author not found: {name}"),
+                ));
+            }
+
+            author
+        }
+        None => None,
+    };
+
+    let tags = tags_repository
+        .find_in_names(new_post.tags.iter().map(|tag| tag.as_str()).collect())
+        .await?;
+
+    let mut missing = tags_diff_set(tags.clone(), &new_post.tags)
+        .into_iter()
+        .collect::<Vec<_>>();
+    missing.sort();
+    if options.create_missing_tags {
+        violations.extend(
+            missing
+                .iter()
+                .filter_map(|name| check_tag_name("tags", name)),
+        );
+    } else {
+        violations.extend(
+            missing.drain(..).map(|name| {
+                Violation::new("tags", "tag_not_found", format!("Tag not found: {name}"))
+            }),
+        );
+    }
+
+    if !violations.is_empty() {
+        return Err(PostRepositoryError::InvalidPost(violations));
+    }
+
+    Ok(ResolvedPost {
+        tags: ResolvedTags {
+            existing: tags,
+            missing,
+        },
+        author,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_post(title: &str, content: &str, tags: usize) -> NewPost {
+        NewPost {
+            slug: None,
+            title: title.to_string(),
+            content: content.to_string(),
+            tags: (0..tags).map(|index| format!("tag-{index}")).collect(),
+            author: None,
+            status: None,
+            publish_at: None,
+        }
+    }
+
+    fn codes(violations: &[Violation]) -> Vec<&str> {
+        violations.iter().map(|violation| violation.code).collect()
+    }
+
+    #[test]
+    fn test_valid_post_has_no_violations() {
+        assert!(check_new_post(&new_post("title", "content", MAX_TAGS)).is_empty());
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let violations = check_new_post(&new_post(
+            &" ".repeat(MAX_TITLE_LENGTH + 1),
+            &"a".repeat(MAX_CONTENT_LENGTH + 1),
+            MAX_TAGS + 1,
+        ));
+
+        assert_eq!(
+            codes(&violations),
+            vec![
+                "title_empty",
+                "title_too_long",
+                "content_too_long",
+                "too_many_tags"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lengths_count_characters() {
+        let title = "é".repeat(MAX_TITLE_LENGTH);
+
+        assert!(check_new_post(&new_post(&title, "content", 0)).is_empty());
+    }
+
+    #[test]
+    fn test_only_scheduled_posts_have_a_publish_at() {
+        let scheduled = |status, publish_at| NewPost {
+            status,
+            publish_at,
+            ..new_post("title", "content", 0)
+        };
+        let now = chrono::Utc::now();
+
+        assert!(check_new_post(&scheduled(Some(PostStatus::Scheduled), Some(now))).is_empty());
+        assert_eq!(
+            codes(&check_new_post(&scheduled(
+                Some(PostStatus::Scheduled),
+                None
+            ))),
+            vec!["publish_at_missing"]
+        );
+        assert_eq!(
+            codes(&check_new_post(&scheduled(
+                Some(PostStatus::Draft),
+                Some(now)
+            ))),
+            vec!["publish_at_unexpected"]
+        );
+        assert_eq!(
+            codes(&check_new_post(&scheduled(None, Some(now)))),
+            vec!["publish_at_unexpected"]
+        );
+    }
+
+    #[test]
+    fn test_requested_slugs_are_checked() {
+        let with_slug = |slug: &str| NewPost {
+            slug: Some(slug.to_string()),
+            ..new_post("title", "content", 0)
+        };
+
+        assert!(check_new_post(&with_slug("hello-world")).is_empty());
+        assert_eq!(
+            codes(&check_new_post(&with_slug("Hello World"))),
+            vec!["slug_invalid"]
+        );
+        assert_eq!(
+            codes(&check_new_post(&with_slug(
+                &"a".repeat(MAX_SLUG_LENGTH + 1)
+            ))),
+            vec!["slug_too_long"]
+        );
+    }
+}
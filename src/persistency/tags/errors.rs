@@ -1,3 +1,4 @@
+use crate::persistency::models::{describe_violations, Violation};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,9 +9,21 @@ pub enum TagRepositoryError {
     #[error("Failed to create tag in the database")]
     TagCreation,
 
+    #[error("Invalid tag: {}", describe_violations(.0))]
+    InvalidTag(Vec<Violation>),
+
+    #[error("Tag already exists: {0}")]
+    TagAlreadyExists(String),
+
     #[error("Failed to list tags from the database")]
     TagListing,
 
+    #[error("Unsupported sort order: {0}")]
+    InvalidSort(String),
+
+    #[error("Invalid or expired cursor: {0}")]
+    InvalidCursor(String),
+
     #[error("Failed to count tags in the database")]
     TagCount,
 
@@ -20,6 +33,18 @@ pub enum TagRepositoryError {
     #[error("Failed to fetch tag from the database")]
     TagGet,
 
+    #[error("Tag not found: {0}")]
+    TagNotFound(String),
+
+    #[error("Tag {0} is used by {1} posts")]
+    TagInUse(String, usize),
+
+    #[error("Tag {0} was modified concurrently, it is now at revision {1}")]
+    RevisionMismatch(String, u64),
+
     #[error("Failed to update tag in the database")]
     TagUpdate,
+
+    #[error("Failed to delete tag from the database")]
+    TagDeletion,
 }
@@ -0,0 +1,281 @@
+use super::{
+    errors::TagRepositoryError,
+    models::{DeleteTagOptions, FindTagsResponse, MemoryTagEntity, NewTag, TagPatch, TagSort},
+    validation::validate_tag_name,
+};
+use crate::{
+    models::Tag,
+    persistency::{
+        memory::MemoryDatabase,
+        models::{next_page_cursor, FindAllOptions, Precondition},
+        traits::TagRepository,
+    },
+};
+
+#[derive(Clone)]
+pub struct MemoryTagsRepository {
+    db: MemoryDatabase,
+}
+
+impl MemoryTagsRepository {
+    pub fn new(db: MemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+impl TagRepository for MemoryTagsRepository {
+    async fn create(&self, new_tag: NewTag) -> Result<Tag, TagRepositoryError> {
+        validate_tag_name(&new_tag.name)?;
+        let mut tables = self.db.tables().write().await;
+
+        if tables.tags.values().any(|tag| tag.name == new_tag.name) {
+            return Err(TagRepositoryError::TagAlreadyExists(new_tag.name));
+        }
+
+        let tag = MemoryTagEntity {
+            id: self.db.generate_id(),
+            name: new_tag.name,
+            revision: 1,
+        };
+        tables.tags.insert(tag.id.clone(), tag.clone());
+
+        Ok(tag.into())
+    }
+
+    async fn find_all(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindTagsResponse, TagRepositoryError> {
+        let sort = TagSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+        let tables = self.db.tables().read().await;
+
+        let post_count = |tag: &MemoryTagEntity| {
+            tables
+                .posts
+                .values()
+                .filter(|post| post.deleted_at.is_none() && post.tag_ids.contains(&tag.id))
+                .count()
+        };
+
+        let mut tags = tables
+            .tags
+            .values()
+            .map(|tag| (post_count(tag), Tag::from(tag.clone())))
+            .collect::<Vec<_>>();
+        let key = |(post_count, tag): &(usize, Tag)| sort.field.key(tag, *post_count);
+        tags.sort_by(|a, b| {
+            sort.direction
+                .compare((&key(a), &a.1.id), (&key(b), &b.1.id))
+        });
+
+        let total = tags.len();
+        let offset = if cursor.is_some() { 0 } else { options.offset };
+        let mut tags = tags
+            .into_iter()
+            .filter(|tag| match &cursor {
+                Some((cursor_key, id)) => sort
+                    .direction
+                    .compare((&key(tag), &tag.1.id), (cursor_key, id))
+                    .is_gt(),
+                None => true,
+            })
+            .skip(offset)
+            .take(options.limit.saturating_add(1))
+            .collect();
+        let next_cursor = next_page_cursor(&mut tags, options.limit, |(post_count, tag)| {
+            sort.cursor(tag, *post_count)
+        });
+        let tags = tags.into_iter().map(|(_, tag)| tag).collect();
+
+        Ok(FindTagsResponse {
+            tags,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Tag>, TagRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let tags = tables
+            .tags
+            .values()
+            .filter(|tag| names.contains(&tag.name.as_str()))
+            .cloned()
+            .map(|tag| tag.into())
+            .collect();
+
+        Ok(tags)
+    }
+
+    async fn get(&self, name: &str) -> Result<Tag, TagRepositoryError> {
+        let tables = self.db.tables().read().await;
+
+        let tag = tables
+            .tags
+            .values()
+            .find(|tag| tag.name == name)
+            .cloned()
+            .ok_or_else(|| TagRepositoryError::TagNotFound(name.to_string()))?;
+
+        Ok(tag.into())
+    }
+
+    async fn update(
+        &self,
+        name: &str,
+        new_tag: NewTag,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError> {
+        validate_tag_name(&new_tag.name)?;
+
+        let mut tables = self.db.tables().write().await;
+
+        if tables
+            .tags
+            .values()
+            .any(|tag| tag.name == new_tag.name && tag.name != name)
+        {
+            return Err(TagRepositoryError::TagAlreadyExists(new_tag.name));
+        }
+
+        let tag = tables
+            .tags
+            .values_mut()
+            .find(|tag| tag.name == name)
+            .ok_or_else(|| TagRepositoryError::TagNotFound(name.to_string()))?;
+        if !precondition.allows(tag.revision) {
+            return Err(TagRepositoryError::RevisionMismatch(
+                name.to_string(),
+                tag.revision,
+            ));
+        }
+        let renamed = tag.name != new_tag.name;
+        tag.name = new_tag.name;
+        tag.revision += 1;
+        let tag = tag.clone();
+
+        if renamed {
+            tables.touch_posts(|post| post.tag_ids.contains(&tag.id));
+        }
+
+        Ok(tag.into())
+    }
+
+    async fn patch(
+        &self,
+        name: &str,
+        patch: TagPatch,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError> {
+        match patch.name {
+            Some(new_name) => {
+                self.update(name, NewTag { name: new_name }, precondition)
+                    .await
+            }
+            None => {
+                let tag = self.get(name).await?;
+                if !precondition.allows(tag.revision) {
+                    return Err(TagRepositoryError::RevisionMismatch(
+                        name.to_string(),
+                        tag.revision,
+                    ));
+                }
+
+                Ok(tag)
+            }
+        }
+    }
+
+    async fn delete(
+        &self,
+        name: &str,
+        options: DeleteTagOptions,
+        precondition: Precondition,
+    ) -> Result<(), TagRepositoryError> {
+        let mut tables = self.db.tables().write().await;
+
+        let tag = tables
+            .tags
+            .values()
+            .find(|tag| tag.name == name)
+            .ok_or_else(|| TagRepositoryError::TagNotFound(name.to_string()))?;
+        if !precondition.allows(tag.revision) {
+            return Err(TagRepositoryError::RevisionMismatch(
+                name.to_string(),
+                tag.revision,
+            ));
+        }
+        let id = tag.id.clone();
+        // Posts in the trash keep their tags, so they still count as uses.
+        let uses = tables
+            .posts
+            .values()
+            .filter(|post| post.tag_ids.contains(&id))
+            .count();
+        if uses > 0 && !options.force {
+            return Err(TagRepositoryError::TagInUse(name.to_string(), uses));
+        }
+        tables.tags.remove(&id);
+        tables.touch_posts(|post| post.tag_ids.contains(&id));
+        for post in tables.posts.values_mut() {
+            post.tag_ids.retain(|tag_id| *tag_id != id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_tag(name: &str) -> NewTag {
+        NewTag {
+            name: name.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_duplicate_names() {
+        let repository = MemoryTagsRepository::new(MemoryDatabase::default());
+
+        repository.create(new_tag("rust")).await.unwrap();
+        let result = repository.create(new_tag("rust")).await;
+
+        assert!(
+            matches!(result, Err(TagRepositoryError::TagAlreadyExists(name)) if name == "rust")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_all_orders_by_name() {
+        let repository = MemoryTagsRepository::new(MemoryDatabase::default());
+
+        for name in ["zig", "c", "rust"] {
+            repository.create(new_tag(name)).await.unwrap();
+        }
+
+        let response = repository
+            .find_all(FindAllOptions {
+                limit: 2,
+                offset: 1,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let names = response
+            .tags
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["rust", "zig"]);
+        assert_eq!(response.total, 3);
+    }
+}
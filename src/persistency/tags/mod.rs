@@ -1,3 +1,5 @@
 pub mod errors;
+pub mod memory_tags_repository;
 pub mod models;
 pub mod surrealdb_tags_repository;
+pub mod validation;
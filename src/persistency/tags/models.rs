@@ -1,11 +1,124 @@
-use crate::models::Tag;
+use super::errors::TagRepositoryError;
+use crate::{
+    models::Tag,
+    persistency::models::{parse_sort, Cursor, SortDirection, SortKey},
+    utils::patch::required_member,
+};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewTag {
     pub name: String,
 }
 
+/// JSON Merge Patch (RFC 7396) of a tag. Its `name` cannot be removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagPatch {
+    #[serde(default, deserialize_with = "required_member")]
+    pub name: Option<String>,
+}
+
+/// How to delete a tag that is still attached to posts, e.g. `?force=true`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteTagOptions {
+    /// Detaches the tag from its posts and deletes it, instead of refusing to.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagSortField {
+    Name,
+    PostCount,
+}
+
+impl TagSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagSortField::Name => "name",
+            TagSortField::PostCount => "post_count",
+        }
+    }
+
+    pub fn key(&self, tag: &Tag, post_count: usize) -> SortKey {
+        match self {
+            TagSortField::Name => SortKey::Text(tag.name.clone()),
+            TagSortField::PostCount => SortKey::Count(post_count),
+        }
+    }
+
+    pub fn parse_cursor_key(&self, key: &str) -> Option<SortKey> {
+        match self {
+            TagSortField::Name => Some(SortKey::Text(key.to_string())),
+            TagSortField::PostCount => key.parse().ok().map(SortKey::Count),
+        }
+    }
+}
+
+/// Validated tag listing order, parsed from `FindAllOptions::sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagSort {
+    pub field: TagSortField,
+    pub direction: SortDirection,
+}
+
+impl Default for TagSort {
+    fn default() -> Self {
+        Self {
+            field: TagSortField::Name,
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+impl TryFrom<Option<&str>> for TagSort {
+    type Error = TagRepositoryError;
+
+    fn try_from(sort: Option<&str>) -> Result<Self, Self::Error> {
+        let Some(sort) = sort else {
+            return Ok(Self::default());
+        };
+
+        let (field, direction) = parse_sort(sort);
+        let field = [TagSortField::Name, TagSortField::PostCount]
+            .into_iter()
+            .find(|candidate| candidate.as_str() == field)
+            .ok_or_else(|| TagRepositoryError::InvalidSort(sort.to_string()))?;
+
+        Ok(Self { field, direction })
+    }
+}
+
+impl fmt::Display for TagSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.direction.sort_prefix(), self.field.as_str())
+    }
+}
+
+impl TagSort {
+    pub fn cursor(&self, tag: &Tag, post_count: usize) -> Cursor {
+        Cursor {
+            sort: self.to_string(),
+            key: self.field.key(tag, post_count).to_cursor_key(),
+            id: tag.id.clone(),
+        }
+    }
+
+    /// Decodes a client supplied cursor into the sort key and id it points at, rejecting cursors
+    /// issued for another order.
+    pub fn decode_cursor(&self, cursor: &str) -> Result<(SortKey, String), TagRepositoryError> {
+        Cursor::decode(cursor)
+            .filter(|decoded| decoded.sort == self.to_string())
+            .and_then(|decoded| {
+                let key = self.field.parse_cursor_key(&decoded.key)?;
+
+                Some((key, decoded.id))
+            })
+            .ok_or_else(|| TagRepositoryError::InvalidCursor(cursor.to_string()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurrealTagEntityInput {
     pub name: String,
@@ -21,6 +134,25 @@ impl From<NewTag> for SurrealTagEntityInput {
 pub struct SurrealTagEntityOutput {
     pub id: String,
     pub name: String,
+    pub revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurrealTagEntityWithCountOutput {
+    pub id: String,
+    pub name: String,
+    pub revision: u64,
+    pub post_count: usize,
+}
+
+impl From<SurrealTagEntityWithCountOutput> for Tag {
+    fn from(tag: SurrealTagEntityWithCountOutput) -> Self {
+        Self {
+            id: tag.id,
+            name: tag.name,
+            revision: tag.revision,
+        }
+    }
 }
 
 impl From<SurrealTagEntityOutput> for Tag {
@@ -28,6 +160,24 @@ impl From<SurrealTagEntityOutput> for Tag {
         Self {
             id: tag.id,
             name: tag.name,
+            revision: tag.revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryTagEntity {
+    pub id: String,
+    pub name: String,
+    pub revision: u64,
+}
+
+impl From<MemoryTagEntity> for Tag {
+    fn from(tag: MemoryTagEntity) -> Self {
+        Self {
+            id: tag.id,
+            name: tag.name,
+            revision: tag.revision,
         }
     }
 }
@@ -36,4 +186,5 @@ impl From<SurrealTagEntityOutput> for Tag {
 pub struct FindTagsResponse {
     pub tags: Vec<Tag>,
     pub total: usize,
+    pub next_cursor: Option<String>,
 }
@@ -1,15 +1,22 @@
 use super::{
     errors::TagRepositoryError,
-    models::{FindTagsResponse, NewTag, SurrealTagEntityInput, SurrealTagEntityOutput},
+    models::{
+        DeleteTagOptions, FindTagsResponse, NewTag, SurrealTagEntityInput, SurrealTagEntityOutput,
+        SurrealTagEntityWithCountOutput, TagPatch, TagSort, TagSortField,
+    },
+    validation::validate_tag_name,
 };
 use crate::{
     models::Tag,
     persistency::{
-        models::{FindAllOptions, SurrealCountRecord},
+        models::{
+            next_page_cursor, FindAllOptions, Precondition, SortKey, SurrealCountRecord,
+            SurrealRecord,
+        },
         traits::TagRepository,
     },
 };
-use surrealdb::Surreal;
+use surrealdb::{error::Db, Response, Surreal};
 
 #[derive(Clone)]
 pub struct SurrealdbTagsRepository {
@@ -22,11 +29,41 @@ impl SurrealdbTagsRepository {
     }
 }
 
+fn is_unique_name_violation(error: &surrealdb::Error) -> bool {
+    matches!(error, surrealdb::Error::Db(Db::IndexExists { index, .. }) if index == "unique_name")
+}
+
+/// Whether a statement of `response` failed because its tag took a name in use. Transactions
+/// only report the other statements as not executed, so every error is looked at.
+fn has_unique_name_violation(response: &mut Response) -> bool {
+    response
+        .take_errors()
+        .values()
+        .any(is_unique_name_violation)
+}
+
+/// Condition selecting the tags listed after `(cursor_key, cursor_id)` in `sort` order.
+fn keyset_condition(sort: TagSort) -> String {
+    let operator = sort.direction.keyset_operator();
+    let (field, value) = match sort.field {
+        TagSortField::Name => ("name", "$cursor_key"),
+        TagSortField::PostCount => (
+            "count(<-posts_tags<-(posts WHERE deleted_at = NONE))",
+            "<int>$cursor_key",
+        ),
+    };
+
+    format!(
+        "({field} {operator} {value} OR ({field} = {value} AND string::split(<string>id, ':')[1] > $cursor_id))"
+    )
+}
+
 impl SurrealdbTagsRepository {
     async fn register_tag_in_db(
         &self,
         tag_entity: SurrealTagEntityInput,
     ) -> Result<SurrealTagEntityOutput, TagRepositoryError> {
+        let name = tag_entity.name.clone();
         let tag = self
             .db
             .query(include_str!("./queries/create_tag.surql"))
@@ -34,7 +71,13 @@ impl SurrealdbTagsRepository {
             .await
             .map_err(TagRepositoryError::Database)?
             .take::<Vec<SurrealTagEntityOutput>>(0)
-            .map_err(|_| TagRepositoryError::TagCreation)?
+            .map_err(|e| {
+                if is_unique_name_violation(&e) {
+                    TagRepositoryError::TagAlreadyExists(name)
+                } else {
+                    TagRepositoryError::TagCreation
+                }
+            })?
             .first()
             .cloned()
             .ok_or(TagRepositoryError::TagCreation)?;
@@ -46,15 +89,28 @@ impl SurrealdbTagsRepository {
         &self,
         limit: usize,
         offset: usize,
-    ) -> Result<Vec<SurrealTagEntityOutput>, TagRepositoryError> {
+        sort: TagSort,
+        cursor: Option<(SortKey, String)>,
+    ) -> Result<Vec<SurrealTagEntityWithCountOutput>, TagRepositoryError> {
+        let order = format!("{} {}", sort.field.as_str(), sort.direction.as_surql());
+        let (keyset, offset, cursor_key, cursor_id) = match cursor {
+            Some((key, id)) => (keyset_condition(sort), 0, key.to_cursor_key(), id),
+            None => ("true".to_string(), offset, String::new(), String::new()),
+        };
         let tags = self
             .db
-            .query(include_str!("./queries/list_tags.surql"))
+            .query(
+                include_str!("./queries/list_tags.surql")
+                    .replace("{order}", &order)
+                    .replace("{keyset}", &keyset),
+            )
             .bind(("limit", limit))
             .bind(("offset", offset))
+            .bind(("cursor_key", cursor_key))
+            .bind(("cursor_id", cursor_id))
             .await
             .map_err(TagRepositoryError::Database)?
-            .take::<Vec<SurrealTagEntityOutput>>(0)
+            .take::<Vec<SurrealTagEntityWithCountOutput>>(0)
             .map_err(|_| TagRepositoryError::TagListing)?;
 
         Ok(tags)
@@ -105,38 +161,109 @@ impl SurrealdbTagsRepository {
             .map_err(|_| TagRepositoryError::TagGet)?
             .first()
             .cloned()
-            .ok_or(TagRepositoryError::TagGet)?;
+            .ok_or_else(|| TagRepositoryError::TagNotFound(name.to_string()))?;
 
         Ok(tag)
     }
 
+    /// Error for a conditional write that matched no tag: either it does not exist or it is no
+    /// longer at one of the expected revisions.
+    async fn unmatched_write_error(&self, name: &str) -> TagRepositoryError {
+        match self.get_tag_in_db(name).await {
+            Ok(tag) => TagRepositoryError::RevisionMismatch(name.to_string(), tag.revision),
+            Err(error) => error,
+        }
+    }
+
     async fn update_tag_in_db(
         &self,
         name: &str,
         tag_entity: SurrealTagEntityInput,
+        precondition: &Precondition,
     ) -> Result<SurrealTagEntityOutput, TagRepositoryError> {
-        let tag = self
+        let new_name = tag_entity.name.clone();
+        let mut response = self
             .db
             .query(include_str!("./queries/update_tag.surql"))
             .bind(("tag_old_name", name))
             .bind(("tag_new_name", tag_entity.name))
+            .bind(("revisions", precondition.revisions()))
             .await
-            .map_err(TagRepositoryError::Database)?
-            .take::<Vec<SurrealTagEntityOutput>>(0)
-            .map_err(|_| TagRepositoryError::TagUpdate)?
+            .map_err(TagRepositoryError::Database)?;
+        let tag = response
+            .take::<Vec<SurrealTagEntityOutput>>(response.num_statements() - 1)
+            .map_err(|_| {
+                if has_unique_name_violation(&mut response) {
+                    TagRepositoryError::TagAlreadyExists(new_name)
+                } else {
+                    TagRepositoryError::TagUpdate
+                }
+            })?
             .first()
-            .ok_or(TagRepositoryError::TagUpdate)?
-            .clone();
+            .cloned();
 
-        Ok(tag)
+        match tag {
+            Some(tag) => Ok(tag),
+            None => Err(self.unmatched_write_error(name).await),
+        }
+    }
+
+    /// Error for a delete that matched no tag: besides the reasons of `unmatched_write_error`,
+    /// the tag may still be used by posts and the delete not forced.
+    async fn unmatched_delete_error(
+        &self,
+        name: &str,
+        options: DeleteTagOptions,
+        precondition: &Precondition,
+    ) -> TagRepositoryError {
+        let tag = self
+            .db
+            .query(include_str!("./queries/get_tag_usage.surql"))
+            .bind(("tag_name", name))
+            .await
+            .map_err(TagRepositoryError::Database)
+            .and_then(|mut response| {
+                response
+                    .take::<Vec<SurrealTagEntityWithCountOutput>>(0)
+                    .map_err(|_| TagRepositoryError::TagGet)
+            })
+            .map(|tags| tags.first().cloned());
+
+        match tag {
+            Ok(Some(tag)) if !precondition.allows(tag.revision) => {
+                TagRepositoryError::RevisionMismatch(name.to_string(), tag.revision)
+            }
+            Ok(Some(tag)) if tag.post_count > 0 && !options.force => {
+                TagRepositoryError::TagInUse(name.to_string(), tag.post_count)
+            }
+            Ok(_) => TagRepositoryError::TagNotFound(name.to_string()),
+            Err(error) => error,
+        }
     }
 
-    async fn delete_tag_in_db(&self, name: &str) -> Result<(), TagRepositoryError> {
-        self.db
+    async fn delete_tag_in_db(
+        &self,
+        name: &str,
+        options: DeleteTagOptions,
+        precondition: &Precondition,
+    ) -> Result<(), TagRepositoryError> {
+        let mut response = self
+            .db
             .query(include_str!("./queries/delete_tag.surql"))
             .bind(("tag_name", name))
+            .bind(("force", options.force))
+            .bind(("revisions", precondition.revisions()))
             .await
             .map_err(TagRepositoryError::Database)?;
+        let deleted = response
+            .take::<Vec<SurrealRecord>>(response.num_statements() - 1)
+            .map_err(|_| TagRepositoryError::TagDeletion)?;
+
+        if deleted.is_empty() {
+            return Err(self
+                .unmatched_delete_error(name, options, precondition)
+                .await);
+        }
 
         Ok(())
     }
@@ -144,6 +271,7 @@ impl SurrealdbTagsRepository {
 
 impl TagRepository for SurrealdbTagsRepository {
     async fn create(&self, new_tag: NewTag) -> Result<Tag, TagRepositoryError> {
+        validate_tag_name(&new_tag.name)?;
         let tag_entity = SurrealTagEntityInput::from(new_tag);
 
         let created_tag = self.register_tag_in_db(tag_entity).await?.into();
@@ -155,16 +283,36 @@ impl TagRepository for SurrealdbTagsRepository {
         &self,
         options: FindAllOptions,
     ) -> Result<FindTagsResponse, TagRepositoryError> {
-        let tags = self
-            .list_tags_in_db(options.limit, options.offset)
+        let sort = TagSort::try_from(options.sort.as_deref())?;
+        let cursor = options
+            .cursor
+            .as_deref()
+            .map(|cursor| sort.decode_cursor(cursor))
+            .transpose()?;
+
+        let mut tags = self
+            .list_tags_in_db(
+                options.limit.saturating_add(1),
+                options.offset,
+                sort,
+                cursor,
+            )
             .await?
             .into_iter()
-            .map(|tag| tag.into())
+            .map(|tag| (tag.post_count, Tag::from(tag)))
             .collect();
+        let next_cursor = next_page_cursor(&mut tags, options.limit, |(post_count, tag)| {
+            sort.cursor(tag, *post_count)
+        });
+        let tags = tags.into_iter().map(|(_, tag)| tag).collect();
 
         let total = self.count_tags_in_db().await?;
 
-        Ok(FindTagsResponse { tags, total })
+        Ok(FindTagsResponse {
+            tags,
+            total,
+            next_cursor,
+        })
     }
 
     async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Tag>, TagRepositoryError> {
@@ -184,16 +332,56 @@ impl TagRepository for SurrealdbTagsRepository {
         Ok(tag)
     }
 
-    async fn update(&self, name: &str, new_tag: NewTag) -> Result<Tag, TagRepositoryError> {
+    async fn update(
+        &self,
+        name: &str,
+        new_tag: NewTag,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError> {
+        validate_tag_name(&new_tag.name)?;
+
         let tag_entity = SurrealTagEntityInput::from(new_tag);
 
-        let updated_tag = self.update_tag_in_db(name, tag_entity).await?.into();
+        let updated_tag = self
+            .update_tag_in_db(name, tag_entity, &precondition)
+            .await?
+            .into();
 
         Ok(updated_tag)
     }
 
-    async fn delete(&self, name: &str) -> Result<(), TagRepositoryError> {
-        self.delete_tag_in_db(name).await?;
+    async fn patch(
+        &self,
+        name: &str,
+        patch: TagPatch,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError> {
+        match patch.name {
+            Some(new_name) => {
+                self.update(name, NewTag { name: new_name }, precondition)
+                    .await
+            }
+            None => {
+                let tag = self.get(name).await?;
+                if !precondition.allows(tag.revision) {
+                    return Err(TagRepositoryError::RevisionMismatch(
+                        name.to_string(),
+                        tag.revision,
+                    ));
+                }
+
+                Ok(tag)
+            }
+        }
+    }
+
+    async fn delete(
+        &self,
+        name: &str,
+        options: DeleteTagOptions,
+        precondition: Precondition,
+    ) -> Result<(), TagRepositoryError> {
+        self.delete_tag_in_db(name, options, &precondition).await?;
 
         Ok(())
     }
@@ -0,0 +1,62 @@
+use super::errors::TagRepositoryError;
+use crate::persistency::models::Violation;
+
+/// Maximum tag name length, in characters.
+pub const MAX_TAG_NAME_LENGTH: usize = 64;
+
+/// Problem with `name` as a tag name, reported on `field`. Names cannot hold commas, which
+/// separate tags in listing filters.
+pub fn check_tag_name(field: &'static str, name: &str) -> Option<Violation> {
+    if name.trim().is_empty() {
+        Some(Violation::new(
+            field,
+            "tag_name_empty",
+            "Tag name must not be empty".to_string(),
+        ))
+    } else if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        Some(Violation::new(
+            field,
+            "tag_name_too_long",
+            format!("Tag name must be at most {MAX_TAG_NAME_LENGTH} characters long: {name}"),
+        ))
+    } else if name.trim() != name || name.chars().any(|c| c == ',' || c.is_control()) {
+        Some(Violation::new(
+            field,
+            "tag_name_invalid",
+            format!("Tag name must not hold commas or control characters, nor start or end with spaces: {name}"),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Validates the name of a tag about to be created or renamed.
+pub fn validate_tag_name(name: &str) -> Result<(), TagRepositoryError> {
+    match check_tag_name("name", name) {
+        Some(violation) => Err(TagRepositoryError::InvalidTag(vec![violation])),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(name: &str) -> Option<&'static str> {
+        check_tag_name("name", name).map(|violation| violation.code)
+    }
+
+    #[test]
+    fn test_check_tag_name() {
+        assert_eq!(code("rust"), None);
+        assert_eq!(code("rust 2024"), None);
+        assert_eq!(code(" "), Some("tag_name_empty"));
+        assert_eq!(
+            code(&"a".repeat(MAX_TAG_NAME_LENGTH + 1)),
+            Some("tag_name_too_long")
+        );
+        assert_eq!(code("rust,go"), Some("tag_name_invalid"));
+        assert_eq!(code(" rust"), Some("tag_name_invalid"));
+        assert_eq!(code("rust\n"), Some("tag_name_invalid"));
+    }
+}
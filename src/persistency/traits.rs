@@ -1,25 +1,108 @@
 use super::{
-    models::FindAllOptions,
+    api_keys::{errors::ApiKeyRepositoryError, models::NewApiKey},
+    authors::{
+        errors::AuthorRepositoryError,
+        models::{AuthorPatch, FindAuthorsResponse, NewAuthor},
+    },
+    models::{FindAllOptions, Precondition},
     posts::{
         errors::PostRepositoryError,
-        models::{FindPostsResponse, NewPost},
+        models::{
+            FindDeletedPostsResponse, FindPostRevisionsResponse, FindPostsResponse, NewPost,
+            PostPatch, PostTagRelation, PostsFilter, SavePostOptions, SearchPostsResponse,
+            SlugLookup,
+        },
     },
     tags::{
         errors::TagRepositoryError,
-        models::{FindTagsResponse, NewTag},
+        models::{DeleteTagOptions, FindTagsResponse, NewTag, TagPatch},
     },
 };
-use crate::models::{Post, Tag};
+use crate::models::{ApiKey, Author, Post, PostRevision, Tag};
+use chrono::{DateTime, Utc};
 
 pub trait PostRepository {
-    async fn create(&self, new_post: NewPost) -> Result<Post, PostRepositoryError>;
+    async fn create(
+        &self,
+        new_post: NewPost,
+        options: SavePostOptions,
+    ) -> Result<Post, PostRepositoryError>;
     async fn find_all(
         &self,
         options: FindAllOptions,
+        filter: PostsFilter,
     ) -> Result<FindPostsResponse, PostRepositoryError>;
+    async fn search(
+        &self,
+        query: &str,
+        options: FindAllOptions,
+    ) -> Result<SearchPostsResponse, PostRepositoryError>;
     async fn get(&self, id: &str) -> Result<Post, PostRepositoryError>;
-    async fn update(&self, id: &str, new_post: NewPost) -> Result<Post, PostRepositoryError>;
-    async fn delete(&self, id: &str) -> Result<(), PostRepositoryError>;
+    async fn get_by_slug(&self, slug: &str) -> Result<SlugLookup, PostRepositoryError>;
+    async fn update(
+        &self,
+        id: &str,
+        new_post: NewPost,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn patch(
+        &self,
+        id: &str,
+        patch: PostPatch,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn add_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn remove_tags(
+        &self,
+        id: &str,
+        tags: Vec<String>,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn revisions(
+        &self,
+        id: &str,
+        options: FindAllOptions,
+    ) -> Result<FindPostRevisionsResponse, PostRepositoryError>;
+    async fn get_revision(
+        &self,
+        id: &str,
+        revision: u64,
+    ) -> Result<PostRevision, PostRepositoryError>;
+    async fn restore_revision(
+        &self,
+        id: &str,
+        revision: u64,
+        options: SavePostOptions,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn delete(&self, id: &str, precondition: Precondition)
+        -> Result<(), PostRepositoryError>;
+    async fn find_deleted(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindDeletedPostsResponse, PostRepositoryError>;
+    async fn restore_deleted(
+        &self,
+        id: &str,
+        precondition: Precondition,
+    ) -> Result<Post, PostRepositoryError>;
+    async fn purge(&self, id: &str, precondition: Precondition) -> Result<(), PostRepositoryError>;
+    async fn purge_deleted_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, PostRepositoryError>;
+    async fn publish_scheduled(&self, now: DateTime<Utc>)
+        -> Result<Vec<Post>, PostRepositoryError>;
+    async fn find_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError>;
+    async fn remove_dangling_relations(&self) -> Result<Vec<PostTagRelation>, PostRepositoryError>;
 }
 
 pub trait TagRepository {
@@ -30,6 +113,57 @@ pub trait TagRepository {
     ) -> Result<FindTagsResponse, TagRepositoryError>;
     async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Tag>, TagRepositoryError>;
     async fn get(&self, name: &str) -> Result<Tag, TagRepositoryError>;
-    async fn update(&self, name: &str, new_tag: NewTag) -> Result<Tag, TagRepositoryError>;
-    async fn delete(&self, name: &str) -> Result<(), TagRepositoryError>;
+    async fn update(
+        &self,
+        name: &str,
+        new_tag: NewTag,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError>;
+    async fn patch(
+        &self,
+        name: &str,
+        patch: TagPatch,
+        precondition: Precondition,
+    ) -> Result<Tag, TagRepositoryError>;
+    async fn delete(
+        &self,
+        name: &str,
+        options: DeleteTagOptions,
+        precondition: Precondition,
+    ) -> Result<(), TagRepositoryError>;
+}
+
+pub trait AuthorRepository {
+    async fn create(&self, new_author: NewAuthor) -> Result<Author, AuthorRepositoryError>;
+    async fn find_all(
+        &self,
+        options: FindAllOptions,
+    ) -> Result<FindAuthorsResponse, AuthorRepositoryError>;
+    async fn find_in_names(&self, names: Vec<&str>) -> Result<Vec<Author>, AuthorRepositoryError>;
+    async fn get(&self, name: &str) -> Result<Author, AuthorRepositoryError>;
+    async fn update(
+        &self,
+        name: &str,
+        new_author: NewAuthor,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError>;
+    async fn patch(
+        &self,
+        name: &str,
+        patch: AuthorPatch,
+        precondition: Precondition,
+    ) -> Result<Author, AuthorRepositoryError>;
+    async fn delete(
+        &self,
+        name: &str,
+        precondition: Precondition,
+    ) -> Result<(), AuthorRepositoryError>;
+}
+
+pub trait ApiKeyRepository {
+    async fn create(&self, new_api_key: NewApiKey) -> Result<ApiKey, ApiKeyRepositoryError>;
+    /// Every API key, by name.
+    async fn find_all(&self) -> Result<Vec<ApiKey>, ApiKeyRepositoryError>;
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError>;
+    async fn delete(&self, name: &str) -> Result<(), ApiKeyRepositoryError>;
 }
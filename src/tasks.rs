@@ -0,0 +1,126 @@
+use crate::persistency::{posts::errors::PostRepositoryError, traits::PostRepository};
+use chrono::{DateTime, Duration, Utc};
+use tracing::{debug, error, info};
+
+/// How often the trash is checked for expired posts.
+pub const TRASH_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// How often scheduled posts are checked for being due.
+pub const PUBLISH_SCHEDULED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Purges the posts deleted more than `retention` before `now`.
+async fn purge_expired_posts(
+    post_repository: &impl PostRepository,
+    retention: Duration,
+    now: DateTime<Utc>,
+) -> Result<usize, PostRepositoryError> {
+    match now.checked_sub_signed(retention) {
+        Some(cutoff) => post_repository.purge_deleted_before(cutoff).await,
+        None => Ok(0),
+    }
+}
+
+/// Purges the posts kept in the trash for longer than `retention`, on startup and then every
+/// `TRASH_PURGE_INTERVAL`, for as long as the daemon runs.
+pub async fn purge_expired_trash(post_repository: impl PostRepository, retention: Duration) {
+    let mut interval = actix_web::rt::time::interval(TRASH_PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match purge_expired_posts(&post_repository, retention, Utc::now()).await {
+            Ok(0) => debug!("No expired posts in the trash"),
+            Ok(purged) => info!("Purged {purged} expired posts from the trash"),
+            Err(e) => error!("Failed to purge expired posts from the trash: {e}"),
+        }
+    }
+}
+
+/// Publishes scheduled posts once their `publish_at` is reached, checking every
+/// `PUBLISH_SCHEDULED_INTERVAL` for as long as the daemon runs.
+pub async fn publish_scheduled_posts(post_repository: impl PostRepository) {
+    let mut interval = actix_web::rt::time::interval(PUBLISH_SCHEDULED_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match post_repository.publish_scheduled(Utc::now()).await {
+            Ok(published) => {
+                for post in published {
+                    info!("Published scheduled post {}", post.id);
+                }
+            }
+            Err(e) => error!("Failed to publish scheduled posts: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistency::{
+        authors::memory_authors_repository::MemoryAuthorsRepository,
+        memory::MemoryDatabase,
+        models::{FindAllOptions, Precondition},
+        posts::{
+            memory_posts_repository::MemoryPostsRepository,
+            models::{NewPost, SavePostOptions},
+        },
+        tags::memory_tags_repository::MemoryTagsRepository,
+    };
+
+    #[tokio::test]
+    async fn test_purges_posts_deleted_before_the_retention() {
+        let db = MemoryDatabase::default();
+        let posts = MemoryPostsRepository::new(
+            db.clone(),
+            MemoryTagsRepository::new(db.clone()),
+            MemoryAuthorsRepository::new(db),
+        );
+        let post = posts
+            .create(
+                NewPost {
+                    slug: None,
+                    title: "hello".to_string(),
+                    content: "world".to_string(),
+                    tags: Default::default(),
+                    author: None,
+                    status: None,
+                    publish_at: None,
+                },
+                SavePostOptions::default(),
+            )
+            .await
+            .unwrap();
+        posts.delete(&post.id, Precondition::ANY).await.unwrap();
+        let retention = Duration::days(30);
+
+        let kept = purge_expired_posts(&posts, retention, Utc::now() + Duration::days(29))
+            .await
+            .unwrap();
+        let purged = purge_expired_posts(&posts, retention, Utc::now() + Duration::days(31))
+            .await
+            .unwrap();
+        let trash = posts.find_deleted(FindAllOptions::default()).await.unwrap();
+
+        assert_eq!(kept, 0);
+        assert_eq!(purged, 1);
+        assert_eq!(trash.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retention_longer_than_time_itself_purges_nothing() {
+        let db = MemoryDatabase::default();
+        let posts = MemoryPostsRepository::new(
+            db.clone(),
+            MemoryTagsRepository::new(db.clone()),
+            MemoryAuthorsRepository::new(db),
+        );
+
+        let purged = purge_expired_posts(&posts, Duration::max_value(), Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(purged, 0);
+    }
+}
@@ -0,0 +1,33 @@
+/// Line by line diff of `old` and `new`, every line prefixed with ` ` when kept, `-` when
+/// removed or `+` when added, as in the body of a unified diff.
+pub fn line_diff(old: &str, new: &str) -> String {
+    diff::lines(old, new)
+        .into_iter()
+        .map(|line| match line {
+            diff::Result::Both(line, _) => format!(" {line}"),
+            diff::Result::Left(line) => format!("-{line}"),
+            diff::Result::Right(line) => format!("+{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_texts_keep_every_line() {
+        assert_eq!(line_diff("a\nb", "a\nb"), " a\n b");
+    }
+
+    #[test]
+    fn test_changed_lines_are_removed_then_added() {
+        assert_eq!(line_diff("a\nb\nc", "a\nd\nc"), " a\n-b\n+d\n c");
+    }
+
+    #[test]
+    fn test_diff_from_empty_text_adds_every_line() {
+        assert_eq!(line_diff("", "a\nb"), "+a\n+b");
+    }
+}
@@ -1 +1,6 @@
+pub mod diff;
+pub mod patch;
+pub mod query;
+pub mod slug;
 pub mod tag;
+pub mod unix;
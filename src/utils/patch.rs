@@ -0,0 +1,58 @@
+//! Members of JSON Merge Patch (RFC 7396) bodies. An absent member leaves the field untouched,
+//! while `null` asks for it to be removed.
+
+use serde::{de::Error, Deserialize, Deserializer};
+
+/// Deserializes a member that cannot be removed, rejecting `null`. Use it along with
+/// `#[serde(default)]` so an absent member yields `None`.
+pub fn required_member<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    Option::<T>::deserialize(deserializer)?
+        .map(Some)
+        .ok_or_else(|| D::Error::custom("member cannot be removed"))
+}
+
+/// Deserializes a member whose removal clears it, turning `null` into its empty value. Use it
+/// along with `#[serde(default)]` so an absent member yields `None`.
+pub fn clearable_member<'de, D: Deserializer<'de>, T: Deserialize<'de> + Default>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    Ok(Some(
+        Option::<T>::deserialize(deserializer)?.unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Patch {
+        #[serde(default, deserialize_with = "required_member")]
+        title: Option<String>,
+        #[serde(default, deserialize_with = "clearable_member")]
+        tags: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn test_absent_members_are_untouched() {
+        let patch = serde_json::from_str::<Patch>("{}").unwrap();
+
+        assert_eq!(patch.title, None);
+        assert_eq!(patch.tags, None);
+    }
+
+    #[test]
+    fn test_null_clears_clearable_members() {
+        let patch = serde_json::from_str::<Patch>(r#"{"title": "hello", "tags": null}"#).unwrap();
+
+        assert_eq!(patch.title.as_deref(), Some("hello"));
+        assert_eq!(patch.tags, Some(vec![]));
+    }
+
+    #[test]
+    fn test_null_is_rejected_for_required_members() {
+        assert!(serde_json::from_str::<Patch>(r#"{"title": null}"#).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{de::Error, Deserialize, Deserializer};
+use std::{fmt::Display, str::FromStr};
+
+/// Deserializes a comma separated query parameter, e.g. `?tags_any=rust,release`, into its
+/// trimmed, non-empty items, parsed with `FromStr`. A missing parameter yields an empty list.
+pub fn comma_separated<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+
+    value
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| item.parse().map_err(D::Error::custom))
+                .collect()
+        })
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::web;
+
+    #[derive(Deserialize)]
+    struct Query {
+        #[serde(default, deserialize_with = "comma_separated")]
+        tags: Vec<String>,
+
+        #[serde(default, deserialize_with = "comma_separated")]
+        limits: Vec<u8>,
+    }
+
+    #[test]
+    fn test_comma_separated_splits_and_trims() {
+        let query = web::Query::<Query>::from_query("tags=rust,%20release,,").unwrap();
+
+        assert_eq!(query.tags, vec!["rust", "release"]);
+    }
+
+    #[test]
+    fn test_comma_separated_missing_parameter() {
+        let query = web::Query::<Query>::from_query("").unwrap();
+
+        assert!(query.tags.is_empty());
+    }
+
+    #[test]
+    fn test_comma_separated_parses_items() {
+        let query = web::Query::<Query>::from_query("limits=1,%202").unwrap();
+
+        assert_eq!(query.limits, vec![1, 2]);
+        assert!(web::Query::<Query>::from_query("limits=1,many").is_err());
+    }
+}
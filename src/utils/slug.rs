@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+/// Maximum slug length, in characters.
+pub const MAX_SLUG_LENGTH: usize = 96;
+
+/// Slug used when a text has nothing left to transliterate, e.g. only punctuation.
+const FALLBACK_SLUG: &str = "post";
+
+/// Whether `slug` is made of lowercase ASCII letters and digits, in words joined by single
+/// hyphens.
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Slug of `text`, transliterated to ASCII, e.g. `"Ça va, Zoë?"` becomes `"ca-va-zoe"`. Long
+/// slugs are cut at a word boundary to fit `MAX_SLUG_LENGTH`.
+pub fn slugify(text: &str) -> String {
+    let ascii = any_ascii::any_ascii(text).to_ascii_lowercase();
+    let mut slug = String::with_capacity(ascii.len());
+
+    for word in ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if slug.len() + word.len() + 1 > MAX_SLUG_LENGTH {
+            if slug.is_empty() {
+                slug.push_str(&word[..MAX_SLUG_LENGTH]);
+            }
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+
+    if slug.is_empty() {
+        FALLBACK_SLUG.to_string()
+    } else {
+        slug
+    }
+}
+
+/// `slug` if it is not `taken` yet, otherwise the first of `slug-2`, `slug-3`... that is not.
+pub fn available_slug(slug: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(slug) {
+        return slug.to_string();
+    }
+
+    (2..)
+        .map(|suffix| {
+            let suffix = format!("-{suffix}");
+            let base = &slug[..slug.len().min(MAX_SLUG_LENGTH - suffix.len())];
+
+            format!("{}{suffix}", base.trim_end_matches('-'))
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .expect("a free suffix among the finitely many taken slugs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_transliterates() {
+        assert_eq!(slugify("Ça va, Zoë?"), "ca-va-zoe");
+        assert_eq!(slugify("  Rust 1.75 -- released!  "), "rust-1-75-released");
+        assert_eq!(slugify("日本語"), "ribenyu");
+    }
+
+    #[test]
+    fn test_slugify_falls_back_when_nothing_is_left() {
+        assert_eq!(slugify("!!!"), FALLBACK_SLUG);
+    }
+
+    #[test]
+    fn test_slugify_cuts_long_slugs_at_word_boundaries() {
+        let slug = slugify(&"word ".repeat(MAX_SLUG_LENGTH));
+
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("word"));
+        assert_eq!(
+            slugify(&"a".repeat(MAX_SLUG_LENGTH + 1)).len(),
+            MAX_SLUG_LENGTH
+        );
+    }
+
+    #[test]
+    fn test_valid_slugs() {
+        assert!(is_valid_slug("hello-world-2"));
+        for slug in [
+            "",
+            "Hello",
+            "hello--world",
+            "-hello",
+            "hello-",
+            "héllo",
+            "hello_world",
+        ] {
+            assert!(!is_valid_slug(slug), "{slug}");
+        }
+    }
+
+    #[test]
+    fn test_available_slug_appends_a_free_suffix() {
+        let taken = HashSet::from(["hello".to_string(), "hello-2".to_string()]);
+
+        assert_eq!(available_slug("world", &taken), "world");
+        assert_eq!(available_slug("hello", &taken), "hello-3");
+    }
+
+    #[test]
+    fn test_available_slug_fits_the_suffix() {
+        let slug = "a".repeat(MAX_SLUG_LENGTH);
+        let taken = HashSet::from([slug.clone()]);
+
+        let available = available_slug(&slug, &taken);
+
+        assert_eq!(available.len(), MAX_SLUG_LENGTH);
+        assert!(available.ends_with("-2"));
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    ffi::{c_char, c_int, CString},
+    fs,
+    io::{self, ErrorKind},
+    mem::MaybeUninit,
+    os::unix::{
+        fs::{chown, PermissionsExt},
+        net::UnixListener,
+    },
+    path::Path,
+    process, ptr,
+};
+
+/// Largest buffer handed to the user and group database lookups.
+const MAX_LOOKUP_BUFFER: usize = 1 << 20;
+
+/// Owner, group and mode given to a unix socket, each left as created when `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketPermissions {
+    pub owner: Option<u32>,
+    pub group: Option<u32>,
+    pub mode: Option<u32>,
+}
+
+/// Listens on a unix socket at `path` with `permissions`, replacing any socket left there.
+///
+/// The socket is bound next to `path` and renamed into place once its permissions are set, so
+/// clients never get to connect to it with the permissions it was created with.
+pub fn bind_unix_socket(path: &Path, permissions: SocketPermissions) -> io::Result<UnixListener> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "Socket path has no file name"))?;
+    let staging = path.with_file_name(format!(
+        ".{}.{}",
+        file_name.to_string_lossy(),
+        process::id()
+    ));
+
+    match fs::remove_file(&staging) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let listener = UnixListener::bind(&staging)?;
+
+    let prepared = chown(&staging, permissions.owner, permissions.group)
+        .and_then(|()| match permissions.mode {
+            Some(mode) => fs::set_permissions(&staging, fs::Permissions::from_mode(mode)),
+            None => Ok(()),
+        })
+        .and_then(|()| fs::rename(&staging, path));
+    if let Err(e) = prepared {
+        let _ = fs::remove_file(&staging);
+        return Err(e);
+    }
+
+    Ok(listener)
+}
+
+/// Id of the user named `user`, which may also be given by id.
+pub fn user_id(user: &str) -> io::Result<u32> {
+    if let Ok(id) = user.parse() {
+        return Ok(id);
+    }
+
+    let name = c_name(user)?;
+    lookup("user", user, |buffer| {
+        let mut entry = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = ptr::null_mut();
+        // SAFETY: every pointer outlives the call and `buffer` is as long as its given length.
+        // `result` is either null or points to `entry`, which the call then initialized.
+        unsafe {
+            let code = libc::getpwnam_r(
+                name.as_ptr(),
+                entry.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            );
+            (code, (!result.is_null()).then(|| (*result).pw_uid))
+        }
+    })
+}
+
+/// Id of the group named `group`, which may also be given by id.
+pub fn group_id(group: &str) -> io::Result<u32> {
+    if let Ok(id) = group.parse() {
+        return Ok(id);
+    }
+
+    let name = c_name(group)?;
+    lookup("group", group, |buffer| {
+        let mut entry = MaybeUninit::<libc::group>::uninit();
+        let mut result = ptr::null_mut();
+        // SAFETY: as in `user_id`.
+        unsafe {
+            let code = libc::getgrnam_r(
+                name.as_ptr(),
+                entry.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            );
+            (code, (!result.is_null()).then(|| (*result).gr_gid))
+        }
+    })
+}
+
+fn c_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Name contains a NUL"))
+}
+
+/// Runs a `get*nam_r` style lookup, growing its buffer until the entry fits in it.
+fn lookup(
+    kind: &str,
+    name: &str,
+    call: impl Fn(&mut [c_char]) -> (c_int, Option<u32>),
+) -> io::Result<u32> {
+    let mut buffer = vec![0; 1024];
+
+    loop {
+        match call(&mut buffer) {
+            (0, Some(id)) => return Ok(id),
+            (0, None) => {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("Unknown {kind}: {name}"),
+                ))
+            }
+            (libc::ERANGE, _) if buffer.len() < MAX_LOOKUP_BUFFER => {
+                buffer.resize(buffer.len() * 2, 0)
+            }
+            (code, _) => return Err(io::Error::from_raw_os_error(code)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn test_ids_are_parsed_or_looked_up() {
+        assert_eq!(user_id("1000").unwrap(), 1000);
+        assert_eq!(user_id("root").unwrap(), 0);
+        assert_eq!(group_id("1000").unwrap(), 1000);
+        assert_eq!(group_id("root").unwrap(), 0);
+
+        assert_eq!(
+            user_id("no-such-user-here").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            group_id("no-such-group-here").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_sockets_are_bound_with_permissions() {
+        let path = std::env::temp_dir().join(format!("iemanjad-test-{}.sock", process::id()));
+        fs::write(&path, "stale").unwrap();
+
+        let listener = bind_unix_socket(
+            &path,
+            SocketPermissions {
+                mode: Some(0o640),
+                ..Default::default()
+            },
+        );
+        let metadata = fs::metadata(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(listener.is_ok());
+        assert_eq!(metadata.unwrap().mode() & 0o777, 0o640);
+    }
+}